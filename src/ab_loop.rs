@@ -0,0 +1,93 @@
+/* --------------------------
+A-B loop
+    - Repeats a section of the current track through mpv's `ab-loop-a` and `ab-loop-b`, handy for
+      practicing along with a part
+    - One key cycles through: set A, set B, clear. Changing tracks clears the loop
+    - Points are taken from mpv directly, the playback state position is only updated once a second
+-------------------------- */
+
+use crate::helpers::format_timestamp;
+use crate::mpv::SeekFlag;
+use crate::tui::App;
+
+/// A loop shorter than this is most likely a double press
+const AB_LOOP_MIN_SECS: f64 = 0.5;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AbLoop {
+    pub a: Option<f64>,
+    pub b: Option<f64>,
+}
+
+impl AbLoop {
+    /// Short description for the player, `None` when there's no loop
+    ///
+    pub fn label(&self) -> Option<String> {
+        match (self.a, self.b) {
+            (Some(a), Some(b)) => {
+                Some(format!("A-B {} – {}", format_timestamp(a), format_timestamp(b)))
+            }
+            (Some(a), None) => Some(format!("A {} – B ?", format_timestamp(a))),
+            _ => None,
+        }
+    }
+}
+
+impl App {
+    pub async fn cycle_ab_loop(&mut self) {
+        if self.stopped {
+            return;
+        }
+        let position = match self.mpv_handle.position().await {
+            Some(position) => position,
+            None => self.state.current_playback_state.position,
+        };
+        self.ab_loop = match self.ab_loop {
+            AbLoop { a: None, .. } => AbLoop { a: Some(position), b: None },
+            AbLoop { a: Some(a), b: None } => {
+                if (position - a).abs() < AB_LOOP_MIN_SECS {
+                    return;
+                }
+                // B before A just means the points were set the other way around
+                AbLoop { a: Some(a.min(position)), b: Some(a.max(position)) }
+            }
+            AbLoop { .. } => AbLoop::default(),
+        };
+        // mpv only gets the loop once both points are known
+        match self.ab_loop {
+            AbLoop { a: Some(a), b: Some(b) } => {
+                self.mpv_handle.set_ab_loop(Some(a), Some(b)).await;
+                self.mpv_handle.seek(a, SeekFlag::Absolute).await;
+                self.state.current_playback_state.position = a;
+                self.update_mpris_position(a);
+            }
+            _ => self.mpv_handle.set_ab_loop(None, None).await,
+        }
+        self.dirty = true;
+    }
+
+    /// Called on every song change, a loop only makes sense within its own track
+    ///
+    pub async fn clear_ab_loop(&mut self) {
+        if self.ab_loop != AbLoop::default() {
+            self.ab_loop = AbLoop::default();
+            self.mpv_handle.set_ab_loop(None, None).await;
+            self.dirty = true;
+        }
+    }
+
+    /// Seeks to a position in the current track, used by the seek to timestamp popup
+    ///
+    pub async fn seek_to(&mut self, target: f64) {
+        if self.stopped {
+            return;
+        }
+        let duration = self.state.current_playback_state.duration;
+        let target = if duration > 0.0 { target.min(duration - 1.0).max(0.0) } else { target };
+        self.state.current_playback_state.position = target;
+        self.update_mpris_position(target);
+        self.mpv_handle.seek(target, SeekFlag::Absolute).await;
+        let _ = self.handle_discord(true).await;
+        self.dirty = true;
+    }
+}
@@ -0,0 +1,78 @@
+/* --------------------------
+Audio output
+    - Lists mpv's `audio-device-list` in the global popup and switches outputs live
+    - The chosen device is remembered in the preferences, which are per machine. When it's not
+      connected on startup mpv keeps using the configured (or automatic) device
+    - Optionally every device keeps its own volume level
+-------------------------- */
+
+use crate::tui::App;
+
+/// mpv's own pick, also what the equalizer settings are stored under when no device is chosen
+const DEFAULT_AUDIO_DEVICE: &str = "auto";
+
+/// The device set through the `mpv:` config section, which mpv starts with
+///
+pub fn configured_audio_device(config: &serde_yaml::Value) -> String {
+    config["mpv"]["audio-device"].as_str().unwrap_or(DEFAULT_AUDIO_DEVICE).to_string()
+}
+
+impl App {
+    /// The output device mpv is playing to
+    ///
+    pub fn current_audio_device(&self) -> String {
+        self.audio_device.clone()
+    }
+
+    /// Switches to the preferred device if it's connected. Called on startup and after mpv restarts
+    ///
+    pub async fn restore_audio_device(&mut self) {
+        let Some(preferred) = self.preferences.audio_device.clone() else {
+            return;
+        };
+        let devices = self.mpv_handle.audio_devices().await;
+        if devices.iter().any(|d| d.name == preferred) {
+            self.mpv_handle.set_audio_device(preferred.clone()).await;
+            self.audio_device = preferred;
+        } else {
+            log::warn!(
+                "Preferred audio device '{}' is not available, using '{}'",
+                preferred,
+                self.audio_device
+            );
+        }
+    }
+
+    pub async fn switch_audio_device(&mut self, name: String) {
+        if name == self.audio_device {
+            return;
+        }
+        if self.preferences.per_device_volume {
+            self.preferences
+                .device_volumes
+                .insert(self.audio_device.clone(), self.state.current_playback_state.volume);
+        }
+
+        self.mpv_handle.set_audio_device(name.clone()).await;
+        self.audio_device = name.clone();
+        self.preferences.audio_device = Some(name.clone());
+
+        if self.preferences.per_device_volume {
+            if let Some(volume) = self.preferences.device_volumes.get(&name).copied() {
+                self.state.current_playback_state.volume = volume;
+                self.mpv_handle.set_volume(volume).await;
+                #[cfg(target_os = "linux")]
+                {
+                    if let Some(ref mut controls) = self.controls {
+                        let _ = controls.set_volume(volume as f64 / 100.0);
+                    }
+                }
+            }
+        }
+        if let Err(e) = self.preferences.save() {
+            log::error!("Failed to save preferences: {}", e);
+        }
+        // every device has its own equalizer settings
+        self.apply_equalizer().await;
+    }
+}
@@ -8,6 +8,7 @@ HTTP client for Jellyfin API
 
 use crate::database::extension::DownloadStatus;
 use crate::keyboard::Searchable;
+use crate::radio::RadioSeed;
 use dirs::data_dir;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
@@ -43,6 +44,13 @@ pub struct SelectedServer {
     pub auth: AuthMethod,
 }
 
+/// Containers mpv plays as they are, the server only transcodes other ones (or when over the bitrate limit)
+pub const DIRECT_PLAY_CONTAINERS: &str =
+    "opus,webm|opus,mp3,aac,m4a|aac,m4a|alac,m4b|aac,flac,webma,webm|webma,wav,ogg,wv|wavpack";
+
+/// Playlist ids go into the query string, so bulk changes are split to keep each request reasonably short
+const PLAYLIST_IDS_PER_REQUEST: usize = 100;
+
 #[derive(Debug)]
 pub struct Transcoding {
     pub enabled: bool,
@@ -423,6 +431,43 @@ impl Client {
         Ok(albums.items)
     }
 
+    /// Albums for the home tab: the latest additions, or the favorites when `favorites` is set
+    ///
+    pub async fn home_albums(
+        &self,
+        favorites: bool,
+        limit: usize,
+    ) -> Result<Vec<Album>, reqwest::Error> {
+        let url = format!("{}/Users/{}/Items", self.base_url, self.user_id);
+        let (sort_by, sort_order, filters) = match favorites {
+            true => ("SortName", "Ascending", "IsFavorite"),
+            false => ("DateCreated,SortName", "Descending", ""),
+        };
+
+        let response = self
+            .http_client
+            .get(url)
+            .header("X-MediaBrowser-Token", self.access_token.to_string())
+            .header(self.authorization_header.0.as_str(), self.authorization_header.1.as_str())
+            .header("Content-Type", "application/json")
+            .query(&[
+                ("SortBy", sort_by),
+                ("SortOrder", sort_order),
+                ("Filters", filters),
+                ("Recursive", "true"),
+                ("IncludeItemTypes", "MusicAlbum"),
+                ("Fields", "DateCreated,ParentId,ProductionYear,PremiereDate"),
+                ("StartIndex", "0"),
+                ("Limit", &limit.to_string()),
+            ])
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let albums: Albums = response.json().await?;
+        Ok(albums.items)
+    }
+
     /// Produces a list of songs in an album
     ///
     pub async fn album_tracks(&self, id: &str) -> Result<Vec<DiscographySong>, reqwest::Error> {
@@ -663,6 +708,86 @@ impl Client {
         Ok(songs)
     }
 
+    /// Tracks the user has played, on any client. `most_played` sorts by play count,
+    /// otherwise the most recently played come first
+    ///
+    pub async fn played_tracks(
+        &self,
+        most_played: bool,
+        limit: usize,
+    ) -> Result<Vec<DiscographySong>, reqwest::Error> {
+        let url = format!("{}/Users/{}/Items", self.base_url, self.user_id);
+
+        let response = self
+            .http_client
+            .get(url)
+            .header("X-MediaBrowser-Token", self.access_token.to_string())
+            .header(self.authorization_header.0.as_str(), self.authorization_header.1.as_str())
+            .header("Content-Type", "application/json")
+            .query(&[
+                ("SortBy", if most_played { "PlayCount,DatePlayed" } else { "DatePlayed" }),
+                ("SortOrder", "Descending"),
+                ("Filters", "IsPlayed"),
+                ("Recursive", "true"),
+                ("IncludeItemTypes", "Audio"),
+                ("Fields", "Genres, DateCreated, MediaSources, ParentId"),
+                ("ImageTypeLimit", "1"),
+                ("StartIndex", "0"),
+                ("Limit", &limit.to_string()),
+            ])
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let songs: Discography = response.json().await?;
+        Ok(songs.items.into_iter().filter(|s| !s.album_artists.is_empty()).collect())
+    }
+
+    /// Tracks similar to the seed item, picked by Jellyfin's InstantMix
+    ///
+    pub async fn instant_mix(
+        &self,
+        seed: &RadioSeed,
+        limit: usize,
+    ) -> Result<Vec<DiscographySong>, Box<dyn Error>> {
+        let url = match seed {
+            RadioSeed::Genre { name } => {
+                // genre names can contain anything, let url do the escaping
+                let mut url = url::Url::parse(&self.base_url)?;
+                url.path_segments_mut().map_err(|_| "invalid server url")?.pop_if_empty().extend([
+                    "MusicGenres",
+                    name.as_str(),
+                    "InstantMix",
+                ]);
+                url.to_string()
+            }
+            RadioSeed::Track { id, .. }
+            | RadioSeed::Album { id, .. }
+            | RadioSeed::Artist { id, .. } => {
+                format!("{}/Items/{}/InstantMix", self.base_url, id)
+            }
+        };
+
+        let response = self
+            .http_client
+            .get(url)
+            .header("X-MediaBrowser-Token", self.access_token.to_string())
+            .header(self.authorization_header.0.as_str(), self.authorization_header.1.as_str())
+            .header("Content-Type", "text/json")
+            .query(&[
+                ("UserId", self.user_id.as_str()),
+                ("Fields", "Genres, DateCreated, MediaSources, ParentId"),
+                ("ImageTypeLimit", "1"),
+                ("Limit", &limit.to_string()),
+            ])
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let songs: Discography = response.json().await?;
+        Ok(songs.items.into_iter().filter(|s| !s.album_artists.is_empty()).collect())
+    }
+
     /// Returns a list of artists with recently added albums
     ///
     // pub async fn new_artists(&self) -> Result<Vec<String>, Box<dyn Error>> {
@@ -842,7 +967,7 @@ impl Client {
             "?UserId={}&api_key={}&StartTimeTicks=0&EnableRedirection=true&EnableRemoteMedia=false",
             self.user_id, self.access_token
         );
-        url += "&container=opus,webm|opus,mp3,aac,m4a|aac,m4a|alac,m4b|aac,flac,webma,webm|webma,wav,ogg,wv|wavpack";
+        url += &format!("&container={}", DIRECT_PLAY_CONTAINERS);
 
         if transcoding.enabled {
             url += &format!(
@@ -1116,6 +1241,19 @@ impl Client {
             .await
     }
 
+    /// Adds any number of tracks to a playlist, stops at the first request that fails
+    ///
+    pub async fn add_to_playlist_chunked(
+        &self,
+        track_ids: &[String],
+        playlist_id: &String,
+    ) -> Result<(), reqwest::Error> {
+        for chunk in track_ids.chunks(PLAYLIST_IDS_PER_REQUEST) {
+            self.add_to_playlist(&chunk.join(","), playlist_id).await?.error_for_status()?;
+        }
+        Ok(())
+    }
+
     /// Removes a track from a playlist
     ///
     pub async fn remove_from_playlist(
@@ -1134,6 +1272,20 @@ impl Client {
             .send()
             .await
     }
+
+    /// Removes any number of playlist entries, stops at the first request that fails
+    ///
+    pub async fn remove_from_playlist_chunked(
+        &self,
+        entry_ids: &[String],
+        playlist_id: &String,
+    ) -> Result<(), reqwest::Error> {
+        for chunk in entry_ids.chunks(PLAYLIST_IDS_PER_REQUEST) {
+            self.remove_from_playlist(&chunk.join(","), playlist_id).await?.error_for_status()?;
+        }
+        Ok(())
+    }
+
     // POST /Playlists/{playlistId}/Items/{itemId}/Move/{newIndex}
     pub async fn move_playlist_item(
         &self,
@@ -1200,22 +1352,73 @@ impl Client {
             .await
     }
 
-    /// Sends a 'playing' event to the server
+    /// Registers this session as remote controllable, so other clients can send it commands
     ///
-    pub async fn playing(&self, song_id: &String) -> Result<(), reqwest::Error> {
-        let url = format!("{}/Sessions/Playing", self.base_url);
-        let _response = self
-            .http_client
+    pub async fn post_capabilities(&self) -> Result<(), reqwest::Error> {
+        let url = format!("{}/Sessions/Capabilities/Full", self.base_url);
+        self.http_client
             .post(url)
-            .header("X-MediaBrowser-Token", self.access_token.to_string())
+            .timeout(Duration::from_secs(5))
+            .header("X-MediaBrowser-Token", &self.access_token)
             .header(self.authorization_header.0.as_str(), self.authorization_header.1.as_str())
             .header("Content-Type", "application/json")
             .json(&serde_json::json!({
-                "ItemId": song_id,
-                "PositionTicks": 0
+                "PlayableMediaTypes": ["Audio"],
+                "SupportedCommands": ["SetVolume"],
+                "SupportsMediaControl": true,
+                "SupportsPersistentIdentifier": false,
             }))
             .send()
-            .await;
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+
+    /// WebSocket endpoint the server pushes remote control commands to
+    ///
+    pub fn websocket_url(&self) -> String {
+        let base = if let Some(rest) = self.base_url.strip_prefix("https://") {
+            format!("wss://{}", rest)
+        } else if let Some(rest) = self.base_url.strip_prefix("http://") {
+            format!("ws://{}", rest)
+        } else {
+            format!("ws://{}", self.base_url)
+        };
+        format!(
+            "{}/socket?api_key={}&deviceId={}",
+            base.trim_end_matches('/'),
+            self.access_token,
+            self.device_id
+        )
+    }
+
+    /// Sends a 'playing' event to the server. `started_at` is the unix time in milliseconds, for
+    /// plays reported after the fact
+    ///
+    pub async fn playing(
+        &self,
+        song_id: &String,
+        started_at: Option<i64>,
+    ) -> Result<(), reqwest::Error> {
+        let url = format!("{}/Sessions/Playing", self.base_url);
+        let mut body = serde_json::json!({
+            "ItemId": song_id,
+            "PositionTicks": 0
+        });
+        if let Some(started_at) = started_at {
+            // same ticks as the web client sends, 100ns since the unix epoch
+            body["PlaybackStartTimeTicks"] = serde_json::json!(started_at * 10_000);
+        }
+        self.http_client
+            .post(url)
+            .header("X-MediaBrowser-Token", self.access_token.to_string())
+            .header(self.authorization_header.0.as_str(), self.authorization_header.1.as_str())
+            .header("Content-Type", "application/json")
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?;
 
         Ok(())
     }
@@ -1237,8 +1440,7 @@ impl Client {
             body.insert("PositionTicks".into(), serde_json::Value::Number(ticks.into()));
         }
 
-        let _ = self
-            .http_client
+        self.http_client
             .post(url)
             .timeout(Duration::from_millis(300))
             .header("X-MediaBrowser-Token", &self.access_token)
@@ -1246,7 +1448,8 @@ impl Client {
             .header("Content-Type", "application/json")
             .json(&body)
             .send()
-            .await?;
+            .await?
+            .error_for_status()?;
 
         Ok(())
     }
@@ -1257,7 +1460,7 @@ impl Client {
         let url = format!("{}/Sessions/Playing/Progress", self.base_url);
         // new http client, this is a pure function so we can create a new one
         let client = reqwest::Client::new();
-        let _response = client
+        client
             .post(url)
             .header("X-MediaBrowser-Token", self.access_token.to_string())
             .header(self.authorization_header.0.as_str(), self.authorization_header.1.as_str())
@@ -1269,7 +1472,7 @@ impl Client {
                 "ShuffleMode": "Sorted",
                 "PositionTicks": pr.position_ticks,
                 // "PlaybackStartTimeTicks": pr.playback_start_time_ticks,
-                "PlaybackRate": 1,
+                "PlaybackRate": pr.playback_rate,
                 "SecondarySubtitleStreamIndex": -1,
                 // "BufferedRanges": [{"start": 0, "end": 1457709999.9999998}],
                 "MediaSourceId": pr.media_source_id,
@@ -1278,7 +1481,8 @@ impl Client {
                 "EventName": "timeupdate"
             }))
             .send()
-            .await;
+            .await?
+            .error_for_status()?;
 
         Ok(())
     }
@@ -1551,11 +1755,15 @@ struct ViewsResponse {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct MediaSource {
     #[serde(rename = "Container", default)]
-    container: String,
+    pub container: String,
     #[serde(rename = "Size", default)]
-    size: u64,
+    pub size: u64,
+    #[serde(rename = "Path", default)]
+    pub path: String,
+    #[serde(rename = "Bitrate", default)]
+    pub bitrate: u64,
     #[serde(rename = "MediaStreams", default)]
-    media_streams: Vec<MediaStream>,
+    pub media_streams: Vec<MediaStream>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -1568,8 +1776,12 @@ pub struct MediaStream {
     pub channels: u64,
     #[serde(rename = "SampleRate", default)]
     pub sample_rate: u64,
+    #[serde(rename = "BitDepth", default)]
+    pub bit_depth: u64,
+    #[serde(rename = "ChannelLayout", default)]
+    pub channel_layout: String,
     #[serde(rename = "Type", default)]
-    type_: String,
+    pub type_: String,
 }
 
 /// Lyrics
@@ -1623,8 +1835,8 @@ pub struct ProgressReport {
     pub position_ticks: u64,
     #[serde(rename = "PlaybackStartTimeTicks")]
     pub playback_start_time_ticks: u64,
-    // #[serde(rename = "PlaybackRate")]
-    // playback_rate: u64,
+    #[serde(rename = "PlaybackRate")]
+    pub playback_rate: f64,
     // #[serde(rename = "SecondarySubtitleStreamIndex")]
     // secondary_subtitle_stream_index: i64,
     // #[serde(rename = "PlayMethod")]
@@ -1671,6 +1883,8 @@ pub struct Album {
     // pub production_year: Option<String>,
     #[serde(rename = "PremiereDate", default)]
     pub premiere_date: String,
+    #[serde(rename = "NormalizationGain", default)]
+    pub normalization_gain: f64,
 }
 
 impl Searchable for Album {
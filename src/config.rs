@@ -35,6 +35,104 @@ impl LyricsVisibility {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplayGainMode {
+    Off,
+    Track,
+    Album,
+}
+
+/// Volume normalization based on the NormalizationGain Jellyfin computes for tracks and albums.
+#[derive(Debug, Clone, Copy)]
+pub struct ReplayGain {
+    pub mode: ReplayGainMode,
+    pub preamp: f64, // dB, added on top of the gain
+    // Jellyfin does not report sample peaks, so this clamps the gain to 0 dB: quiet tracks are
+    // never boosted and both modes only attenuate. Without it positive gain is applied as is
+    pub prevent_clipping: bool,
+}
+impl ReplayGain {
+    pub fn from_config(config: &serde_yaml::Value) -> Self {
+        let replaygain = &config["replaygain"];
+        let mode = match replaygain["mode"].as_str() {
+            Some("track") => ReplayGainMode::Track,
+            Some("album") => ReplayGainMode::Album,
+            Some("off") | None => ReplayGainMode::Off,
+            Some(other) => {
+                log::warn!("Unknown replaygain mode '{}', disabling replaygain", other);
+                ReplayGainMode::Off
+            }
+        };
+        Self {
+            mode,
+            preamp: replaygain["preamp"].as_f64().unwrap_or(0.0),
+            prevent_clipping: replaygain["prevent_clipping"].as_bool().unwrap_or(true),
+        }
+    }
+
+    /// Final gain in dB passed to mpv. `None` if disabled or the server hasn't computed a gain.
+    /// With `prevent_clipping` it is never positive, there are no peaks to limit a boost by.
+    pub fn gain_db(&self, track_gain: f64, album_gain: f64) -> Option<f64> {
+        let gain = match self.mode {
+            ReplayGainMode::Off => return None,
+            ReplayGainMode::Track => track_gain,
+            // fall back to the track gain for albums the server hasn't analyzed
+            ReplayGainMode::Album if album_gain != 0.0 => album_gain,
+            ReplayGainMode::Album => track_gain,
+        };
+        if gain == 0.0 {
+            return None;
+        }
+        let gain = gain + self.preamp;
+        Some(if self.prevent_clipping { gain.min(0.0) } else { gain })
+    }
+}
+
+/// Crossfades between tracks and short fades on pause, resume and stop. Both are done in the mpv
+/// thread by the fader, these are just the settings it gets.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Transitions {
+    pub crossfade: f64,     // seconds, 0 disables
+    pub within_album: bool, // also crossfade between tracks of the same album
+    pub pause_fade: f64,    // seconds, 0 disables
+}
+impl Transitions {
+    /// The configured durations. Whether they are used is up to `enabled` and the global popup toggle
+    pub fn from_config(config: &serde_yaml::Value) -> (Self, bool) {
+        let transitions = &config["transitions"];
+        let seconds = |key: &str, default: f64| {
+            transitions[key].as_f64().map_or(default, |v| v.clamp(0.0, 30.0))
+        };
+        let settings = Self {
+            crossfade: seconds("crossfade", 5.0),
+            within_album: transitions["within_album"].as_bool().unwrap_or(false),
+            pause_fade: transitions["pause_fade_ms"]
+                .as_f64()
+                .map_or(0.3, |ms| ms.clamp(0.0, 5000.0) / 1000.0),
+        };
+        (settings, transitions["enabled"].as_bool().unwrap_or(false))
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.crossfade > 0.0 || self.pause_fade > 0.0
+    }
+}
+
+/// How far the seek keys jump, in seconds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SeekSteps {
+    pub short: f64, // left / right
+    pub long: f64,  // , / .
+}
+impl SeekSteps {
+    pub fn from_config(config: &serde_yaml::Value) -> Self {
+        let steps = &config["seek_steps"];
+        let seconds =
+            |key: &str, default: f64| steps[key].as_f64().map_or(default, |v| v.clamp(1.0, 3600.0));
+        Self { short: seconds("short", 5.0), long: seconds("long", 60.0) }
+    }
+}
+
 /// This makes sure all dirs are created before we do anything.
 /// Also makes unwraps on dirs::data_dir and config_dir safe to do. In theory ;)
 pub fn prepare_directories() -> Result<(), Box<dyn std::error::Error>> {
@@ -485,3 +583,47 @@ pub fn update_cache_with_new_auth(
 
     cache
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replaygain(mode: ReplayGainMode, preamp: f64, prevent_clipping: bool) -> ReplayGain {
+        ReplayGain { mode, preamp, prevent_clipping }
+    }
+
+    #[test]
+    fn gain_follows_the_mode() {
+        let track = replaygain(ReplayGainMode::Track, 0.0, false);
+        assert_eq!(track.gain_db(-6.0, -4.0), Some(-6.0));
+
+        let album = replaygain(ReplayGainMode::Album, 0.0, false);
+        assert_eq!(album.gain_db(-6.0, -4.0), Some(-4.0));
+        // albums the server hasn't analyzed fall back to the track gain
+        assert_eq!(album.gain_db(-6.0, 0.0), Some(-6.0));
+
+        let off = replaygain(ReplayGainMode::Off, 3.0, false);
+        assert_eq!(off.gain_db(-6.0, -4.0), None);
+        // no gain computed yet
+        assert_eq!(track.gain_db(0.0, -4.0), None);
+    }
+
+    #[test]
+    fn preamp_is_added_on_top() {
+        let track = replaygain(ReplayGainMode::Track, 2.5, false);
+        assert_eq!(track.gain_db(-6.0, 0.0), Some(-3.5));
+        assert_eq!(track.gain_db(-1.0, 0.0), Some(1.5));
+    }
+
+    #[test]
+    fn prevent_clipping_never_boosts() {
+        let track = replaygain(ReplayGainMode::Track, 3.0, true);
+        assert_eq!(track.gain_db(-6.0, 0.0), Some(-3.0));
+        assert_eq!(track.gain_db(-1.0, 0.0), Some(0.0));
+        assert_eq!(track.gain_db(4.0, 0.0), Some(0.0));
+
+        let album = replaygain(ReplayGainMode::Album, 0.0, true);
+        assert_eq!(album.gain_db(-6.0, 2.0), Some(0.0));
+        assert_eq!(album.gain_db(-6.0, -2.0), Some(-2.0));
+    }
+}
@@ -0,0 +1,407 @@
+/* --------------------------
+Home tab
+    - A dashboard of shelves: recently added albums, recently played and most played tracks,
+      favorite albums and playlists to continue listening to
+    - Online the shelves come from Jellyfin, so plays on other clients count too. Offline they are
+      built from the local database and only hold downloaded music
+    - "Continue listening" lists the playlists of the recently played tracks, playing one picks up
+      after the last track played from it
+-------------------------- */
+
+use crate::client::{Album, Client, DiscographySong, Playlist};
+use crate::database::database::Status;
+use crate::database::extension::{get_played_tracks, get_playlist_tracks, get_playlists_of_tracks};
+use crate::tui::App;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{prelude::*, widgets::*, Frame};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::sync::Arc;
+
+/// How many items a shelf holds
+const SHELF_LIMIT: usize = 30;
+/// How many recently played tracks are looked at to find the playlists to continue
+const RECENT_TRACKS: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum Shelf {
+    #[default]
+    RecentlyAdded,
+    RecentlyPlayed,
+    MostPlayed,
+    FavoriteAlbums,
+    ContinueListening,
+}
+
+impl Shelf {
+    /// In the order <Tab> goes through them
+    pub const ALL: [Shelf; 5] = [
+        Shelf::RecentlyAdded,
+        Shelf::RecentlyPlayed,
+        Shelf::MostPlayed,
+        Shelf::FavoriteAlbums,
+        Shelf::ContinueListening,
+    ];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Shelf::RecentlyAdded => "Recently added",
+            Shelf::RecentlyPlayed => "Recently played",
+            Shelf::MostPlayed => "Most played",
+            Shelf::FavoriteAlbums => "Favorite albums",
+            Shelf::ContinueListening => "Continue listening",
+        }
+    }
+
+    fn empty_message(&self) -> &'static str {
+        match self {
+            Shelf::RecentlyAdded => "No albums yet",
+            Shelf::RecentlyPlayed | Shelf::MostPlayed => "Nothing played yet",
+            Shelf::FavoriteAlbums => "No favorite albums yet",
+            Shelf::ContinueListening => "No playlists played yet",
+        }
+    }
+
+    fn cycle(&self, forwards: bool) -> Shelf {
+        let i = *self as usize;
+        let n = Shelf::ALL.len();
+        Shelf::ALL[if forwards { (i + 1) % n } else { (i + n - 1) % n }]
+    }
+
+    /// The shelf in the other column, albums and playlists are on the left, tracks on the right
+    ///
+    fn beside(&self) -> Shelf {
+        match self {
+            Shelf::RecentlyAdded => Shelf::RecentlyPlayed,
+            Shelf::FavoriteAlbums | Shelf::ContinueListening => Shelf::MostPlayed,
+            Shelf::RecentlyPlayed => Shelf::RecentlyAdded,
+            Shelf::MostPlayed => Shelf::FavoriteAlbums,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ShelfItem {
+    Album(Album),
+    Track(DiscographySong),
+    /// The playlist and the last track played from it
+    Playlist(Playlist, String),
+}
+
+fn or_log<T: Default, E: std::fmt::Display>(result: Result<T, E>, what: &str) -> T {
+    result.unwrap_or_else(|e| {
+        log::error!("Failed to load {} for the home tab: {}", what, e);
+        T::default()
+    })
+}
+
+/// Fetches every shelf, online from Jellyfin and offline from the local database
+///
+async fn load_shelves(
+    client: Option<Arc<Client>>,
+    pool: Arc<SqlitePool>,
+    albums: Vec<Album>,
+) -> [Vec<ShelfItem>; 5] {
+    let (mut recently_added, favorite_albums, recently_played, most_played) = match client {
+        Some(client) => (
+            or_log(client.home_albums(false, SHELF_LIMIT).await, "recent albums"),
+            or_log(client.home_albums(true, SHELF_LIMIT).await, "favorite albums"),
+            or_log(client.played_tracks(false, RECENT_TRACKS).await, "recent tracks"),
+            or_log(client.played_tracks(true, SHELF_LIMIT).await, "most played tracks"),
+        ),
+        None => {
+            // offline the album list only holds albums with downloaded tracks
+            let mut favorites =
+                albums.iter().filter(|a| a.user_data.is_favorite).cloned().collect::<Vec<Album>>();
+            favorites.sort_by_key(|a| a.name.to_lowercase());
+            (
+                albums,
+                favorites,
+                or_log(get_played_tracks(&pool, false, RECENT_TRACKS).await, "recent tracks"),
+                or_log(get_played_tracks(&pool, true, SHELF_LIMIT).await, "most played tracks"),
+            )
+        }
+    };
+    // jellyfin dates are all in the same format, so they sort as strings
+    recently_added.sort_by(|a, b| b.date_created.cmp(&a.date_created));
+
+    let recent_ids = recently_played.iter().map(|t| t.id.clone()).collect::<Vec<String>>();
+    let playlists =
+        or_log(get_playlists_of_tracks(&pool, &recent_ids, SHELF_LIMIT).await, "playlists");
+
+    [
+        recently_added.into_iter().take(SHELF_LIMIT).map(ShelfItem::Album).collect(),
+        recently_played.into_iter().take(SHELF_LIMIT).map(ShelfItem::Track).collect(),
+        most_played.into_iter().map(ShelfItem::Track).collect(),
+        favorite_albums.into_iter().take(SHELF_LIMIT).map(ShelfItem::Album).collect(),
+        playlists.into_iter().map(|(p, last)| ShelfItem::Playlist(p, last)).collect(),
+    ]
+}
+
+impl App {
+    /// Reloads every shelf in the background. Called when the tab is opened, on startup if it
+    /// was open and when the play history or the library changed while it is open. A refresh
+    /// asked for while one is running is done once that one is in
+    ///
+    pub fn refresh_dashboard(&mut self) {
+        if self.dashboard_loading {
+            self.dashboard_stale = true;
+            return;
+        }
+        self.dashboard_loading = true;
+        self.dashboard_stale = false;
+
+        let client = self.client.clone();
+        let pool = self.db.pool.clone();
+        let albums = match client {
+            Some(_) => vec![],
+            None => self.original_albums.clone(),
+        };
+        let tx = self.db.status_tx.clone();
+        tokio::spawn(async move {
+            let shelves = load_shelves(client, pool, albums).await;
+            let _ = tx.send(Status::DashboardLoaded { shelves: Box::new(shelves) }).await;
+        });
+    }
+
+    /// Puts the fetched shelves in place, keeping the selection where it was
+    ///
+    pub fn dashboard_loaded(&mut self, shelves: [Vec<ShelfItem>; 5]) {
+        self.dashboard_loading = false;
+        self.shelves = shelves;
+        for shelf in Shelf::ALL {
+            let selected = self.state.selected_shelf_items[shelf as usize].selected().unwrap_or(0);
+            self.shelf_select_by_index(shelf, selected);
+        }
+        if self.dashboard_stale {
+            self.refresh_dashboard();
+        }
+    }
+
+    fn shelf_select_by_index(&mut self, shelf: Shelf, index: usize) {
+        let items = &self.shelves[shelf as usize];
+        let state = &mut self.state.selected_shelf_items[shelf as usize];
+        if items.is_empty() {
+            state.select(None);
+            return;
+        }
+        state.select(Some(index.min(items.len() - 1)));
+    }
+
+    fn move_shelf_selection(&mut self, delta: isize) {
+        let shelf = self.state.selected_shelf;
+        let current = self.state.selected_shelf_items[shelf as usize].selected().unwrap_or(0);
+        self.shelf_select_by_index(shelf, current.saturating_add_signed(delta));
+    }
+
+    fn selected_shelf_item(&self) -> Option<(usize, &ShelfItem)> {
+        let shelf = self.state.selected_shelf as usize;
+        let index = self.state.selected_shelf_items[shelf].selected()?;
+        Some((index, self.shelves[shelf].get(index)?))
+    }
+
+    /// The tracks to queue for the selected item and the one to start at. A track plays along with
+    /// the rest of its shelf, a playlist continues after the last track played from it
+    ///
+    async fn selected_shelf_tracks(&self) -> Option<(Vec<DiscographySong>, usize)> {
+        let (index, item) = self.selected_shelf_item()?;
+        match item {
+            ShelfItem::Track(_) => {
+                let tracks = self.shelves[self.state.selected_shelf as usize]
+                    .iter()
+                    .filter_map(|item| match item {
+                        ShelfItem::Track(track) => Some(track.clone()),
+                        _ => None,
+                    })
+                    .collect();
+                Some((tracks, index))
+            }
+            ShelfItem::Album(album) => Some((self.tracks_by_id(&album.id).await, 0)),
+            ShelfItem::Playlist(playlist, last_played) => {
+                let mut tracks =
+                    get_playlist_tracks(&self.db.pool, &playlist.id, self.client.as_ref())
+                        .await
+                        .unwrap_or_default();
+                if tracks.is_empty() {
+                    if let Some(client) = self.client.as_ref() {
+                        tracks = client.playlist(&playlist.id, None).await.ok()?.items;
+                    }
+                }
+                let start = match tracks.iter().position(|t| t.id == *last_played) {
+                    Some(i) if i + 1 < tracks.len() => i + 1,
+                    _ => 0,
+                };
+                Some((tracks, start))
+            }
+        }
+    }
+
+    /// Keys that behave differently in the Home tab. Returns false for everything else,
+    /// which is then handled like in any other tab (playback, volume, popups, tab switching...)
+    ///
+    pub async fn handle_dashboard_key_event(&mut self, key_event: KeyEvent) -> bool {
+        match key_event.code {
+            KeyCode::Down => self.move_shelf_selection(1),
+            KeyCode::Up => self.move_shelf_selection(-1),
+            KeyCode::PageDown => self.move_shelf_selection(self.left_list_height as isize),
+            KeyCode::PageUp => self.move_shelf_selection(-(self.left_list_height as isize)),
+            KeyCode::Char('g') => self.move_shelf_selection(isize::MIN),
+            KeyCode::Char('G') => self.move_shelf_selection(isize::MAX),
+            KeyCode::Tab => self.state.selected_shelf = self.state.selected_shelf.cycle(true),
+            KeyCode::BackTab => self.state.selected_shelf = self.state.selected_shelf.cycle(false),
+            KeyCode::Char('h') | KeyCode::Char('l') => {
+                self.state.selected_shelf = self.state.selected_shelf.beside();
+            }
+            KeyCode::Enter => {
+                if let Some((tracks, start)) = self.selected_shelf_tracks().await {
+                    self.initiate_main_queue(&tracks, start).await;
+                }
+            }
+            KeyCode::Char('e') => {
+                let Some((tracks, start)) = self.selected_shelf_tracks().await else {
+                    return true;
+                };
+                if tracks.is_empty() {
+                    return true;
+                }
+                let single = matches!(self.selected_shelf_item(), Some((_, ShelfItem::Track(_))));
+                match (key_event.modifiers == KeyModifiers::CONTROL, single) {
+                    (true, true) => self.push_next_to_temporary_queue(&tracks, start).await,
+                    (true, false) => self.push_all_next_to_temporary_queue(&tracks).await,
+                    (false, true) => self.push_to_temporary_queue(&tracks, start, 1).await,
+                    (false, false) => {
+                        self.push_to_temporary_queue(&tracks, 0, tracks.len()).await;
+                    }
+                }
+            }
+            // things that only make sense for the other tabs
+            KeyCode::Char('a')
+            | KeyCode::Char('A')
+            | KeyCode::Char('f')
+            | KeyCode::Char('d')
+            | KeyCode::Char('J')
+            | KeyCode::Char('K')
+            | KeyCode::Char('/')
+            | KeyCode::Delete => {}
+            _ => return false,
+        }
+        true
+    }
+
+    pub fn render_dashboard(&mut self, app_container: Rect, frame: &mut Frame) {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(app_container);
+        let left = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Ratio(1, 3); 3])
+            .split(columns[0]);
+        let right = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Ratio(1, 2); 2])
+            .split(columns[1]);
+
+        let areas = [left[0], right[0], right[1], left[1], left[2]];
+        for shelf in Shelf::ALL {
+            self.render_shelf(shelf, areas[shelf as usize], frame);
+        }
+    }
+
+    fn render_shelf(&mut self, shelf: Shelf, area: Rect, frame: &mut Frame) {
+        let foreground = self.theme.resolve(&self.theme.foreground);
+        let foreground_dim = self.theme.resolve(&self.theme.foreground_dim);
+        let section_title = self.theme.resolve(&self.theme.section_title);
+        let focused = self.state.selected_shelf == shelf;
+        let title_color = if focused { self.theme.primary_color } else { section_title };
+
+        let items = &self.shelves[shelf as usize];
+        let mut block = Block::new()
+            .borders(Borders::ALL)
+            .border_type(self.border_type)
+            .border_style(self.theme.resolve(if focused {
+                &self.theme.border_focused
+            } else {
+                &self.theme.border
+            }))
+            .title_top(Line::from(shelf.title()).fg(title_color).left_aligned())
+            .title_top(Line::from(format!("({})", items.len())).fg(title_color).right_aligned());
+        if focused {
+            self.left_list_height = block.inner(area).height.max(1) as usize;
+            block = block.title_bottom(
+                Line::from(vec![
+                    "<Enter>".fg(self.theme.primary_color).bold(),
+                    " play ".fg(section_title),
+                    "<e>".fg(self.theme.primary_color).bold(),
+                    " enqueue ".fg(section_title),
+                ])
+                .alignment(Alignment::Center),
+            );
+        }
+
+        if items.is_empty() {
+            let message = Paragraph::new(shelf.empty_message())
+                .fg(foreground_dim)
+                .block(block.padding(Padding::new(0, 0, area.height.saturating_sub(2) / 2, 0)))
+                .alignment(Alignment::Center);
+            frame.render_widget(message, area);
+            return;
+        }
+
+        let list_items = items
+            .iter()
+            .map(|item| {
+                let (name, detail) = match item {
+                    ShelfItem::Album(album) => (
+                        album.name.clone(),
+                        album
+                            .album_artists
+                            .iter()
+                            .map(|a| a.name.clone())
+                            .collect::<Vec<String>>()
+                            .join(", "),
+                    ),
+                    ShelfItem::Track(track) if shelf == Shelf::MostPlayed => (
+                        track.name.clone(),
+                        format!("{} - {} plays", track.album_artist, track.user_data.play_count),
+                    ),
+                    ShelfItem::Track(track) => (track.name.clone(), track.album_artist.clone()),
+                    ShelfItem::Playlist(playlist, _) => {
+                        (playlist.name.clone(), format!("{} tracks", playlist.child_count))
+                    }
+                };
+                let color = match item {
+                    ShelfItem::Track(track) if track.id == self.active_song_id => {
+                        self.theme.primary_color
+                    }
+                    _ => foreground,
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(name, Style::default().fg(color)),
+                    Span::styled(format!("  {}", detail), Style::default().fg(foreground_dim)),
+                ]))
+            })
+            .collect::<Vec<ListItem>>();
+
+        let highlight_style = match focused {
+            true => Style::default()
+                .bg(self.theme.resolve(&self.theme.selected_active_background))
+                .fg(self.theme.resolve(&self.theme.selected_active_foreground))
+                .add_modifier(Modifier::BOLD),
+            false => Style::default()
+                .bg(self.theme.resolve(&self.theme.selected_inactive_background))
+                .fg(self.theme.resolve(&self.theme.selected_inactive_foreground))
+                .add_modifier(Modifier::BOLD),
+        };
+        let list = List::new(list_items)
+            .block(block)
+            .highlight_symbol(">>")
+            .highlight_style(highlight_style)
+            .repeat_highlight_symbol(true);
+        frame.render_stateful_widget(
+            list,
+            area,
+            &mut self.state.selected_shelf_items[shelf as usize],
+        );
+    }
+}
@@ -1,7 +1,11 @@
 use super::extension::{
-    get_last_library_update, insert_lyrics, query_download_track, set_last_library_update,
+    count_queued_scrobbles, get_last_library_update, has_queued_scrobbles, insert_lyrics,
+    insert_play_history, next_queued_scrobble, query_download_track, queue_scrobble,
+    remove_queued_scrobble, set_last_library_update,
 };
 use crate::client::{NetworkQuality, ProgressReport, Transcoding};
+use crate::dashboard::ShelfItem;
+use crate::stats::PlayRecord;
 use crate::{
     client::{Artist, Client, DiscographySong},
     database::extension::{
@@ -35,6 +39,8 @@ pub enum Command {
     CancelDownloads,
     Jellyfin(JellyfinCommand),
     DislikeTrack { track_id: String, disliked: bool },
+    RecordPlay { play: PlayRecord },
+    ReplayScrobbles,
 }
 
 pub enum Status {
@@ -59,6 +65,9 @@ pub enum Status {
     AllDownloaded,
 
     NetworkQualityChanged(NetworkQuality),
+    PlayHistoryUpdated,
+    DashboardLoaded { shelves: Box<[Vec<ShelfItem>; 5]> },
+    ScrobbleOutbox { pending: i64, replayed: usize }, // pending reports, and how many were just sent
 
     Error { error: String },
 }
@@ -129,6 +138,12 @@ pub async fn t_database<'a>(
 
     if !online || client.is_none() {
         let mut active_task: Option<tokio::task::JoinHandle<()>> = None;
+        let _ = tx
+            .send(Status::ScrobbleOutbox {
+                pending: count_queued_scrobbles(&pool).await,
+                replayed: 0,
+            })
+            .await;
 
         loop {
             tokio::select! {
@@ -210,6 +225,15 @@ pub async fn t_database<'a>(
                                         log::error!("Failed to mark track {} as disliked: {}", track_id, e);
                                     }
                                 }
+                                Command::RecordPlay { play } => {
+                                    if let Err(e) = insert_play_history(&pool, &play).await {
+                                        log::error!("Failed to record play of {}: {}", play.track_id, e);
+                                    }
+                                    let _ = tx.send(Status::PlayHistoryUpdated).await;
+                                }
+                                Command::Jellyfin(jellyfin_cmd) => {
+                                    store_scrobble(&pool, &tx, &jellyfin_cmd).await;
+                                }
                                 _ => {
                                     log::warn!("Received unsupported command: {:?}", cmd);
                                 }
@@ -270,6 +294,15 @@ pub async fn t_database<'a>(
     let mut netcheck_interval = tokio::time::interval(Duration::from_secs(120));
     let mut last_quality = network_quality; // or NetworkQuality::Normal
 
+    // playback reports saved while offline are sent as soon as the server is reachable
+    let mut scrobble_task: Option<tokio::task::JoinHandle<()>> = None;
+    let _ = tx
+        .send(Status::ScrobbleOutbox { pending: count_queued_scrobbles(&pool).await, replayed: 0 })
+        .await;
+    if last_quality != NetworkQuality::CzechTrain {
+        replay_scrobbles(&mut scrobble_task, &pool, &tx, &client);
+    }
+
     loop {
         tokio::select! {
             Some(cmd) = rx.recv() => {
@@ -358,24 +391,20 @@ pub async fn t_database<'a>(
                         }
                     }
                     Command::Jellyfin(jellyfin_cmd) => {
-                        match jellyfin_cmd {
-                            JellyfinCommand::Stopped { id, position_ticks } => {
-                                if let Err(e) = client.stopped(id, position_ticks).await {
-                                    log::error!("Failed to send stopped report to jellyfin: {}", e);
-                                }
-                            }
-                            JellyfinCommand::Playing { id } => {
-                                if let Err(e) = client.playing(&id).await {
-                                    log::error!("Failed to send playing report to jellyfin: {}", e);
-                                }
-                            }
-                            JellyfinCommand::ReportProgress { progress_report } => {
-                                if let Err(e) = client.report_progress(&progress_report).await {
-                                    log::error!("Failed to report progress to jellyfin: {}", e);
-                                }
-                            }
+                        // while anything is waiting, reports line up behind it so the server gets them in order
+                        if last_quality == NetworkQuality::CzechTrain {
+                            store_scrobble(&pool, &tx, &jellyfin_cmd).await;
+                        } else if has_queued_scrobbles(&pool).await {
+                            store_scrobble(&pool, &tx, &jellyfin_cmd).await;
+                            replay_scrobbles(&mut scrobble_task, &pool, &tx, &client);
+                        } else if let Err(e) = send_jellyfin_command(&client, &jellyfin_cmd).await {
+                            log::warn!("Failed to report playback to jellyfin: {}", e);
+                            store_scrobble(&pool, &tx, &jellyfin_cmd).await;
                         }
                     }
+                    Command::ReplayScrobbles => {
+                        replay_scrobbles(&mut scrobble_task, &pool, &tx, &client);
+                    }
                     Command::CancelDownloads => {
                         if let Err(e) = cancel_all_downloads(&pool, tx.clone(), &cancel_tx).await {
                             let _ = tx.send(Status::Error { error: e.to_string() }).await;
@@ -386,6 +415,12 @@ pub async fn t_database<'a>(
                             log::error!("Failed to mark track {} as disliked: {}", track_id, e);
                         }
                     }
+                    Command::RecordPlay { play } => {
+                        if let Err(e) = insert_play_history(&pool, &play).await {
+                            log::error!("Failed to record play of {}: {}", play.track_id, e);
+                        }
+                        let _ = tx.send(Status::PlayHistoryUpdated).await;
+                    }
                 }
             },
             _ = db_interval.tick() => {
@@ -418,6 +453,9 @@ pub async fn t_database<'a>(
                     &reqwest::Client::new(),
                     &client.base_url,
                 ).await;
+                if new_quality != NetworkQuality::CzechTrain {
+                    replay_scrobbles(&mut scrobble_task, &pool, &tx, &client);
+                }
                 if new_quality != last_quality {
                     last_quality = new_quality;
                     // notify UI
@@ -451,6 +489,130 @@ pub async fn t_database<'a>(
     }
 }
 
+async fn send_jellyfin_command(
+    client: &Client,
+    cmd: &JellyfinCommand,
+) -> Result<(), reqwest::Error> {
+    match cmd {
+        JellyfinCommand::Stopped { id, position_ticks } => {
+            client.stopped(id.clone(), *position_ticks).await
+        }
+        JellyfinCommand::Playing { id } => client.playing(id, None).await,
+        JellyfinCommand::ReportProgress { progress_report } => {
+            client.report_progress(progress_report).await
+        }
+    }
+}
+
+/// Keeps a playback report for later, the outbox is replayed in order once the server is reachable
+///
+async fn store_scrobble(pool: &SqlitePool, tx: &Sender<Status>, cmd: &JellyfinCommand) {
+    let res = match cmd {
+        JellyfinCommand::Playing { id } => queue_scrobble(pool, "playing", id, 0).await,
+        JellyfinCommand::ReportProgress { progress_report } => {
+            let ticks = progress_report.position_ticks;
+            queue_scrobble(pool, "progress", &progress_report.item_id, ticks).await
+        }
+        JellyfinCommand::Stopped { id: Some(id), position_ticks } => {
+            queue_scrobble(pool, "stopped", id, position_ticks.unwrap_or(0)).await
+        }
+        JellyfinCommand::Stopped { id: None, .. } => return,
+    };
+    if let Err(e) = res {
+        log::error!("Failed to save playback report for later: {}", e);
+        return;
+    }
+    let _ = tx
+        .send(Status::ScrobbleOutbox { pending: count_queued_scrobbles(pool).await, replayed: 0 })
+        .await;
+}
+
+/// Starts sending the outbox unless it is empty or already being sent
+///
+fn replay_scrobbles(
+    task: &mut Option<tokio::task::JoinHandle<()>>,
+    pool: &Arc<Pool<Sqlite>>,
+    tx: &Sender<Status>,
+    client: &Arc<Client>,
+) {
+    if task.as_ref().is_some_and(|handle| !handle.is_finished()) {
+        return;
+    }
+    *task =
+        Some(tokio::spawn(t_scrobble_replayer(Arc::clone(pool), tx.clone(), Arc::clone(client))));
+}
+
+/// Sends the saved reports oldest first, as the playback session they came from. The server
+/// decides from the stop position whether a track counts as played, like it does for live plays.
+/// A report is only removed once the server accepted it, so an interrupted replay picks up where
+/// it stopped.
+///
+async fn t_scrobble_replayer(pool: Arc<Pool<Sqlite>>, tx: Sender<Status>, client: Arc<Client>) {
+    let mut replayed = 0;
+    let mut attempted = false;
+    loop {
+        let scrobble = match next_queued_scrobble(&pool).await {
+            Ok(Some(scrobble)) => scrobble,
+            Ok(None) => break,
+            Err(e) => {
+                log::error!("Failed to read the scrobble outbox: {}", e);
+                break;
+            }
+        };
+        attempted = true;
+        let track_id = scrobble.track_id.clone();
+        let res = match scrobble.kind.as_str() {
+            "playing" => client.playing(&track_id, Some(scrobble.created_at)).await,
+            "progress" => {
+                client
+                    .report_progress(&ProgressReport {
+                        volume_level: 100,
+                        is_paused: false,
+                        position_ticks: scrobble.position_ticks,
+                        playback_start_time_ticks: 0,
+                        playback_rate: 1.0,
+                        media_source_id: track_id.clone(),
+                        can_seek: false,
+                        item_id: track_id.clone(),
+                        event_name: "timeupdate".into(),
+                    })
+                    .await
+            }
+            _ => client.stopped(Some(track_id.clone()), Some(scrobble.position_ticks)).await,
+        };
+        match res {
+            Ok(()) if scrobble.kind == "playing" => replayed += 1,
+            Ok(()) => {}
+            // a track deleted in the meantime would block the outbox forever
+            Err(e) if e.status().is_some_and(|s| s.is_client_error()) => {
+                log::warn!("Dropping offline {} report of {}: {}", scrobble.kind, track_id, e);
+            }
+            Err(e) => {
+                log::warn!(
+                    "Failed to replay offline {} report of {}: {}",
+                    scrobble.kind,
+                    track_id,
+                    e
+                );
+                break;
+            }
+        }
+        if let Err(e) = remove_queued_scrobble(&pool, scrobble.id).await {
+            log::error!("Failed to remove replayed scrobble {}: {}", scrobble.id, e);
+            break;
+        }
+    }
+    if !attempted {
+        return;
+    }
+    if replayed > 0 {
+        log::info!("Replayed {} offline plays", replayed);
+    }
+    let _ = tx
+        .send(Status::ScrobbleOutbox { pending: count_queued_scrobbles(&pool).await, replayed })
+        .await;
+}
+
 // If an update has been requested, we process it here.
 // The t_functions are expected to send the status to the UI themselves.
 async fn handle_update(
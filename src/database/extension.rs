@@ -3,15 +3,20 @@ use crate::client::LibraryView;
 use crate::{
     client::{Album, Artist, Client, DiscographySong, Lyric, Playlist},
     database::database::data_updater,
-    keyboard::ActiveSection,
+    genres::Genre,
+    keyboard::{ActiveSection, ActiveTab},
     popup::PopupMenu,
+    radio::RadioProfile,
+    saved_queues::SavedQueue,
+    smart_playlists::{SmartPlaylistOrder, SmartPlaylistRules},
+    stats::{ListeningStats, PlayRecord, StatsEntry, StatsWindow},
     tui,
 };
 use serde::{Deserialize, Serialize};
 use sqlx::migrate::Migrator;
 use sqlx::sqlite::SqlitePoolOptions;
 use sqlx::{migrate::MigrateDatabase, FromRow, Pool, Row, Sqlite, SqlitePool};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 use std::{fmt, path::PathBuf};
@@ -86,6 +91,23 @@ impl tui::App {
             Status::NetworkQualityChanged(network_quality) => {
                 self.network_quality = network_quality;
             }
+            Status::ScrobbleOutbox { pending, replayed } => {
+                self.scrobbles_pending = pending;
+                self.scrobbles_replayed += replayed;
+                if let Some(PopupMenu::GlobalRoot {
+                    scrobbles_pending, scrobbles_replayed, ..
+                }) = &mut self.popup.current_menu
+                {
+                    *scrobbles_pending = self.scrobbles_pending;
+                    *scrobbles_replayed = self.scrobbles_replayed;
+                }
+            }
+            Status::DashboardLoaded { shelves } => self.dashboard_loaded(*shelves),
+            Status::PlayHistoryUpdated => match self.state.active_tab {
+                ActiveTab::Home => self.refresh_dashboard(),
+                ActiveTab::Stats => self.refresh_stats().await,
+                _ => {}
+            },
             Status::AllDownloaded => {
                 // pretty nifty huh
                 if let Some(popup) = &mut self.popup.current_menu {
@@ -249,6 +271,12 @@ impl tui::App {
                         get_playlists_with_tracks(&self.db.pool).await.unwrap_or_default();
                     self.reorder_lists();
                 }
+                // new tracks, play counts and favorites may change what the rules match
+                self.refresh_smart_playlists().await;
+                self.refresh_genres().await;
+                if self.state.active_tab == ActiveTab::Home {
+                    self.refresh_dashboard();
+                }
                 self.db_updating = false;
             }
             Status::UpdateFailed { error } => {
@@ -860,6 +888,41 @@ pub async fn get_album_tracks(
     Ok(out)
 }
 
+pub async fn get_track(
+    pool: &SqlitePool,
+    track_id: &str,
+    client: Option<&Arc<Client>>,
+) -> Result<Option<DiscographySong>, Box<dyn std::error::Error>> {
+    let record: Option<(String, String, i64)> = sqlx::query_as(
+        r#"
+        SELECT track, download_status, disliked
+        FROM tracks
+        WHERE id = ?
+        "#,
+    )
+    .bind(track_id)
+    .fetch_optional(pool)
+    .await?;
+
+    let Some((json_str, download_status, disliked)) = record else {
+        return Ok(None);
+    };
+    if client.is_none() && download_status != "Downloaded" {
+        return Ok(None);
+    }
+
+    let mut track: DiscographySong = serde_json::from_str(&json_str)?;
+    track.download_status = match download_status.as_str() {
+        "Downloaded" => DownloadStatus::Downloaded,
+        "Queued" => DownloadStatus::Queued,
+        "Downloading" => DownloadStatus::Downloading,
+        _ => DownloadStatus::NotDownloaded,
+    };
+    track.disliked = disliked != 0;
+
+    Ok(Some(track))
+}
+
 pub async fn get_playlist_tracks(
     pool: &SqlitePool,
     playlist_id: &str,
@@ -905,6 +968,269 @@ pub async fn get_playlist_tracks(
     Ok(tracks)
 }
 
+/// Evaluates the rules of a smart playlist against the cached tracks. Offline only downloaded tracks qualify
+///
+pub async fn get_smart_playlist_tracks(
+    pool: &SqlitePool,
+    rules: &SmartPlaylistRules,
+    online: bool,
+) -> Result<Vec<DiscographySong>, Box<dyn std::error::Error>> {
+    let libs = selected_library_ids(pool).await;
+    if libs.is_empty() {
+        return Ok(vec![]);
+    }
+
+    enum Bind {
+        Text(String),
+        Int(i64),
+    }
+    let mut conditions = vec![format!("t.library_id IN ({})", vec!["?"; libs.len()].join(","))];
+    let mut binds: Vec<Bind> = libs.into_iter().map(Bind::Text).collect();
+
+    if !online {
+        conditions.push("t.download_status = 'Downloaded'".to_string());
+    }
+    if !rules.include_disliked {
+        conditions.push("t.disliked = 0".to_string());
+    }
+    for (path, values) in [("$.Genres", &rules.genres), ("$.Artists", &rules.artists)] {
+        if values.is_empty() {
+            continue;
+        }
+        conditions.push(format!(
+            "EXISTS (SELECT 1 FROM json_each(t.track, '{}') WHERE lower(value) IN ({}))",
+            path,
+            vec!["?"; values.len()].join(",")
+        ));
+        binds.extend(values.iter().map(|v| Bind::Text(v.to_lowercase())));
+    }
+    if let Some(days) = rules.added_within_days {
+        // DateCreated is ISO 8601, which compares fine as text
+        conditions.push(
+            "json_extract(t.track, '$.DateCreated') >= strftime('%Y-%m-%dT%H:%M:%S', 'now', ?)"
+                .to_string(),
+        );
+        binds.push(Bind::Text(format!("-{} days", days)));
+    }
+    if let Some(favorite) = rules.favorite {
+        conditions
+            .push("COALESCE(json_extract(t.track, '$.UserData.IsFavorite'), 0) = ?".to_string());
+        binds.push(Bind::Int(favorite as i64));
+    }
+    let play_count = "COALESCE(json_extract(t.track, '$.UserData.PlayCount'), 0)";
+    if let Some(min) = rules.min_play_count {
+        conditions.push(format!("{} >= ?", play_count));
+        binds.push(Bind::Int(min as i64));
+    }
+    if let Some(max) = rules.max_play_count {
+        conditions.push(format!("{} <= ?", play_count));
+        binds.push(Bind::Int(max as i64));
+    }
+    if let Some(min) = rules.min_duration_secs {
+        conditions.push("json_extract(t.track, '$.RunTimeTicks') >= ?".to_string());
+        binds.push(Bind::Int(min as i64 * 10_000_000));
+    }
+    if let Some(max) = rules.max_duration_secs {
+        conditions.push("json_extract(t.track, '$.RunTimeTicks') <= ?".to_string());
+        binds.push(Bind::Int(max as i64 * 10_000_000));
+    }
+
+    let order = match rules.order {
+        SmartPlaylistOrder::RecentlyAdded => {
+            "json_extract(t.track, '$.DateCreated') DESC".to_string()
+        }
+        SmartPlaylistOrder::MostPlayed => format!("{} DESC", play_count),
+        SmartPlaylistOrder::LeastPlayed => format!("{} ASC", play_count),
+        SmartPlaylistOrder::Name => "lower(json_extract(t.track, '$.Name'))".to_string(),
+        SmartPlaylistOrder::Album => "lower(json_extract(t.track, '$.Album')), \
+            json_extract(t.track, '$.ParentIndexNumber'), json_extract(t.track, '$.IndexNumber')"
+            .to_string(),
+        SmartPlaylistOrder::Random => "RANDOM()".to_string(),
+    };
+
+    let sql = format!(
+        r#"
+        SELECT t.track, t.disliked
+        FROM tracks t
+        WHERE {}
+        ORDER BY {}
+        LIMIT ?
+        "#,
+        conditions.join(" AND "),
+        order
+    );
+
+    let mut query = sqlx::query_as::<_, (String, i64)>(&sql);
+    for bind in binds {
+        query = match bind {
+            Bind::Text(value) => query.bind(value),
+            Bind::Int(value) => query.bind(value),
+        };
+    }
+    // a negative limit means no limit in sqlite
+    let records = query.bind(rules.limit.map_or(-1, |l| l as i64)).fetch_all(pool).await?;
+
+    let mut tracks = Vec::new();
+    for (json_str, disliked) in records {
+        let mut track: DiscographySong = serde_json::from_str(&json_str)?;
+        track.disliked = disliked != 0;
+        tracks.push(track);
+    }
+
+    Ok(tracks)
+}
+
+/// All genres of the cached tracks in the selected libraries, with their track and album counts
+///
+pub async fn get_genres(
+    pool: &SqlitePool,
+    online: bool,
+) -> Result<Vec<Genre>, Box<dyn std::error::Error>> {
+    let libs = selected_library_ids(pool).await;
+    if libs.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let sql = format!(
+        r#"
+        SELECT g.value, COUNT(DISTINCT t.id), COUNT(DISTINCT t.album_id)
+        FROM tracks t, json_each(t.track, '$.Genres') g
+        WHERE t.library_id IN ({})
+          {}
+        GROUP BY g.value
+        ORDER BY lower(g.value)
+        "#,
+        vec!["?"; libs.len()].join(","),
+        if online { "" } else { "AND t.download_status = 'Downloaded'" }
+    );
+
+    let mut q = sqlx::query_as::<_, (String, i64, i64)>(&sql);
+    for lib in libs {
+        q = q.bind(lib);
+    }
+    let records = q.fetch_all(pool).await?;
+
+    Ok(records
+        .into_iter()
+        .filter(|(name, _, _)| !name.trim().is_empty())
+        .map(|(name, track_count, album_count)| Genre { name, track_count, album_count })
+        .collect())
+}
+
+/// Tracks of a genre, ordered by album so they can be shown grouped
+///
+pub async fn get_genre_tracks(
+    pool: &SqlitePool,
+    genre: &str,
+    online: bool,
+) -> Result<Vec<DiscographySong>, Box<dyn std::error::Error>> {
+    let libs = selected_library_ids(pool).await;
+    if libs.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let sql = format!(
+        r#"
+        SELECT t.track, t.disliked
+        FROM tracks t
+        WHERE EXISTS (SELECT 1 FROM json_each(t.track, '$.Genres') WHERE value = ?)
+          AND t.library_id IN ({})
+          {}
+        ORDER BY lower(json_extract(t.track, '$.AlbumArtist')),
+                 lower(json_extract(t.track, '$.Album')),
+                 t.album_id,
+                 json_extract(t.track, '$.ParentIndexNumber'),
+                 json_extract(t.track, '$.IndexNumber')
+        "#,
+        vec!["?"; libs.len()].join(","),
+        if online { "" } else { "AND t.download_status = 'Downloaded'" }
+    );
+
+    let mut q = sqlx::query_as::<_, (String, i64)>(&sql).bind(genre);
+    for lib in libs {
+        q = q.bind(lib);
+    }
+    let records = q.fetch_all(pool).await?;
+
+    let mut tracks = Vec::new();
+    for (json_str, disliked) in records {
+        let mut track: DiscographySong = serde_json::from_str(&json_str)?;
+        track.disliked = disliked != 0;
+        tracks.push(track);
+    }
+
+    Ok(tracks)
+}
+
+/// Cached tracks sharing a genre or an artist with the radio profile. Scoring happens in radio.rs
+///
+pub async fn get_radio_candidates(
+    pool: &SqlitePool,
+    profile: &RadioProfile,
+    online: bool,
+) -> Result<Vec<DiscographySong>, Box<dyn std::error::Error>> {
+    let libs = selected_library_ids(pool).await;
+    if libs.is_empty() || (profile.genres.is_empty() && profile.artists.is_empty()) {
+        return Ok(vec![]);
+    }
+
+    let mut matches = vec![];
+    let mut values: Vec<String> = vec![];
+    for (path, names) in [("$.Genres", &profile.genres), ("$.Artists", &profile.artists)] {
+        if names.is_empty() {
+            continue;
+        }
+        matches.push(format!(
+            "EXISTS (SELECT 1 FROM json_each(t.track, '{}') WHERE lower(value) IN ({}))",
+            path,
+            vec!["?"; names.len()].join(",")
+        ));
+        values.extend(names.iter().map(|n| n.to_lowercase()));
+    }
+
+    let sql = format!(
+        r#"
+        SELECT t.track, t.disliked
+        FROM tracks t
+        WHERE ({})
+          AND t.disliked = 0
+          AND t.library_id IN ({})
+          {}
+        "#,
+        matches.join(" OR "),
+        vec!["?"; libs.len()].join(","),
+        if online { "" } else { "AND t.download_status = 'Downloaded'" }
+    );
+
+    let mut q = sqlx::query_as::<_, (String, i64)>(&sql);
+    for value in values {
+        q = q.bind(value);
+    }
+    for lib in libs {
+        q = q.bind(lib);
+    }
+    let records = q.fetch_all(pool).await?;
+
+    let mut tracks = Vec::new();
+    for (json_str, disliked) in records {
+        let mut track: DiscographySong = serde_json::from_str(&json_str)?;
+        track.disliked = disliked != 0;
+        tracks.push(track);
+    }
+
+    Ok(tracks)
+}
+
+/// Dislikes are local only, so tracks coming from the server have to be checked against this
+///
+pub async fn get_disliked_ids(
+    pool: &SqlitePool,
+) -> Result<HashSet<String>, Box<dyn std::error::Error>> {
+    let records: Vec<(String,)> =
+        sqlx::query_as("SELECT id FROM tracks WHERE disliked = 1").fetch_all(pool).await?;
+    Ok(records.into_iter().map(|(id,)| id).collect())
+}
+
 pub async fn get_all_albums(pool: &SqlitePool) -> Result<Vec<Album>, Box<dyn std::error::Error>> {
     let libs = selected_library_ids(pool).await;
 
@@ -1101,6 +1427,53 @@ pub async fn get_tracks(
     Ok(tracks)
 }
 
+/// Every cached track of the selected libraries, imported playlist files are matched against these
+///
+pub async fn get_library_tracks(
+    pool: &SqlitePool,
+) -> Result<Vec<DiscographySong>, Box<dyn std::error::Error>> {
+    let libs = selected_library_ids(pool).await;
+    if libs.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let sql = format!(
+        "SELECT track FROM tracks WHERE library_id IN ({})",
+        vec!["?"; libs.len()].join(",")
+    );
+    let mut query = sqlx::query_as::<_, (String,)>(&sql);
+    for lib in libs {
+        query = query.bind(lib);
+    }
+
+    let mut tracks = Vec::new();
+    for (json_str,) in query.fetch_all(pool).await? {
+        tracks.push(serde_json::from_str::<DiscographySong>(&json_str)?);
+    }
+    Ok(tracks)
+}
+
+/// When the given tracks were last played, in unix seconds. Tracks never played are left out
+///
+pub async fn get_last_played(
+    pool: &SqlitePool,
+    track_ids: &[String],
+) -> Result<HashMap<String, i64>, sqlx::Error> {
+    let ids = serde_json::to_string(track_ids).unwrap_or_else(|_| "[]".to_string());
+    let rows: Vec<(String, i64)> = sqlx::query_as(
+        r#"
+        SELECT id, CAST(strftime('%s', last_played) AS INTEGER)
+        FROM tracks
+        WHERE last_played IS NOT NULL
+          AND id IN (SELECT value FROM json_each(?))
+        "#,
+    )
+    .bind(ids)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().collect())
+}
+
 /// Favorite toggles
 ///
 pub async fn set_favorite_track(
@@ -1215,3 +1588,346 @@ pub async fn set_last_library_update(pool: &Pool<Sqlite>) {
     .execute(pool)
     .await;
 }
+
+/// ------------ play history ------------
+///
+pub async fn insert_play_history(pool: &SqlitePool, play: &PlayRecord) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO play_history
+            (track_id, title, artist, album, album_id, played_at, listened_secs, duration_secs, skipped)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&play.track_id)
+    .bind(&play.title)
+    .bind(&play.artist)
+    .bind(&play.album)
+    .bind(&play.album_id)
+    .bind(play.played_at)
+    .bind(play.listened_secs)
+    .bind(play.duration_secs)
+    .bind(play.skipped as i64)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn get_listening_stats(
+    pool: &SqlitePool,
+    window: StatsWindow,
+) -> Result<ListeningStats, sqlx::Error> {
+    let since = window.since();
+
+    let (listened_secs, plays, skips): (f64, i64, i64) = sqlx::query_as(
+        r#"
+        SELECT COALESCE(SUM(listened_secs), 0.0), COUNT(*), COALESCE(SUM(skipped), 0)
+        FROM play_history
+        WHERE played_at >= ?
+        "#,
+    )
+    .bind(since)
+    .fetch_one(pool)
+    .await?;
+
+    // (group by, name, artist) for artists, albums and tracks
+    let groupings = [
+        ("artist", "artist", "''"),
+        ("album_id", "MAX(album)", "MAX(artist)"),
+        ("track_id", "MAX(title)", "MAX(artist)"),
+    ];
+    let mut tops = Vec::with_capacity(groupings.len());
+    for (group_by, name, artist) in groupings {
+        let sql = format!(
+            r#"
+            SELECT {name}, {artist}, COUNT(*) - SUM(skipped) AS plays, SUM(skipped), SUM(listened_secs)
+            FROM play_history
+            WHERE played_at >= ?
+            GROUP BY {group_by}
+            ORDER BY plays DESC, SUM(listened_secs) DESC
+            LIMIT 100
+            "#
+        );
+        let rows: Vec<(String, String, i64, i64, f64)> =
+            sqlx::query_as(&sql).bind(since).fetch_all(pool).await?;
+        tops.push(
+            rows.into_iter()
+                .map(|(name, artist, plays, skips, listened_secs)| StatsEntry {
+                    name,
+                    artist,
+                    plays,
+                    skips,
+                    listened_secs,
+                })
+                .collect::<Vec<_>>(),
+        );
+    }
+    let top_tracks = tops.pop().unwrap_or_default();
+    let top_albums = tops.pop().unwrap_or_default();
+    let top_artists = tops.pop().unwrap_or_default();
+
+    Ok(ListeningStats { window, listened_secs, plays, skips, top_artists, top_albums, top_tracks })
+}
+
+/// ------------ scrobble outbox ------------
+///
+pub struct QueuedScrobble {
+    pub id: i64,
+    pub kind: String, // playing, progress or stopped
+    pub track_id: String,
+    pub position_ticks: u64,
+    pub created_at: i64, // unix milliseconds
+}
+
+/// Saves a playback report for later. Only the latest progress of a play is kept, and none once
+/// it has stopped, the server only needs to know where the track was
+///
+pub async fn queue_scrobble(
+    pool: &SqlitePool,
+    kind: &str,
+    track_id: &str,
+    position_ticks: u64,
+) -> Result<(), sqlx::Error> {
+    let created_at =
+        SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis()) as i64;
+    if kind != "playing" {
+        sqlx::query(
+            r#"
+            DELETE FROM scrobble_outbox
+            WHERE kind = 'progress' AND track_id = ?
+              AND id > (
+                SELECT COALESCE(MAX(id), 0) FROM scrobble_outbox
+                WHERE kind = 'playing' AND track_id = ?
+              )
+            "#,
+        )
+        .bind(track_id)
+        .bind(track_id)
+        .execute(pool)
+        .await?;
+    }
+    sqlx::query(
+        r#"
+        INSERT OR IGNORE INTO scrobble_outbox (kind, track_id, position_ticks, created_at)
+        VALUES (?, ?, ?, ?)
+        "#,
+    )
+    .bind(kind)
+    .bind(track_id)
+    .bind(position_ticks as i64)
+    .bind(created_at)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn next_queued_scrobble(
+    pool: &SqlitePool,
+) -> Result<Option<QueuedScrobble>, sqlx::Error> {
+    let row: Option<(i64, String, String, i64, i64)> = sqlx::query_as(
+        r#"
+        SELECT id, kind, track_id, position_ticks, created_at
+        FROM scrobble_outbox
+        ORDER BY id ASC
+        LIMIT 1
+        "#,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|(id, kind, track_id, position_ticks, created_at)| QueuedScrobble {
+        id,
+        kind,
+        track_id,
+        position_ticks: position_ticks.max(0) as u64,
+        created_at,
+    }))
+}
+
+pub async fn remove_queued_scrobble(pool: &SqlitePool, id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM scrobble_outbox WHERE id = ?").bind(id).execute(pool).await?;
+    Ok(())
+}
+
+/// Plays waiting to be sent, progress and stop reports belong to one of them
+///
+pub async fn count_queued_scrobbles(pool: &SqlitePool) -> i64 {
+    sqlx::query_scalar("SELECT COUNT(*) FROM scrobble_outbox WHERE kind = 'playing'")
+        .fetch_one(pool)
+        .await
+        .unwrap_or(0)
+}
+
+pub async fn has_queued_scrobbles(pool: &SqlitePool) -> bool {
+    sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM scrobble_outbox)")
+        .fetch_one(pool)
+        .await
+        .unwrap_or(false)
+}
+
+// ------------ saved queues ------------
+
+/// Saves the queue under a name. Saving under a name that already exists replaces that queue
+///
+pub async fn insert_saved_queue(
+    pool: &SqlitePool,
+    name: &str,
+    songs: &[tui::Song],
+    current_index: usize,
+) -> Result<(), sqlx::Error> {
+    let serialized = serde_json::to_string(songs).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+    let created_at = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()) as i64;
+    sqlx::query(
+        r#"
+        INSERT INTO saved_queues (name, songs, current_index, tracks, created_at)
+        VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT(name) DO UPDATE SET
+            songs = excluded.songs,
+            current_index = excluded.current_index,
+            tracks = excluded.tracks,
+            created_at = excluded.created_at
+        "#,
+    )
+    .bind(name)
+    .bind(serialized)
+    .bind(current_index as i64)
+    .bind(songs.len() as i64)
+    .bind(created_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Newest first, without the songs themselves
+///
+pub async fn get_saved_queues(pool: &SqlitePool) -> Result<Vec<SavedQueue>, sqlx::Error> {
+    let rows: Vec<(i64, String, i64, i64)> = sqlx::query_as(
+        "SELECT id, name, tracks, created_at FROM saved_queues ORDER BY created_at DESC, id DESC",
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|(id, name, tracks, created_at)| SavedQueue {
+            id,
+            name,
+            tracks: tracks as usize,
+            created_at,
+        })
+        .collect())
+}
+
+/// The songs of a saved queue and the index that was playing when it was saved
+///
+pub async fn get_saved_queue_songs(
+    pool: &SqlitePool,
+    id: i64,
+) -> Result<Option<(Vec<tui::Song>, usize)>, sqlx::Error> {
+    let row: Option<(String, i64)> =
+        sqlx::query_as("SELECT songs, current_index FROM saved_queues WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+    let Some((songs, current_index)) = row else {
+        return Ok(None);
+    };
+    let songs = serde_json::from_str(&songs).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+    Ok(Some((songs, current_index.max(0) as usize)))
+}
+
+pub async fn delete_saved_queue(pool: &SqlitePool, id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM saved_queues WHERE id = ?").bind(id).execute(pool).await?;
+    Ok(())
+}
+
+/// ------------ home tab ------------
+///
+/// Downloaded tracks played before, for the home tab while offline. `most_played` sorts by play
+/// count, taking whichever is higher of the count cached from Jellyfin and the local history
+///
+pub async fn get_played_tracks(
+    pool: &SqlitePool,
+    most_played: bool,
+    limit: usize,
+) -> Result<Vec<DiscographySong>, Box<dyn std::error::Error>> {
+    let libs = selected_library_ids(pool).await;
+    if libs.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let (condition, order) = match most_played {
+        true => ("plays > 0", "plays DESC, last_played DESC"),
+        false => ("last_played IS NOT NULL", "last_played DESC"),
+    };
+    let sql = format!(
+        r#"
+        SELECT track, disliked FROM (
+            SELECT t.track, t.disliked, t.last_played,
+                MAX(
+                    COALESCE(json_extract(t.track, '$.UserData.PlayCount'), 0),
+                    (SELECT COUNT(*) FROM play_history h WHERE h.track_id = t.id AND h.skipped = 0)
+                ) AS plays
+            FROM tracks t
+            WHERE t.download_status = 'Downloaded'
+              AND t.library_id IN ({})
+        )
+        WHERE {}
+        ORDER BY {}
+        LIMIT ?
+        "#,
+        vec!["?"; libs.len()].join(","),
+        condition,
+        order
+    );
+    let mut query = sqlx::query_as::<_, (String, i64)>(&sql);
+    for lib in libs {
+        query = query.bind(lib);
+    }
+
+    let mut tracks = Vec::new();
+    for (json_str, disliked) in query.bind(limit as i64).fetch_all(pool).await? {
+        let mut track: DiscographySong = serde_json::from_str(&json_str)?;
+        track.disliked = disliked != 0;
+        tracks.push(track);
+    }
+    Ok(tracks)
+}
+
+/// Playlists holding any of the given tracks, ordered by the first of the tracks each one holds.
+/// That track comes along, it's where listening left off
+///
+pub async fn get_playlists_of_tracks(
+    pool: &SqlitePool,
+    track_ids: &[String],
+    limit: usize,
+) -> Result<Vec<(Playlist, String)>, Box<dyn std::error::Error>> {
+    let ids = serde_json::to_string(track_ids)?;
+    // sqlite takes the bare pm.track_id from the row with the smallest key, the latest play
+    let rows: Vec<(String, String, i64)> = sqlx::query_as(
+        r#"
+        SELECT p.playlist, pm.track_id, MIN(ids.key) AS first_seen
+        FROM json_each(?) ids
+        JOIN playlist_membership pm ON pm.track_id = ids.value
+        JOIN playlists p ON p.id = pm.playlist_id
+        WHERE p.id NOT IN (
+            SELECT id FROM missing_counters
+            WHERE entity_type = 'playlist'
+        )
+        GROUP BY p.id
+        ORDER BY first_seen
+        LIMIT ?
+        "#,
+    )
+    .bind(ids)
+    .bind(limit as i64)
+    .fetch_all(pool)
+    .await?;
+
+    let mut playlists = Vec::with_capacity(rows.len());
+    for (json_str, track_id, _) in rows {
+        playlists.push((serde_json::from_str::<Playlist>(&json_str)?, track_id));
+    }
+    Ok(playlists)
+}
@@ -0,0 +1,16 @@
+-- one row per listened track, names are copied so stats survive library changes
+CREATE TABLE IF NOT EXISTS play_history (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  track_id TEXT NOT NULL,
+  title TEXT NOT NULL,
+  artist TEXT NOT NULL,
+  album TEXT NOT NULL,
+  album_id TEXT NOT NULL,
+  played_at INTEGER NOT NULL,     -- unix seconds, when playback started
+  listened_secs REAL NOT NULL,    -- time actually spent playing, seeking does not count
+  duration_secs REAL NOT NULL,
+  skipped INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE INDEX IF NOT EXISTS idx_play_history_played_at ON play_history(played_at);
+CREATE INDEX IF NOT EXISTS idx_play_history_track_id ON play_history(track_id);
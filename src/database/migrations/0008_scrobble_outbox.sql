@@ -0,0 +1,9 @@
+-- playback reports that could not reach the server, replayed in id order once back online
+CREATE TABLE IF NOT EXISTS scrobble_outbox (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  kind TEXT NOT NULL,                     -- playing, progress or stopped
+  track_id TEXT NOT NULL,
+  position_ticks INTEGER NOT NULL DEFAULT 0,
+  created_at INTEGER NOT NULL,            -- unix milliseconds, when the event originally happened
+  UNIQUE (kind, track_id, created_at)
+);
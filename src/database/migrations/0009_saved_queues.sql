@@ -0,0 +1,9 @@
+-- named copies of the queue, saved and restored from the global popup. Local only, so they work offline
+CREATE TABLE IF NOT EXISTS saved_queues (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  name TEXT NOT NULL UNIQUE,
+  songs TEXT NOT NULL,            -- serialized Vec<Song>
+  current_index INTEGER NOT NULL,
+  tracks INTEGER NOT NULL,
+  created_at INTEGER NOT NULL     -- unix seconds, updated when a name is saved over
+);
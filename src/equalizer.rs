@@ -0,0 +1,158 @@
+/* --------------------------
+Equalizer
+    - 10 octave bands applied through an mpv lavfi `equalizer` chain, labeled so other `af` filters
+      set in the mpv config are left alone
+    - Built-in presets plus any defined under `equalizer: presets:` in the config
+    - The settings are saved in the preferences per output device, so headphones and speakers
+      can each have their own curve
+-------------------------- */
+
+use crate::tui::App;
+use serde::{Deserialize, Serialize};
+
+/// Center frequencies of the bands in Hz
+pub const EQ_BANDS: [u32; 10] = [31, 62, 125, 250, 500, 1000, 2000, 4000, 8000, 16000];
+/// Gains are clamped to this many dB either way
+pub const EQ_MAX_GAIN: f64 = 12.0;
+/// Label of the equalizer in mpv's filter chain
+const EQ_FILTER_LABEL: &str = "eq";
+/// The preset name used once a band has been changed by hand
+pub const EQ_CUSTOM: &str = "Custom";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EqPreset {
+    pub name: String,
+    pub gains: [f64; 10],
+}
+
+fn builtin_presets() -> Vec<EqPreset> {
+    let preset = |name: &str, gains: [f64; 10]| EqPreset { name: name.to_string(), gains };
+    vec![
+        preset("Flat", [0.0; 10]),
+        preset("Bass boost", [6.0, 5.0, 4.0, 2.0, 0.5, 0.0, 0.0, 0.0, 0.0, 0.0]),
+        preset("Bass cut", [-6.0, -5.0, -3.0, -1.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]),
+        preset("Treble boost", [0.0, 0.0, 0.0, 0.0, 0.0, 0.5, 1.5, 3.0, 4.5, 5.5]),
+        preset("Vocal", [-2.0, -2.0, -1.0, 1.0, 3.0, 3.5, 3.0, 1.5, 0.0, -1.0]),
+        preset("Loudness", [5.0, 4.0, 2.0, 0.0, -1.0, -1.0, 0.0, 1.0, 3.0, 4.0]),
+        preset("Acoustic", [3.0, 3.0, 2.0, 1.0, 1.5, 1.5, 2.0, 2.5, 2.0, 1.5]),
+        preset("Electronic", [4.0, 3.5, 1.0, 0.0, -1.5, 1.0, 0.5, 1.0, 3.5, 4.5]),
+    ]
+}
+
+/// Equalizer settings for one output device
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EqSettings {
+    pub preset: String,
+    pub gains: [f64; 10],
+    #[serde(default)]
+    pub bypass: bool,
+}
+
+impl Default for EqSettings {
+    fn default() -> Self {
+        Self { preset: String::from("Flat"), gains: [0.0; 10], bypass: false }
+    }
+}
+
+impl EqSettings {
+    /// The value for mpv's `af add`, or `None` when nothing should be filtered
+    ///
+    pub fn filter(&self) -> Option<String> {
+        if self.bypass || self.gains.iter().all(|g| *g == 0.0) {
+            return None;
+        }
+        let mut chain = vec![];
+        // boosting would clip loud masters, so the whole signal is lowered by the largest boost
+        let headroom = self.gains.iter().cloned().fold(0.0, f64::max);
+        if headroom > 0.0 {
+            chain.push(format!("volume=-{:.1}dB", headroom));
+        }
+        for (freq, gain) in EQ_BANDS.iter().zip(self.gains.iter()) {
+            if *gain != 0.0 {
+                chain.push(format!("equalizer=f={}:t=o:w=1:g={:.1}", freq, gain));
+            }
+        }
+        Some(format!("lavfi=[{}]", chain.join(",")))
+    }
+}
+
+/// The `equalizer` config section
+///
+#[derive(Debug, Clone)]
+pub struct Equalizer {
+    pub presets: Vec<EqPreset>,
+    /// Used for devices that have no saved settings yet
+    pub default: EqSettings,
+}
+
+impl Equalizer {
+    /// Parses the `equalizer` section. Broken presets are skipped and reported in the returned errors
+    ///
+    pub fn from_config(config: &serde_yaml::Value) -> (Self, Vec<String>) {
+        let mut presets = builtin_presets();
+        let mut errors = vec![];
+
+        let equalizer = &config["equalizer"];
+        if let Some(user_presets) = equalizer["presets"].as_mapping() {
+            for (name, gains) in user_presets {
+                let Some(name) = name.as_str() else {
+                    errors.push(String::from("an equalizer preset name is not a string"));
+                    continue;
+                };
+                let gains = match serde_yaml::from_value::<[f64; 10]>(gains.clone()) {
+                    Ok(gains) => gains.map(|g| g.clamp(-EQ_MAX_GAIN, EQ_MAX_GAIN)),
+                    Err(_) => {
+                        errors.push(format!("equalizer preset '{}' needs 10 gains in dB", name));
+                        continue;
+                    }
+                };
+                // a user preset with a built-in name replaces it
+                presets.retain(|p| p.name != name);
+                presets.push(EqPreset { name: name.to_string(), gains });
+            }
+        }
+
+        let mut default = EqSettings::default();
+        if let Some(name) = equalizer["preset"].as_str() {
+            match presets.iter().find(|p| p.name.eq_ignore_ascii_case(name)) {
+                Some(preset) => {
+                    default.preset = preset.name.clone();
+                    default.gains = preset.gains;
+                }
+                None => errors.push(format!("unknown equalizer preset '{}'", name)),
+            }
+        }
+        default.bypass = equalizer["bypass"].as_bool().unwrap_or(false);
+
+        (Self { presets, default }, errors)
+    }
+
+    pub fn preset(&self, name: &str) -> Option<&EqPreset> {
+        self.presets.iter().find(|p| p.name == name)
+    }
+}
+
+impl App {
+    pub fn eq_settings(&self) -> EqSettings {
+        self.preferences
+            .equalizer
+            .get(&self.current_audio_device())
+            .cloned()
+            .unwrap_or_else(|| self.equalizer.default.clone())
+    }
+
+    /// Saves the settings for the current device and applies them
+    ///
+    pub async fn set_eq_settings(&mut self, settings: EqSettings) {
+        self.preferences.equalizer.insert(self.current_audio_device(), settings);
+        if let Err(e) = self.preferences.save() {
+            log::error!("Failed to save preferences: {}", e);
+        }
+        self.apply_equalizer().await;
+    }
+
+    pub async fn apply_equalizer(&self) {
+        self.mpv_handle.set_audio_filter(EQ_FILTER_LABEL, self.eq_settings().filter()).await;
+    }
+}
@@ -0,0 +1,501 @@
+/* --------------------------
+Fader
+    - Lives in the mpv thread and drives a lavfi `volume` filter through `af-command`, so fades never
+      touch the volume the user set
+    - Crossfade: for the last seconds of a track the next one already plays in a second mpv, coming
+      up while the current one goes down. Once mpv moves on to that track it stays muted, seeks to
+      where the second mpv is and takes over. Skipped between tracks of the same album, so gapless
+      albums stay gapless
+    - Pause, resume and stop get a short fade of their own, they (and skips or seeks) cut a
+      running crossfade short
+    - Nothing is read from mpv while a fade runs, the position is extrapolated from when it started
+-------------------------- */
+
+use crate::config::Transitions;
+use crate::mpv::{loadfile, MpvFile};
+use libmpv2::Mpv;
+use std::collections::HashMap;
+use std::f64::consts::FRAC_PI_2;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Label of the fade filter in mpv's filter chain
+const FADE_FILTER_LABEL: &str = "fade";
+/// How often the gain is updated while something is fading
+pub const FADE_INTERVAL: Duration = Duration::from_millis(30);
+/// mpv is muted this close to the end of a crossfading track, so the next one can't be heard
+/// from its start before the handover
+const HANDOVER_MUTE: f64 = 0.25;
+/// How far apart the two players may be when mpv takes over
+const HANDOVER_TOLERANCE: f64 = 0.04;
+/// Seeks mpv makes to catch up with the second player before it takes over anyway
+const HANDOVER_SEEKS: u8 = 3;
+/// Gives up on a handover that takes longer, mpv then just plays on
+const HANDOVER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// What to do once a fade out has finished
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FadeEnd {
+    Nothing,
+    Pause,
+    Stop,
+}
+
+struct Fade {
+    from: f64,
+    to: f64,
+    started: Instant,
+    duration: Duration,
+    then: FadeEnd,
+}
+
+impl Fade {
+    fn level(&self) -> f64 {
+        let t = self.started.elapsed().as_secs_f64() / self.duration.as_secs_f64().max(0.001);
+        self.from + (self.to - self.from) * t.min(1.0)
+    }
+
+    fn done(&self) -> bool {
+        self.started.elapsed() >= self.duration
+    }
+}
+
+/// Where the current track was at some point, the position is extrapolated from it while playing
+struct Clock {
+    position: f64,
+    at: Instant,
+}
+
+/// The next track playing in the second mpv
+struct Overlap {
+    url: String,
+    stage: Stage,
+}
+
+enum Stage {
+    /// both play, the next track comes up while the current one goes down
+    Fading,
+    /// mpv is on the next track, muted until it plays from where the second mpv is
+    Handover { started: Instant, seek: Option<Instant>, seeks: u8 },
+}
+
+#[derive(Default)]
+pub struct Fader {
+    transitions: Transitions,
+    installed: bool,
+    /// whether loadfile takes an index before the per-file options
+    indexed_loadfile: bool,
+    /// every file mpv was given, for its album and its options in the second mpv
+    files: HashMap<String, MpvFile>,
+    /// pause, resume and stop fade
+    fade: Option<Fade>,
+    /// level the last pause/resume/stop fade ended on
+    level: f64,
+    /// whether the current track crossfades into the next, decided once when it would start
+    fading_out: Option<bool>,
+    /// plays the next track during a crossfade, created the first time one is needed
+    second: Option<Mpv>,
+    overlap: Option<Overlap>,
+    /// mpv was muted for the handover
+    muted: bool,
+    /// how far the second mpv gets while mpv seeks, in seconds of the track
+    lead: f64,
+    clock: Option<Clock>,
+    last_index: Option<usize>,
+    last_paused: bool,
+    last_speed: f64,
+    /// the last tick asked for the fast fade interval
+    fast: bool,
+    /// crossfade part of the gain, from the last tick
+    transition: f64,
+    /// last gain sent to mpv
+    gain: f64,
+}
+
+impl Fader {
+    pub fn new(indexed_loadfile: bool) -> Self {
+        Self {
+            indexed_loadfile,
+            level: 1.0,
+            transition: 1.0,
+            gain: 1.0,
+            lead: 0.15,
+            ..Default::default()
+        }
+    }
+
+    /// Adds or removes the fade filter to match the settings
+    ///
+    pub fn set_transitions(&mut self, mpv: &Mpv, transitions: Transitions) -> bool {
+        self.transitions = transitions;
+        self.finish(mpv);
+        self.interrupt(mpv);
+        let label = format!("@{}", FADE_FILTER_LABEL);
+        let res = match (transitions.enabled(), self.installed) {
+            (true, false) => {
+                mpv.command("af", &["add", &format!("{}:lavfi=[volume=volume=1.0]", label)])
+            }
+            (false, true) => mpv.command("af", &["remove", &label]),
+            _ => Ok(()),
+        };
+        if let Err(e) = &res {
+            log::error!("mpv failed to update the fade filter: {:?}", e);
+            return false;
+        }
+        self.installed = transitions.enabled();
+        self.level = 1.0;
+        self.transition = 1.0;
+        self.gain = 1.0;
+        true
+    }
+
+    pub fn remember(&mut self, file: &MpvFile) {
+        self.files.insert(file.url.clone(), file.clone());
+    }
+
+    /// Skips, seeks, pauses and stops end a crossfade right away, the current track plays on at
+    /// full volume and the rest of it doesn't crossfade anymore
+    ///
+    pub fn interrupt(&mut self, mpv: &Mpv) {
+        if self.overlap.is_some() {
+            self.fading_out = Some(false);
+        }
+        self.end_overlap(mpv);
+    }
+
+    /// Starts fading out, the pause happens once it's silent. Pauses right away if fades are off
+    ///
+    pub fn pause(&mut self, mpv: &Mpv) -> bool {
+        self.fade_out(mpv, FadeEnd::Pause)
+    }
+
+    pub fn stop(&mut self, mpv: &Mpv) -> bool {
+        self.fade_out(mpv, FadeEnd::Stop)
+    }
+
+    fn fade_out(&mut self, mpv: &Mpv, then: FadeEnd) -> bool {
+        let paused = mpv.get_property::<bool>("pause").unwrap_or(false);
+        if !self.installed
+            || self.transitions.pause_fade <= 0.0
+            || (paused && then == FadeEnd::Pause)
+        {
+            return Self::end(mpv, then);
+        }
+        let from = self.fade.as_ref().map_or(self.level, |f| f.level());
+        self.fade = Some(Fade {
+            from,
+            to: 0.0,
+            started: Instant::now(),
+            duration: Duration::from_secs_f64(self.transitions.pause_fade),
+            then,
+        });
+        true
+    }
+
+    fn end(mpv: &Mpv, then: FadeEnd) -> bool {
+        let res = match then {
+            FadeEnd::Nothing => Ok(()),
+            FadeEnd::Pause => mpv.set_property("pause", true),
+            FadeEnd::Stop => mpv.command("stop", &[]),
+        };
+        if let Err(e) = &res {
+            log::error!("mpv {:?} failed: {:?}", then, e);
+        }
+        res.is_ok()
+    }
+
+    /// Any other command cuts a pending fade short, so commands still run in the order they were sent
+    ///
+    pub fn finish(&mut self, mpv: &Mpv) {
+        if let Some(fade) = self.fade.take() {
+            self.level = fade.to;
+            Self::end(mpv, fade.then);
+            if fade.then == FadeEnd::Stop {
+                self.level = 1.0;
+            }
+            self.apply(mpv);
+        }
+    }
+
+    /// Updates the gain. Returns how soon it wants to be called again, if anything is fading.
+    /// `jumped` is set after seeks and new files, the position has to be read again then
+    ///
+    pub fn tick(
+        &mut self,
+        mpv: &Mpv,
+        current_index: usize,
+        paused: bool,
+        duration: f64,
+        speed: f64,
+        jumped: bool,
+    ) -> Option<Duration> {
+        if !self.installed {
+            return None;
+        }
+
+        // resumed, by us or by anything else (the pending resume unpauses on its own)
+        if self.last_paused && !paused && self.fade.is_none() && self.level < 1.0 {
+            self.fade = Some(Fade {
+                from: self.level,
+                to: 1.0,
+                started: Instant::now(),
+                duration: Duration::from_secs_f64(self.transitions.pause_fade.max(0.05)),
+                then: FadeEnd::Nothing,
+            });
+        }
+        // outside of fades the position is read on every (1s) tick, so buffering can't skew it
+        if !self.fast || jumped || paused != self.last_paused || speed != self.last_speed {
+            self.clock = None;
+        }
+        self.last_paused = paused;
+        self.last_speed = speed;
+
+        if let Some(level) = self.fade.as_ref().map(|f| f.level()) {
+            self.level = level;
+        }
+        if self.fade.as_ref().is_some_and(|f| f.done()) {
+            self.finish(mpv);
+        }
+
+        if self.last_index != Some(current_index) {
+            self.fading_out = None;
+            self.clock = None;
+            self.last_index = Some(current_index);
+            self.next_track(mpv, current_index);
+        }
+        if paused && self.overlap.as_ref().is_some_and(|o| matches!(o.stage, Stage::Fading)) {
+            self.interrupt(mpv);
+        }
+
+        let mut wants_tick = self.fade.is_some();
+        let crossfade = self.transitions.crossfade;
+        let mut transition = 1.0;
+        if self.overlap.as_ref().is_some_and(|o| matches!(o.stage, Stage::Handover { .. })) {
+            wants_tick = true;
+            self.handover(mpv, speed, jumped);
+            // muted, the filter stays down too in case something else unmutes it
+            if self.overlap.is_some() {
+                transition = 0.0;
+            }
+        } else if crossfade > 0.0 {
+            let position = self.position(mpv, speed);
+            let remaining = duration - position;
+
+            if duration > crossfade * 2.0 && remaining <= crossfade {
+                if self.fading_out.is_none() {
+                    self.fading_out = Some(!paused && self.start_overlap(mpv));
+                }
+            } else {
+                // seeked back out of the crossfade
+                self.fading_out = None;
+                self.end_overlap(mpv);
+            }
+
+            if self.fading_out == Some(true) && self.overlap.is_some() {
+                // equal power, together they are as loud as either track on its own
+                let t = (1.0 - remaining / crossfade).clamp(0.0, 1.0) * FRAC_PI_2;
+                transition = t.cos();
+                self.raise_second(mpv, t.sin());
+                if remaining <= HANDOVER_MUTE && !self.muted {
+                    self.muted = mpv.set_property("mute", true).is_ok();
+                }
+            }
+
+            // keep an eye on the end of the track a little early, the 1s tick is too coarse
+            wants_tick |= duration > 0.0 && remaining <= crossfade + 1.5;
+        } else {
+            self.fading_out = None;
+        }
+
+        self.transition = transition;
+        self.fast = wants_tick;
+        self.apply(mpv);
+        wants_tick.then_some(FADE_INTERVAL)
+    }
+
+    /// Position in the current track. Only read from mpv when the clock was reset
+    ///
+    fn position(&mut self, mpv: &Mpv, speed: f64) -> f64 {
+        let clock = self.clock.get_or_insert_with(|| Clock {
+            position: mpv.get_property::<f64>("time-pos").unwrap_or(0.0),
+            at: Instant::now(),
+        });
+        match self.last_paused {
+            true => clock.position,
+            false => clock.position + clock.at.elapsed().as_secs_f64() * speed,
+        }
+    }
+
+    /// The url the current track crossfades into. Only into a different album, and never while
+    /// repeating one track
+    ///
+    fn next_url(&self, mpv: &Mpv) -> Option<String> {
+        if mpv.get_property::<String>("loop-file").is_ok_and(|l| l != "no") {
+            return None;
+        }
+        let pos = mpv.get_property::<i64>("playlist-pos").ok()?;
+        let count = mpv.get_property::<i64>("playlist-count").unwrap_or(0);
+        let next = if pos + 1 < count {
+            pos + 1
+        } else if mpv.get_property::<String>("loop-playlist").is_ok_and(|l| l != "no") {
+            0
+        } else {
+            return None;
+        };
+        let current = mpv.get_property::<String>("path").unwrap_or_default();
+        let next = mpv.get_property::<String>(&format!("playlist/{}/filename", next)).ok()?;
+        if self.transitions.within_album {
+            return Some(next);
+        }
+        let album = |url: &String| self.files.get(url).map(|f| f.album_id.as_str());
+        match (album(&current), album(&next)) {
+            (Some(a), Some(b)) if !a.is_empty() && a == b => None,
+            _ => Some(next),
+        }
+    }
+
+    /// Starts the next track in the second mpv, silent until the first tick raises it
+    ///
+    fn start_overlap(&mut self, mpv: &Mpv) -> bool {
+        let Some(url) = self.next_url(mpv) else {
+            return false;
+        };
+        let second = match self.second.take() {
+            Some(second) => second,
+            None => match second_player() {
+                Ok(second) => second,
+                Err(e) => {
+                    log::error!("Failed to start the second mpv for crossfades: {:?}", e);
+                    return false;
+                }
+            },
+        };
+        // same output, filters (the equalizer) and speed as mpv, without the fade filter
+        for name in ["ao", "audio-device", "af", "speed", "audio-pitch-correction"] {
+            if let Ok(value) = mpv.get_property::<String>(name) {
+                let _ = second.set_property(name, value.as_str());
+            }
+        }
+        let _ = second.command("af", &["remove", &format!("@{}", FADE_FILTER_LABEL)]);
+        let _ = second.set_property("volume", 0.0);
+
+        let options = self.files.get(&url).map(MpvFile::options).unwrap_or_default();
+        let res = loadfile(&second, &url, "replace", None, &options, self.indexed_loadfile);
+        self.second = Some(second);
+        if let Err(e) = res {
+            log::error!("The second mpv failed to load '{}': {:?}", url, e);
+            return false;
+        }
+        self.overlap = Some(Overlap { url, stage: Stage::Fading });
+        true
+    }
+
+    /// Follows the volume the user set, mpv's volume is cubic so the gain is too
+    ///
+    fn raise_second(&self, mpv: &Mpv, gain: f64) {
+        let Some(second) = &self.second else {
+            return;
+        };
+        let volume = mpv.get_property::<f64>("volume").unwrap_or(100.0);
+        let _ = second.set_property("volume", volume * gain.cbrt());
+    }
+
+    /// mpv moved on. If it is the track the second mpv plays, mpv takes over from it
+    ///
+    fn next_track(&mut self, mpv: &Mpv, current_index: usize) {
+        let Some(overlap) = &mut self.overlap else {
+            return;
+        };
+        let url = mpv.get_property::<String>(&format!("playlist/{}/filename", current_index));
+        if !matches!(overlap.stage, Stage::Fading) || url.ok().as_ref() != Some(&overlap.url) {
+            self.end_overlap(mpv);
+            return;
+        }
+        if !self.muted {
+            self.muted = mpv.set_property("mute", true).is_ok();
+        }
+        overlap.stage = Stage::Handover { started: Instant::now(), seek: None, seeks: 0 };
+    }
+
+    /// Waits for mpv to start the track, then seeks it to where the second mpv is. Once it got
+    /// there mpv is unmuted and the second mpv stops
+    ///
+    fn handover(&mut self, mpv: &Mpv, speed: f64, jumped: bool) {
+        let (Some(overlap), Some(second)) = (&mut self.overlap, &self.second) else {
+            return;
+        };
+        let Stage::Handover { started, seek, seeks } = &mut overlap.stage else {
+            return;
+        };
+        if started.elapsed() > HANDOVER_TIMEOUT {
+            log::warn!("Crossfade handover to '{}' timed out", overlap.url);
+            self.end_overlap(mpv);
+            return;
+        }
+        // playback restarts once the file is loaded and after every seek
+        if !jumped {
+            return;
+        }
+        let Ok(target) = second.get_property::<f64>("time-pos") else {
+            self.end_overlap(mpv);
+            return;
+        };
+        if let Some(sent) = seek {
+            let position = mpv.get_property::<f64>("time-pos").unwrap_or(0.0);
+            if (target - position).abs() <= HANDOVER_TOLERANCE || *seeks >= HANDOVER_SEEKS {
+                self.end_overlap(mpv);
+                return;
+            }
+            self.lead = sent.elapsed().as_secs_f64() * speed;
+        }
+        let at = format!("{:.3}", target + self.lead);
+        if let Err(e) = mpv.command("seek", &[&at, "absolute+exact"]) {
+            log::error!("Crossfade handover seek failed: {:?}", e);
+            self.end_overlap(mpv);
+            return;
+        }
+        *seek = Some(Instant::now());
+        *seeks += 1;
+    }
+
+    /// Stops the second mpv and gives mpv its sound back
+    ///
+    fn end_overlap(&mut self, mpv: &Mpv) {
+        if self.overlap.take().is_some() {
+            if let Some(second) = &self.second {
+                let _ = second.command("stop", &[]);
+            }
+        }
+        if self.muted {
+            self.muted = false;
+            let _ = mpv.set_property("mute", false);
+        }
+    }
+
+    fn apply(&mut self, mpv: &Mpv) {
+        if !self.installed {
+            return;
+        }
+        // squared, a linear ramp sounds like it drops off too late. The crossfade has its own curve
+        let gain = self.level.powi(2) * self.transition;
+        if (gain - self.gain).abs() < 0.005 && !(gain == 1.0 && self.gain != 1.0) {
+            return;
+        }
+        self.gain = gain;
+        let _ = mpv.command("af-command", &[FADE_FILTER_LABEL, "volume", &format!("{:.3}", gain)]);
+    }
+}
+
+/// A bare mpv for the crossfade overlap, nothing reads its events
+///
+fn second_player() -> Result<Mpv, libmpv2::Error> {
+    let mpv = Mpv::with_initializer(|init| {
+        init.set_option("vo", "null")?;
+        init.set_option("vid", "no")?;
+        init.set_option("idle", "yes")?;
+        init.set_option("really-quiet", "yes")?;
+        Ok(())
+    })?;
+    let _ = mpv.disable_all_events();
+    Ok(mpv)
+}
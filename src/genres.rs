@@ -0,0 +1,499 @@
+/* --------------------------
+Genres tab
+    - Genres come from the cached `Genres` field of every track, so browsing works offline too
+    - Selecting a genre lists its tracks grouped by album
+    - Play, shuffle and append work on the whole genre through the popup
+-------------------------- */
+
+use crate::database::extension::{get_genre_tracks, get_genres, DownloadStatus};
+use crate::helpers;
+use crate::keyboard::{search_ranked_indices, ActiveSection, Searchable};
+use crate::popup::PopupMenu;
+use crate::tui::App;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{prelude::*, widgets::*, Frame};
+
+#[derive(Debug, Clone, Default)]
+pub struct Genre {
+    pub name: String,
+    pub track_count: i64,
+    pub album_count: i64,
+}
+
+impl Searchable for Genre {
+    fn id(&self) -> &str {
+        &self.name
+    }
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl App {
+    /// Reloads the genre list and the tracks of the open genre. Called on startup, when the tab
+    /// is opened and after the library was synchronized
+    ///
+    pub async fn refresh_genres(&mut self) {
+        match get_genres(&self.db.pool, self.client.is_some()).await {
+            Ok(genres) => self.genres = genres,
+            Err(e) => log::error!("Failed to load genres: {}", e),
+        }
+        let selected = self.state.selected_genre.selected().unwrap_or(0);
+        self.genre_select_by_index(selected);
+
+        if !self.state.current_genre.is_empty() {
+            let genre = self.state.current_genre.clone();
+            let selected = self.state.selected_genre_track.selected().unwrap_or(0);
+            self.open_genre(&genre).await;
+            self.genre_track_select_by_index(selected);
+        }
+    }
+
+    /// Loads the tracks of a genre into the center table
+    ///
+    pub async fn open_genre(&mut self, name: &str) {
+        if self.state.current_genre != name {
+            self.state.selected_genre_track.select_first();
+        }
+        self.genre_tracks = match get_genre_tracks(&self.db.pool, name, self.client.is_some()).await
+        {
+            Ok(tracks) => tracks,
+            Err(e) => {
+                log::error!("Failed to load tracks of genre '{}': {}", name, e);
+                vec![]
+            }
+        };
+        self.state.current_genre = name.to_string();
+        let selected = self.state.selected_genre_track.selected().unwrap_or(0);
+        self.genre_track_select_by_index(selected);
+    }
+
+    pub fn selected_genre(&self) -> Option<&Genre> {
+        self.genres.get(self.state.selected_genre.selected()?)
+    }
+
+    /// Makes sure the genre under the cursor is the open one, the popup actions work on it
+    ///
+    pub async fn open_selected_genre(&mut self) {
+        let Some(genre) = self.selected_genre().map(|g| g.name.clone()) else {
+            return;
+        };
+        if genre != self.state.current_genre || self.genre_tracks.is_empty() {
+            self.open_genre(&genre).await;
+        }
+    }
+
+    pub fn genre_select_by_index(&mut self, index: usize) {
+        if self.genres.is_empty() {
+            self.state.selected_genre.select(None);
+            return;
+        }
+        let index = index.min(self.genres.len() - 1);
+        self.state.selected_genre.select(Some(index));
+        self.state.genres_scroll_state =
+            self.state.genres_scroll_state.content_length(self.genres.len()).position(index);
+    }
+
+    pub fn genre_track_select_by_index(&mut self, index: usize) {
+        if self.genre_tracks.is_empty() {
+            self.state.selected_genre_track.select(None);
+            return;
+        }
+        let index = index.min(self.genre_tracks.len() - 1);
+        self.state.selected_genre_track.select(Some(index));
+        self.state.genre_tracks_scroll_state = self
+            .state
+            .genre_tracks_scroll_state
+            .content_length(self.genre_tracks.len())
+            .position(index);
+    }
+
+    fn move_genre_selection(&mut self, delta: isize) {
+        match self.state.active_section {
+            ActiveSection::Tracks => {
+                let current = self.state.selected_genre_track.selected().unwrap_or(0);
+                self.genre_track_select_by_index(current.saturating_add_signed(delta));
+            }
+            _ => {
+                let current = self.state.selected_genre.selected().unwrap_or(0);
+                self.genre_select_by_index(current.saturating_add_signed(delta));
+            }
+        }
+    }
+
+    /// Keys that behave differently in the Genres tab. Returns false for everything else,
+    /// which is then handled like in any other tab (playback, volume, popups, tab switching...)
+    ///
+    pub async fn handle_genres_key_event(&mut self, key_event: KeyEvent) -> bool {
+        // the genres tab has no queue or lyrics pane
+        if !matches!(self.state.active_section, ActiveSection::List | ActiveSection::Tracks) {
+            self.state.active_section = ActiveSection::List;
+        }
+        match key_event.code {
+            KeyCode::Down => self.move_genre_selection(1),
+            KeyCode::Up => self.move_genre_selection(-1),
+            KeyCode::PageDown => {
+                let page = match self.state.active_section {
+                    ActiveSection::Tracks => self.track_list_height,
+                    _ => self.left_list_height,
+                };
+                self.move_genre_selection(page as isize);
+            }
+            KeyCode::PageUp => {
+                let page = match self.state.active_section {
+                    ActiveSection::Tracks => self.track_list_height,
+                    _ => self.left_list_height,
+                };
+                self.move_genre_selection(-(page as isize));
+            }
+            KeyCode::Char('g') => self.move_genre_selection(isize::MIN),
+            KeyCode::Char('G') => self.move_genre_selection(isize::MAX),
+            KeyCode::Tab | KeyCode::BackTab | KeyCode::Char('h') | KeyCode::Char('l') => {
+                self.state.active_section = match self.state.active_section {
+                    ActiveSection::List if !self.genre_tracks.is_empty() => ActiveSection::Tracks,
+                    _ => ActiveSection::List,
+                };
+            }
+            // jump between albums of the genre
+            KeyCode::Char('a') | KeyCode::Char('A') => {
+                if self.state.active_section != ActiveSection::Tracks {
+                    return true;
+                }
+                let tracks = &self.genre_tracks;
+                let current = self.state.selected_genre_track.selected().unwrap_or(0);
+                if current >= tracks.len() {
+                    return true;
+                }
+                let album_start = |i: usize| {
+                    tracks[..i]
+                        .iter()
+                        .rposition(|t| t.album_id != tracks[i].album_id)
+                        .map_or(0, |p| p + 1)
+                };
+                let index = if key_event.code == KeyCode::Char('a') {
+                    tracks
+                        .iter()
+                        .skip(current)
+                        .position(|t| t.album_id != tracks[current].album_id)
+                        .map_or(current, |i| current + i)
+                } else {
+                    // start of this album, or of the previous one when already there
+                    match album_start(current) {
+                        start if start == current && current > 0 => album_start(current - 1),
+                        start => start,
+                    }
+                };
+                self.genre_track_select_by_index(index);
+            }
+            KeyCode::Enter => match self.state.active_section {
+                ActiveSection::Tracks => {
+                    let selected = self.state.selected_genre_track.selected().unwrap_or(0);
+                    self.initiate_main_queue(&self.genre_tracks.clone(), selected).await;
+                }
+                _ => {
+                    let Some(genre) = self.selected_genre().map(|g| g.name.clone()) else {
+                        return true;
+                    };
+                    self.open_genre(&genre).await;
+                    if !self.genre_tracks.is_empty() {
+                        self.state.active_section = ActiveSection::Tracks;
+                    }
+                }
+            },
+            KeyCode::Char('e') => {
+                let (skip, n) = match self.state.active_section {
+                    ActiveSection::Tracks => {
+                        (self.state.selected_genre_track.selected().unwrap_or(0), 1)
+                    }
+                    _ => {
+                        self.open_selected_genre().await;
+                        (0, self.genre_tracks.len())
+                    }
+                };
+                let tracks = self.genre_tracks.clone();
+                if tracks.is_empty() {
+                    return true;
+                }
+                if key_event.modifiers == KeyModifiers::CONTROL && n == 1 {
+                    self.push_next_to_temporary_queue(&tracks, skip).await;
+                } else {
+                    self.push_to_temporary_queue(&tracks, skip, n).await;
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Local search (`/`) in the Genres tab. It only moves the cursor to the best match instead of
+    /// filtering, so the tracks stay grouped by album
+    ///
+    pub fn handle_genre_search_key(&mut self, key_event: KeyEvent) {
+        match key_event.code {
+            KeyCode::Esc | KeyCode::F(1) | KeyCode::Enter => {
+                self.locally_searching = false;
+                self.genre_search_term.clear();
+                return;
+            }
+            KeyCode::Backspace => {
+                self.genre_search_term.pop();
+            }
+            KeyCode::Delete => self.genre_search_term.clear(),
+            KeyCode::Char(c) => self.genre_search_term.push(c),
+            _ => return,
+        }
+        let term = &self.genre_search_term;
+        match self.state.active_section {
+            ActiveSection::Tracks => {
+                if let Some(&index) = search_ranked_indices(&self.genre_tracks, term, false).first()
+                {
+                    self.genre_track_select_by_index(index);
+                }
+            }
+            _ => {
+                if let Some(&index) = search_ranked_indices(&self.genres, term, false).first() {
+                    self.genre_select_by_index(index);
+                }
+            }
+        }
+    }
+
+    pub fn genre_popup(&self) -> Option<PopupMenu> {
+        let genre = match self.state.active_section {
+            ActiveSection::Tracks => self.state.current_genre.clone(),
+            _ => self.selected_genre()?.name.clone(),
+        };
+        if genre.is_empty() {
+            return None;
+        }
+        Some(PopupMenu::GenreRoot { genre_name: genre })
+    }
+
+    pub fn render_genres(&mut self, app_container: Rect, frame: &mut Frame) {
+        let layout = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![Constraint::Percentage(30), Constraint::Percentage(70)])
+            .split(app_container);
+        self.layout_areas.list = layout[0];
+        self.layout_areas.tracks = layout[1];
+
+        let foreground = self.theme.resolve(&self.theme.foreground);
+        let foreground_dim = self.theme.resolve(&self.theme.foreground_dim);
+        let section_title = self.theme.resolve(&self.theme.section_title);
+        let [genres_title_color, tracks_title_color] = match self.state.active_section {
+            ActiveSection::Tracks => [section_title, self.theme.primary_color],
+            _ => [self.theme.primary_color, section_title],
+        };
+        let block = |focused: bool| {
+            Block::new().borders(Borders::ALL).border_type(self.border_type).border_style(
+                self.theme.resolve(if focused {
+                    &self.theme.border_focused
+                } else {
+                    &self.theme.border
+                }),
+            )
+        };
+        let highlight_style = |focused: bool| match focused {
+            true => Style::default()
+                .bg(self.theme.resolve(&self.theme.selected_active_background))
+                .fg(self.theme.resolve(&self.theme.selected_active_foreground))
+                .add_modifier(Modifier::BOLD),
+            false => Style::default()
+                .bg(self.theme.resolve(&self.theme.selected_inactive_background))
+                .fg(self.theme.resolve(&self.theme.selected_inactive_foreground))
+                .add_modifier(Modifier::BOLD),
+        };
+        let list_focused = self.state.active_section != ActiveSection::Tracks;
+
+        // genres
+        let genre_block = block(list_focused);
+        self.left_list_height = genre_block.inner(layout[0]).height.max(1) as usize;
+        let items = self
+            .genres
+            .iter()
+            .map(|genre| {
+                let color = if genre.name == self.state.current_genre {
+                    self.theme.primary_color
+                } else {
+                    foreground
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(genre.name.clone(), Style::default().fg(color)),
+                    Span::styled(
+                        format!(" {} / {}", genre.album_count, genre.track_count),
+                        Style::default().fg(foreground_dim),
+                    ),
+                ]))
+            })
+            .collect::<Vec<ListItem>>();
+        let list = List::new(items)
+            .block(
+                genre_block
+                    .title_top(Line::from("Genres").fg(genres_title_color).left_aligned())
+                    .title_top(
+                        Line::from(format!("({} genres)", self.genres.len()))
+                            .fg(genres_title_color)
+                            .right_aligned(),
+                    )
+                    .title_bottom(
+                        Line::from(vec![
+                            " albums / tracks ".fg(foreground_dim),
+                            "<p>".fg(self.theme.primary_color).bold(),
+                            " play, shuffle, append ".fg(section_title),
+                        ])
+                        .alignment(Alignment::Center),
+                    ),
+            )
+            .highlight_symbol(">>")
+            .highlight_style(highlight_style(list_focused))
+            .scroll_padding(10)
+            .repeat_highlight_symbol(true);
+        frame.render_stateful_widget(list, layout[0], &mut self.state.selected_genre);
+        helpers::render_scrollbar(
+            frame,
+            layout[0],
+            &mut self.state.genres_scroll_state,
+            &self.theme,
+        );
+        if list_focused {
+            self.render_genre_search(frame, layout[0]);
+        }
+
+        // tracks of the open genre, the album is only named on its first track
+        let track_block = block(!list_focused);
+        self.track_list_height =
+            track_block.inner(layout[1]).height.saturating_sub(1).max(1) as usize;
+
+        if self.genre_tracks.is_empty() {
+            let message = Paragraph::new(if self.state.current_genre.is_empty() {
+                "Select a genre with <Enter>"
+            } else {
+                "No tracks in this genre"
+            })
+            .fg(foreground)
+            .block(
+                track_block
+                    .title(Line::from("Tracks").fg(tracks_title_color).left_aligned())
+                    .padding(Padding::new(0, 0, layout[1].height / 2, 0)),
+            )
+            .alignment(Alignment::Center);
+            frame.render_widget(message, layout[1]);
+            return;
+        }
+
+        let rows = self
+            .genre_tracks
+            .iter()
+            .enumerate()
+            .map(|(i, track)| {
+                let first_of_album = i == 0 || self.genre_tracks[i - 1].album_id != track.album_id;
+                let seconds = (track.run_time_ticks / 10_000_000) % 60;
+                let minutes = track.run_time_ticks / 10_000_000 / 60;
+                let album = match first_of_album {
+                    true => Line::from(vec![
+                        Span::styled(
+                            track.album.clone(),
+                            Style::default().fg(self.theme.primary_color).bold(),
+                        ),
+                        Span::styled(
+                            match track.production_year {
+                                0 => String::new(),
+                                year => format!(" ({})", year),
+                            },
+                            Style::default().fg(foreground_dim),
+                        ),
+                    ]),
+                    false => Line::default(),
+                };
+                Row::new(vec![
+                    Cell::from(album),
+                    Cell::from(format!("{}.", track.index_number))
+                        .style(Style::default().fg(Color::DarkGray)),
+                    Cell::from(track.name.clone()),
+                    Cell::from(track.artists.join(", ")),
+                    Cell::from(match track.download_status {
+                        DownloadStatus::Downloaded => "⇊",
+                        DownloadStatus::Queued => "◴",
+                        DownloadStatus::Downloading => self.spinner_stages[self.spinner],
+                        DownloadStatus::NotDownloaded => "",
+                    }),
+                    Cell::from(if track.user_data.is_favorite { "♥" } else { "" })
+                        .style(Style::default().fg(self.theme.primary_color)),
+                    Cell::from(format!("{}:{:02}", minutes, seconds)),
+                ])
+                .style(if track.id == self.active_song_id {
+                    Style::default().fg(self.theme.primary_color).italic()
+                } else if track.disliked {
+                    Style::default().fg(foreground_dim)
+                } else {
+                    Style::default().fg(foreground)
+                })
+            })
+            .collect::<Vec<Row>>();
+
+        let album_count = self
+            .genres
+            .iter()
+            .find(|g| g.name == self.state.current_genre)
+            .map_or(0, |g| g.album_count);
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Percentage(30),
+                Constraint::Length(5),
+                Constraint::Percentage(40),
+                Constraint::Percentage(30),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(6),
+            ],
+        )
+        .header(
+            Row::new(vec!["Album", "No.", "Title", "Artist", "⇊", "♥", "Length"])
+                .style(Style::new().bold().fg(foreground)),
+        )
+        .block(
+            track_block
+                .title(Line::from(self.state.current_genre.clone()).fg(tracks_title_color))
+                .title_top(
+                    Line::from(format!(
+                        "({} albums - {} tracks)",
+                        album_count,
+                        self.genre_tracks.len()
+                    ))
+                    .fg(tracks_title_color)
+                    .right_aligned(),
+                ),
+        )
+        .row_highlight_style(highlight_style(!list_focused))
+        .highlight_symbol(">>")
+        .style(
+            Style::default()
+                .bg(self.theme.resolve_opt(&self.theme.background).unwrap_or(Color::Reset)),
+        );
+        frame.render_stateful_widget(table, layout[1], &mut self.state.selected_genre_track);
+        helpers::render_scrollbar(
+            frame,
+            layout[1],
+            &mut self.state.genre_tracks_scroll_state,
+            &self.theme,
+        );
+        if !list_focused {
+            self.render_genre_search(frame, layout[1]);
+        }
+    }
+
+    fn render_genre_search(&self, frame: &mut Frame, area: Rect) {
+        if !self.locally_searching {
+            return;
+        }
+        frame.render_widget(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Searching: {}", self.genre_search_term))
+                .border_type(self.border_type)
+                .border_style(self.theme.resolve(&self.theme.border_focused)),
+            area,
+        );
+    }
+}
@@ -3,9 +3,18 @@ Help page rendering functions
     - Pressing '?' in any tab should show the help page in its place
     - should of an equivalent layout
 -------------------------- */
+use crate::keybindings::KeyAction;
 use ratatui::{prelude::*, widgets::*, Frame};
 
 impl crate::tui::App {
+    /// Active bindings of the given actions, styled like the other key labels
+    ///
+    fn help_keys(&self, actions: &[KeyAction]) -> Span<'static> {
+        let label =
+            actions.iter().map(|a| self.keybindings.label(*a)).collect::<Vec<_>>().join(", ");
+        label.fg(self.theme.primary_color).bold()
+    }
+
     pub fn render_home_help(&mut self, app_container: Rect, frame: &mut Frame) {
         let outer_layout = Layout::default()
             .direction(Direction::Horizontal)
@@ -40,59 +49,59 @@ impl crate::tui::App {
             Line::from("Usage:").fg(self.theme.resolve(&self.theme.foreground)).underlined(),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)).bold(),
-                "<↑/↓>".fg(self.theme.primary_color).bold(),
-                " (j/k) to navigate".fg(self.theme.resolve(&self.theme.foreground)),
+                self.help_keys(&[KeyAction::Down, KeyAction::Up]),
+                " to navigate".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "<Enter>".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Select]),
                 " to select".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "Tab".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::NextSection]),
                 " to switch to Tracks".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "Shift + Tab".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::PreviousSection]),
                 " to switch to Lyrics".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "a".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::NextAlbum]),
                 " to skip to next album".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "A".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::PreviousAlbum]),
                 " to skip to previous album".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "g".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Top]),
                 " to skip to the top of the list".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "G".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Bottom]),
                 " to skip to the bottom of the list".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "f".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Favorite]),
                 " to favorite an artist".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(""),
             Line::from("Searching:").fg(self.theme.resolve(&self.theme.foreground)).underlined(),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "/".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Search]),
                 " to start searching".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "Esc".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Back]),
                 " to clear search".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
@@ -125,67 +134,87 @@ impl crate::tui::App {
             Line::from("Usage:").fg(self.theme.resolve(&self.theme.foreground)).underlined(),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "<↑/↓>".fg(self.theme.primary_color).bold(),
-                " (j/k) to navigate".fg(self.theme.resolve(&self.theme.foreground)),
+                self.help_keys(&[KeyAction::Down, KeyAction::Up]),
+                " to navigate".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             // "  - Use Enter to play a song",
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "<Enter>".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Select]),
                 " to play a song".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "Tab".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::NextSection]),
                 " to switch to Artists".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "Shift + Tab".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::PreviousSection]),
                 " to switch to Lyrics".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "g".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Top]),
                 " to skip to the top of the list".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "G".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Bottom]),
                 " to skip to the bottom of the list".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "a".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::NextAlbum]),
                 " to jump to next album".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "A".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::PreviousAlbum]),
                 " to jump to previous album, or start of current"
                     .fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "f".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Favorite]),
                 " to favorite a song".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "d".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Download]),
                 " to download a song or album, press again to delete download"
                     .fg(self.theme.resolve(&self.theme.foreground)),
             ]),
+            Line::from(vec![
+                "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
+                self.help_keys(&[KeyAction::VisualSelect]),
+                " to select a range of tracks, ".fg(self.theme.resolve(&self.theme.foreground)),
+                self.help_keys(&[KeyAction::ToggleMark]),
+                " to select a single one".fg(self.theme.resolve(&self.theme.foreground)),
+            ]),
+            Line::from(vec![
+                "  - With tracks selected ".fg(self.theme.resolve(&self.theme.foreground)),
+                self.help_keys(&[KeyAction::Popup]),
+                " acts on all of them, ".fg(self.theme.resolve(&self.theme.foreground)),
+                self.help_keys(&[KeyAction::Back]),
+                " clears the selection".fg(self.theme.resolve(&self.theme.foreground)),
+            ]),
+            Line::from(vec![
+                "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
+                self.help_keys(&[KeyAction::TrackInfo]),
+                " to see the codec, bitrate, file and playback details of a track"
+                    .fg(self.theme.resolve(&self.theme.foreground)),
+            ]),
             Line::from(""),
             Line::from("Searching:").fg(self.theme.resolve(&self.theme.foreground)).underlined(),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "/".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Search]),
                 " to start searching".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "Esc".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Back]),
                 " to clear search".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
@@ -197,19 +226,25 @@ impl crate::tui::App {
             Line::from("General").underlined().fg(self.theme.resolve(&self.theme.foreground)),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "?".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Help]),
                 " to show this help".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "F1..FX".fg(self.theme.primary_color).bold(),
-                " or ".fg(self.theme.resolve(&self.theme.foreground)),
-                "1..9".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[
+                    KeyAction::TabHome,
+                    KeyAction::TabLibrary,
+                    KeyAction::TabAlbums,
+                    KeyAction::TabPlaylists,
+                    KeyAction::TabSearch,
+                    KeyAction::TabStats,
+                    KeyAction::TabGenres,
+                ]),
                 " to switch tabs".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "q".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Quit]),
                 " or ".fg(self.theme.resolve(&self.theme.foreground)),
                 "ctrl + c".fg(self.theme.primary_color).bold(),
                 " to quit".fg(self.theme.resolve(&self.theme.foreground)),
@@ -234,49 +269,49 @@ impl crate::tui::App {
             Line::from("Usage:").fg(self.theme.resolve(&self.theme.foreground)).underlined(),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "<↑/↓>".fg(self.theme.primary_color).bold(),
-                " (j/k) to navigate".fg(self.theme.resolve(&self.theme.foreground)),
+                self.help_keys(&[KeyAction::Down, KeyAction::Up]),
+                " to navigate".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "Shift + <↑/↓>".fg(self.theme.primary_color).bold(),
-                " (J/K) to change order".fg(self.theme.resolve(&self.theme.foreground)),
+                self.help_keys(&[KeyAction::MoveDown, KeyAction::MoveUp]),
+                " to change order".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "<Enter>".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Select]),
                 " to play a song".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "Delete".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::RemoveFromQueue]),
                 " to remove a song from the queue".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "x".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Stop]),
                 " to clear the queue and stop playback"
                     .fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "X".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Reset]),
                 " to clear the queue and also unselect everything"
                     .fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "f".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Favorite]),
                 " to favorite a song".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "g".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Top]),
                 " to skip to the top of the list".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "G".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Bottom]),
                 " to skip to the bottom of the list".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from("Creation:").fg(self.theme.resolve(&self.theme.foreground)).underlined(),
@@ -287,28 +322,24 @@ impl crate::tui::App {
             Line::from(""),
             Line::from(vec![
                 "  - Playing a song with ".fg(self.theme.resolve(&self.theme.foreground)),
-                "<Enter>".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Select]),
                 " will create a new main queue".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "e".fg(self.theme.primary_color).bold(),
-                ", or ".fg(self.theme.resolve(&self.theme.foreground)),
-                "shift + Enter".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Enqueue]),
                 " to enqueue a song (temporary queue)"
                     .fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "ctrl + e".fg(self.theme.primary_color).bold(),
-                ", or ".fg(self.theme.resolve(&self.theme.foreground)),
-                "ctrl + Enter".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::PlayNext]),
                 " play next in the queue (temporary queue)"
                     .fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "E".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::ClearQueue]),
                 " to clear the temporary queue".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
         ];
@@ -338,32 +369,32 @@ impl crate::tui::App {
             Line::from("Usage:").fg(self.theme.resolve(&self.theme.foreground)).underlined(),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "<↑/↓>".fg(self.theme.primary_color).bold(),
-                " (j/k) to navigate".fg(self.theme.resolve(&self.theme.foreground)),
+                self.help_keys(&[KeyAction::Down, KeyAction::Up]),
+                " to navigate".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "<Enter>".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Select]),
                 " to jump to the current lyric".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "Tab".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::NextSection]),
                 " to switch to previous Pane".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "Shift + Tab".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::PreviousSection]),
                 " to switch to Queue".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "g".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Top]),
                 " to select the first lyric".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "G".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Bottom]),
                 " to select the last lyric".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(""),
@@ -388,62 +419,89 @@ impl crate::tui::App {
             Line::from("Usage:").fg(self.theme.resolve(&self.theme.foreground)).underlined(),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "Space".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::PlayPause]),
                 " to play/pause".fg(self.theme.resolve(&self.theme.foreground)),
                 "\t".into(),
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "r".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Repeat]),
                 " to toggle Replay None->All(*)->One(1)"
                     .fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "←/→".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::SeekBackward, KeyAction::SeekForward]),
                 " to seek 5s bck/fwd".fg(self.theme.resolve(&self.theme.foreground)),
                 "\t".into(),
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "p".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Popup]),
                 " to open the command menu".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                ",/.".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::SeekBackwardLong, KeyAction::SeekForwardLong]),
                 " to seek 1m bck/fwd".fg(self.theme.resolve(&self.theme.foreground)),
                 "\t".into(),
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "P".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::GlobalPopup]),
                 " to open the GLOBAL command menu".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "+/-".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::VolumeUp, KeyAction::VolumeDown]),
                 " to change volume".fg(self.theme.resolve(&self.theme.foreground)),
                 "\t".into(),
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "R".fg(self.theme.primary_color).bold(),
-                " to toggle repeat".fg(self.theme.resolve(&self.theme.foreground)),
+                self.help_keys(&[KeyAction::NextTrack, KeyAction::PreviousTrack]),
+                " to play next/previous track".fg(self.theme.resolve(&self.theme.foreground)),
+            ]),
+            Line::from(vec![
+                "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
+                self.help_keys(&[KeyAction::SpeedUp, KeyAction::SpeedDown]),
+                " to change speed".fg(self.theme.resolve(&self.theme.foreground)),
+                "\t".into(),
+                "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
+                self.help_keys(&[KeyAction::SpeedReset]),
+                " to reset speed".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "s".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::UndoQueue]),
+                " to undo a queue change".fg(self.theme.resolve(&self.theme.foreground)),
+                "\t".into(),
+                "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
+                self.help_keys(&[KeyAction::RedoQueue]),
+                " to redo it".fg(self.theme.resolve(&self.theme.foreground)),
+            ]),
+            Line::from(vec![
+                "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
+                self.help_keys(&[KeyAction::AbLoop]),
+                " to set A, set B, clear the loop".fg(self.theme.resolve(&self.theme.foreground)),
+                "\t".into(),
+                "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
+                self.help_keys(&[KeyAction::SeekTo]),
+                " to seek to a timestamp".fg(self.theme.resolve(&self.theme.foreground)),
+            ]),
+            Line::from(vec![
+                "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
+                self.help_keys(&[KeyAction::Shuffle]),
                 " to toggle shuffle".fg(self.theme.resolve(&self.theme.foreground)),
                 "\t".into(),
                 " - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "Ctrl+(Left/h)".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::ShrinkPane]),
                 " shrink current section".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "Ctrl+s".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::GlobalShuffle]),
                 " to shuffle globally".fg(self.theme.resolve(&self.theme.foreground)),
                 "\t".into(),
                 " - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "Ctrl+(Right/l)".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::WidenPane]),
                 " expand current section".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "T".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::ToggleTranscoding]),
                 " to toggle transcoding".fg(self.theme.resolve(&self.theme.foreground)),
                 "\t".into(),
             ]),
@@ -492,61 +550,61 @@ impl crate::tui::App {
             Line::from("Usage:").fg(self.theme.resolve(&self.theme.foreground)).underlined(),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "<↑/↓>".fg(self.theme.primary_color).bold(),
-                " (j/k) to navigate".fg(self.theme.resolve(&self.theme.foreground)),
+                self.help_keys(&[KeyAction::Down, KeyAction::Up]),
+                " to navigate".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "<Enter>".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Select]),
                 " to select".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "Tab".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::NextSection]),
                 " to switch to Tracks".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "Shift + Tab".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::PreviousSection]),
                 " to switch to Lyrics".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "a".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::NextAlbum]),
                 " to skip to alphabetically next playlist"
                     .fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "A".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::PreviousAlbum]),
                 " to skip to alphabetically previous playlist"
                     .fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "g".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Top]),
                 " to skip to the top of the list".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "G".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Bottom]),
                 " to skip to the bottom of the list".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "f".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Favorite]),
                 " to favorite a playlist".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(""),
             Line::from("Searching:").underlined(),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "/".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Search]),
                 " to start searching".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "Esc".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Back]),
                 " to clear search".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
@@ -581,7 +639,7 @@ impl crate::tui::App {
             Line::from("Usage:").fg(self.theme.resolve(&self.theme.foreground)).underlined(),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "p".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::Popup]),
                 " to open a menu with commands to use".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(""),
@@ -589,12 +647,12 @@ impl crate::tui::App {
             Line::from("You can change the order of a playlist using the keybindings below. Note that you can only change a playlist once if fully loads, indicated by the spinner next to its name in the section header.").fg(self.theme.resolve(&self.theme.foreground)),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "Shift+j / Shift+Down".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::MoveDown]),
                 " to move a track down".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
                 "  - Use ".fg(self.theme.resolve(&self.theme.foreground)),
-                "Shift+k / Shift+Up".fg(self.theme.primary_color).bold(),
+                self.help_keys(&[KeyAction::MoveUp]),
                 " to move a track up".fg(self.theme.resolve(&self.theme.foreground)),
             ]),
             Line::from(vec![
@@ -2,8 +2,13 @@ use crate::client::DiscographySong;
 use crate::themes::theme::Theme;
 use crate::{
     client::{Album, Artist, Playlist},
+    dashboard::Shelf,
+    equalizer::EqSettings,
     keyboard::{ActiveSection, ActiveTab, SearchSection},
     popup::PopupMenu,
+    radio::Radio,
+    sleep_timer::SleepTimer,
+    smart_shuffle::ShuffleMode,
     tui::{Filter, MpvPlaybackState, Repeat, Song, Sort},
 };
 use chrono::DateTime;
@@ -12,6 +17,7 @@ use ratatui::layout::{Margin, Rect};
 use ratatui::style::Style;
 use ratatui::widgets::{ListState, Scrollbar, ScrollbarOrientation, ScrollbarState, TableState};
 use ratatui::Frame;
+use std::collections::HashMap;
 use std::fs::OpenOptions;
 
 pub fn find_all_subsequences(needle: &str, haystack: &str) -> Vec<(usize, usize)> {
@@ -72,6 +78,37 @@ pub fn format_release_date(s: &str) -> Option<String> {
     DateTime::parse_from_rfc3339(s).ok().map(|dt| dt.format(" (%-d %b %Y)").to_string())
 }
 
+/// m:ss, or h:mm:ss for anything longer than an hour
+pub fn format_timestamp(seconds: f64) -> String {
+    let seconds = seconds.max(0.0) as u64;
+    if seconds >= 3600 {
+        format!("{}:{:02}:{:02}", seconds / 3600, seconds % 3600 / 60, seconds % 60)
+    } else {
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+/// Parses "90", "1:30", "1:30.5" or "1:02:30" into seconds
+pub fn parse_timestamp(s: &str) -> Option<f64> {
+    let parts = s.trim().split(':').collect::<Vec<&str>>();
+    if parts.len() > 3 || parts.iter().any(|p| p.trim().is_empty()) {
+        return None;
+    }
+    let (last, rest) = parts.split_last()?;
+    let mut seconds = last.trim().parse::<f64>().ok().filter(|s| *s >= 0.0)?;
+    if !rest.is_empty() && seconds >= 60.0 {
+        return None;
+    }
+    for (i, part) in rest.iter().rev().enumerate() {
+        let value = part.trim().parse::<u64>().ok()?;
+        if i == 0 && rest.len() == 2 && value >= 60 {
+            return None;
+        }
+        seconds += value as f64 * 60f64.powi(i as i32 + 1);
+    }
+    Some(seconds)
+}
+
 pub fn render_scrollbar<'a>(
     frame: &mut Frame,
     area: Rect,
@@ -120,6 +157,8 @@ pub struct State {
     pub current_album: Album,
     #[serde(default)]
     pub current_playlist: Playlist,
+    #[serde(default)]
+    pub current_genre: String,
 
     // ratatui list indexes
     #[serde(default)]
@@ -135,6 +174,14 @@ pub struct State {
     #[serde(default)]
     pub selected_playlist: ListState,
     #[serde(default)]
+    pub selected_genre: ListState,
+    #[serde(default)]
+    pub selected_genre_track: TableState,
+    #[serde(default)]
+    pub selected_shelf: Shelf,
+    #[serde(default)]
+    pub selected_shelf_items: [ListState; 5],
+    #[serde(default)]
     pub artists_scroll_state: ScrollbarState,
     #[serde(default)]
     pub tracks_scroll_state: ScrollbarState,
@@ -147,6 +194,10 @@ pub struct State {
     #[serde(default)]
     pub playlist_tracks_scroll_state: ScrollbarState,
     #[serde(default)]
+    pub genres_scroll_state: ScrollbarState,
+    #[serde(default)]
+    pub genre_tracks_scroll_state: ScrollbarState,
+    #[serde(default)]
     pub selected_queue_item: ListState,
     #[serde(default)]
     pub selected_queue_item_manual_override: bool,
@@ -186,6 +237,10 @@ pub struct State {
 
     #[serde(default)]
     pub shuffle: bool,
+    #[serde(default)]
+    pub radio: Option<Radio>,
+    #[serde(default)]
+    pub sleep_timer: Option<SleepTimer>,
 
     #[serde(default)]
     pub current_playback_state: MpvPlaybackState,
@@ -198,22 +253,30 @@ impl State {
             active_section: ActiveSection::default(),
             last_section: ActiveSection::default(),
             search_section: SearchSection::default(),
-            active_tab: ActiveTab::default(),
+            // a fresh start opens on Home, saved states on the tab they were left on
+            active_tab: ActiveTab::Home,
             current_artist: Artist::default(),
             current_album: Album::default(),
             current_playlist: Playlist::default(),
+            current_genre: String::new(),
             selected_artist: ListState::default(),
             selected_track: TableState::default(),
             selected_album: ListState::default(),
             selected_album_track: TableState::default(),
             selected_playlist_track: TableState::default(),
             selected_playlist: ListState::default(),
+            selected_genre: ListState::default(),
+            selected_genre_track: TableState::default(),
+            selected_shelf: Shelf::default(),
+            selected_shelf_items: Default::default(),
             tracks_scroll_state: ScrollbarState::default(),
             albums_scroll_state: ScrollbarState::default(),
             album_tracks_scroll_state: ScrollbarState::default(),
             artists_scroll_state: ScrollbarState::default(),
             playlists_scroll_state: ScrollbarState::default(),
             playlist_tracks_scroll_state: ScrollbarState::default(),
+            genres_scroll_state: ScrollbarState::default(),
+            genre_tracks_scroll_state: ScrollbarState::default(),
             selected_queue_item: ListState::default(),
             selected_queue_item_manual_override: false,
             selected_lyric: ListState::default(),
@@ -236,12 +299,15 @@ impl State {
             search_track_scroll_state: ScrollbarState::default(),
 
             shuffle: false,
+            radio: None,
+            sleep_timer: None,
 
             current_playback_state: MpvPlaybackState {
                 position: 0.0,
                 duration: 0.0,
                 current_index: 0,
                 volume: 100,
+                speed: 1.0,
                 audio_bitrate: 0,
                 audio_samplerate: 0,
                 file_format: String::from(""),
@@ -339,6 +405,31 @@ pub struct Preferences {
     // here we define the preferred percentage splits for each section. Must add up to 100.
     #[serde(default = "Preferences::default_music_column_widths")]
     pub constraint_width_percentages_music: (u16, u16, u16), // (Artists, Albums, Tracks)
+
+    // equalizer settings per output device
+    #[serde(default)]
+    pub equalizer: HashMap<String, EqSettings>,
+    // crossfade and fades, None follows the config
+    #[serde(default)]
+    pub transitions: Option<bool>,
+
+    // preferred output device, None lets mpv (or the mpv config) decide
+    #[serde(default)]
+    pub audio_device: Option<String>,
+    #[serde(default)]
+    pub per_device_volume: bool,
+    #[serde(default)]
+    pub device_volumes: HashMap<String, i64>,
+
+    // keep the pitch when playing faster or slower
+    #[serde(default = "Preferences::default_pitch_correction")]
+    pub pitch_correction: bool,
+
+    // plain random or artist-aware shuffle
+    #[serde(default)]
+    pub shuffle_mode: ShuffleMode,
+    #[serde(default)]
+    pub smart_shuffle_favorites: bool,
 }
 
 const MIN_WIDTH: u16 = 10;
@@ -368,6 +459,18 @@ impl Preferences {
             theme: String::from("Dark"),
 
             constraint_width_percentages_music: (22, 56, 22),
+
+            equalizer: HashMap::new(),
+            transitions: None,
+
+            audio_device: None,
+            per_device_volume: false,
+            device_volumes: HashMap::new(),
+
+            pitch_correction: true,
+
+            shuffle_mode: ShuffleMode::default(),
+            smart_shuffle_favorites: false,
         }
     }
 
@@ -383,6 +486,10 @@ impl Preferences {
         Sort::Descending
     }
 
+    fn default_pitch_correction() -> bool {
+        true
+    }
+
     pub(crate) fn widen_current_pane(&mut self, active_section: &ActiveSection, up: bool) {
         let (a, b, c) = &mut self.constraint_width_percentages_music;
 
@@ -420,6 +527,31 @@ impl Preferences {
         Self::normalize(&mut self.constraint_width_percentages_music);
     }
 
+    /// Moves the border between two neighbouring columns to `position` (percent of the total width).
+    /// Border 0 sits between the left and center column, border 1 between the center and right one.
+    pub(crate) fn move_column_border(&mut self, border: usize, position: u16) {
+        let (a, b, c) = &mut self.constraint_width_percentages_music;
+
+        match border {
+            0 => {
+                let new_a = position.min((*a + *b).saturating_sub(MIN_WIDTH)).max(MIN_WIDTH);
+                *b = (*a + *b).saturating_sub(new_a);
+                *a = new_a;
+            }
+            1 => {
+                let new_c = 100u16
+                    .saturating_sub(position)
+                    .min((*b + *c).saturating_sub(MIN_WIDTH))
+                    .max(MIN_WIDTH);
+                *b = (*b + *c).saturating_sub(new_c);
+                *c = new_c;
+            }
+            _ => {}
+        }
+
+        Self::normalize(&mut self.constraint_width_percentages_music);
+    }
+
     fn normalize(p: &mut (u16, u16, u16)) {
         let total = p.0 + p.1 + p.2;
         if total == 100 {
@@ -0,0 +1,327 @@
+/* --------------------------
+Remote control over a unix socket
+    - The socket lives in a private directory inside the data dir
+    - Every line sent to the socket is a JSON command, every reply is a single JSON line
+    - Commands are queued to the App and handled in the main loop, like MPRIS events
+    - `jellyfin-tui ctl <cmd>` is a small client for scripts and hotkeys
+-------------------------- */
+
+use crate::tui::App;
+use dirs::data_dir;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+use std::path::PathBuf;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{mpsc, oneshot};
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum IpcCommand {
+    Play,
+    Pause,
+    Toggle,
+    Stop,
+    Next,
+    Previous,
+    /// Seconds, absolute unless `relative` is set
+    Seek {
+        position: f64,
+        #[serde(default)]
+        relative: bool,
+    },
+    /// Track or album id. `next` plays it right after the current track
+    Enqueue {
+        id: String,
+        #[serde(default)]
+        next: bool,
+    },
+    NowPlaying,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct IpcResponse {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub now_playing: Option<NowPlaying>,
+}
+
+impl IpcResponse {
+    fn ok() -> Self {
+        Self { ok: true, ..Default::default() }
+    }
+
+    fn error(message: &str) -> Self {
+        Self { ok: false, error: Some(message.to_string()), ..Default::default() }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NowPlaying {
+    pub id: String,
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    pub position: f64,
+    pub duration: f64,
+    pub paused: bool,
+    pub volume: i64,
+    pub queue_position: usize,
+    pub queue_length: usize,
+}
+
+pub struct IpcRequest {
+    pub command: IpcCommand,
+    pub reply: oneshot::Sender<IpcResponse>,
+}
+
+pub fn socket_path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("jellyfin-tui").join("ipc").join("jellyfin-tui.sock"))
+}
+
+/// Starts listening on the socket. Must only be called while holding the instance lock,
+/// any socket file already present is a leftover from a crashed instance and gets replaced.
+///
+pub fn spawn_server(tx: mpsc::Sender<IpcRequest>) {
+    let Some(path) = socket_path() else {
+        log::error!("Could not find data directory, remote control is disabled");
+        return;
+    };
+    let _ = std::fs::remove_file(&path);
+
+    // the socket is created with the process umask, a private directory keeps other users
+    // from connecting before the permissions below are applied
+    if let Some(dir) = path.parent() {
+        let created = std::fs::DirBuilder::new().recursive(true).mode(0o700).create(dir);
+        if let Err(e) = created
+            .and_then(|_| std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700)))
+        {
+            log::error!("Failed to create remote control directory {}: {}", dir.display(), e);
+            return;
+        }
+    }
+
+    let listener = match UnixListener::bind(&path) {
+        Ok(listener) => listener,
+        Err(e) => {
+            log::error!("Failed to bind remote control socket {}: {}", path.display(), e);
+            return;
+        }
+    };
+    if let Err(e) = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600)) {
+        log::warn!("Failed to set permissions of {}: {}", path.display(), e);
+    }
+    log::info!("Remote control socket listening on {}", path.display());
+
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tokio::spawn(handle_connection(stream, tx.clone()));
+                }
+                Err(e) => {
+                    log::error!("Remote control socket error: {}", e);
+                    break;
+                }
+            }
+        }
+    });
+}
+
+pub fn remove_socket() {
+    if let Some(path) = socket_path() {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+async fn handle_connection(stream: UnixStream, tx: mpsc::Sender<IpcRequest>) {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = tokio::io::BufReader::new(reader).lines();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<IpcCommand>(&line) {
+            Ok(command) => {
+                let (reply, rx) = oneshot::channel();
+                if tx.send(IpcRequest { command, reply }).await.is_err() {
+                    return;
+                }
+                rx.await.unwrap_or_else(|_| IpcResponse::error("command was dropped"))
+            }
+            Err(e) => IpcResponse::error(&format!("invalid command: {}", e)),
+        };
+        let Ok(mut out) = serde_json::to_string(&response) else {
+            return;
+        };
+        out.push('\n');
+        if writer.write_all(out.as_bytes()).await.is_err() {
+            return;
+        }
+    }
+}
+
+impl App {
+    pub async fn handle_ipc_events(&mut self) {
+        while let Ok(request) = self.ipc_rx.try_recv() {
+            // each command is an undo step of its own
+            self.queue_history.end_step();
+            let response = self.handle_ipc_command(request.command).await;
+            let _ = request.reply.send(response);
+            self.dirty = true;
+        }
+    }
+
+    async fn handle_ipc_command(&mut self, command: IpcCommand) -> IpcResponse {
+        match command {
+            IpcCommand::Play => self.play().await,
+            IpcCommand::Pause => self.pause().await,
+            IpcCommand::Toggle => {
+                if self.paused {
+                    self.play().await;
+                } else {
+                    self.pause().await;
+                }
+            }
+            IpcCommand::Stop => self.stop().await,
+            IpcCommand::Next => self.next().await,
+            IpcCommand::Previous => self.previous().await,
+            IpcCommand::Seek { position, relative } => {
+                if self.stopped {
+                    return IpcResponse::error("nothing is playing");
+                }
+                let target = match relative {
+                    true => self.state.current_playback_state.position + position,
+                    false => position,
+                };
+                self.seek_to(target).await;
+            }
+            IpcCommand::Enqueue { id, next } => {
+                let tracks = self.tracks_by_id(&id).await;
+                if tracks.is_empty() {
+                    return IpcResponse::error(&format!("no track or album with id {}", id));
+                }
+                if next {
+                    self.push_all_next_to_temporary_queue(&tracks).await;
+                } else {
+                    self.push_to_temporary_queue(&tracks, 0, tracks.len()).await;
+                }
+            }
+            IpcCommand::NowPlaying => {
+                let playback = &self.state.current_playback_state;
+                let Some(song) =
+                    self.state.queue.get(playback.current_index).filter(|_| !self.stopped)
+                else {
+                    return IpcResponse::error("nothing is playing");
+                };
+                return IpcResponse {
+                    now_playing: Some(NowPlaying {
+                        id: song.id.clone(),
+                        title: song.name.clone(),
+                        artist: song.artist.clone(),
+                        album: song.album.clone(),
+                        position: playback.position,
+                        duration: playback.duration,
+                        paused: self.paused,
+                        volume: playback.volume,
+                        queue_position: playback.current_index,
+                        queue_length: self.state.queue.len(),
+                    }),
+                    ..IpcResponse::ok()
+                };
+            }
+        }
+        IpcResponse::ok()
+    }
+}
+
+const CTL_USAGE: &str = "Usage: jellyfin-tui ctl <COMMAND>
+
+Commands:
+  play\t\t\tResume playback
+  pause\t\t\tPause playback
+  toggle\t\tToggle play/pause
+  stop\t\t\tStop playback and clear the queue
+  next\t\t\tPlay the next track
+  previous\t\tPlay the previous track
+  seek <SECONDS>\t\tSeek to a position, +N / -N seeks relative to the current one
+  enqueue <ID>\t\tAdd a track or album to the queue
+  play-next <ID>\tPlay a track or album after the current track
+  now-playing\t\tPrint the current track as JSON";
+
+/// Entry point of `jellyfin-tui ctl`. Returns the exit code.
+///
+pub fn run_ctl(args: &[String]) -> i32 {
+    let command = match parse_ctl_args(args) {
+        Ok(command) => command,
+        Err(e) => {
+            if !e.is_empty() {
+                eprintln!(" ! {}\n", e);
+            }
+            eprintln!("{}", CTL_USAGE);
+            return 2;
+        }
+    };
+
+    let response = match send_command(&command) {
+        Ok(response) => response,
+        Err(e) => {
+            eprintln!(" ! Could not reach jellyfin-tui, is it running? ({})", e);
+            return 1;
+        }
+    };
+
+    if !response.ok {
+        eprintln!(" ! {}", response.error.unwrap_or_default());
+        return 1;
+    }
+    if let Some(now_playing) = response.now_playing {
+        println!("{}", serde_json::to_string_pretty(&now_playing).unwrap_or_default());
+    }
+    0
+}
+
+fn parse_ctl_args(args: &[String]) -> Result<IpcCommand, String> {
+    let argument =
+        |name: &str| args.get(1).cloned().ok_or_else(|| format!("'{}' expects an argument", name));
+    let command = match args.first().map(String::as_str) {
+        Some("play") => IpcCommand::Play,
+        Some("pause") => IpcCommand::Pause,
+        Some("toggle") => IpcCommand::Toggle,
+        Some("stop") => IpcCommand::Stop,
+        Some("next") => IpcCommand::Next,
+        Some("previous") | Some("prev") => IpcCommand::Previous,
+        Some("seek") => {
+            let value = argument("seek")?;
+            let relative = value.starts_with('+') || value.starts_with('-');
+            let position = value
+                .parse::<f64>()
+                .map_err(|_| format!("'{}' is not a number of seconds", value))?;
+            IpcCommand::Seek { position, relative }
+        }
+        Some("enqueue") => IpcCommand::Enqueue { id: argument("enqueue")?, next: false },
+        Some("play-next") => IpcCommand::Enqueue { id: argument("play-next")?, next: true },
+        Some("now-playing") | Some("status") => IpcCommand::NowPlaying,
+        Some(other) => return Err(format!("Unknown command '{}'", other)),
+        None => return Err(String::new()),
+    };
+    Ok(command)
+}
+
+fn send_command(command: &IpcCommand) -> Result<IpcResponse, Box<dyn std::error::Error>> {
+    let path = socket_path().ok_or("could not find data directory")?;
+    let mut stream = std::os::unix::net::UnixStream::connect(path)?;
+    stream.set_read_timeout(Some(std::time::Duration::from_secs(5)))?;
+
+    let mut line = serde_json::to_string(command)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())?;
+
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply)?;
+    Ok(serde_json::from_str(&reply)?)
+}
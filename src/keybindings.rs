@@ -0,0 +1,488 @@
+/* --------------------------
+Configurable key bindings
+    - Every remappable action has a name (used in config.yaml) and a list of default chords
+    - Pressed keys are translated to the *default* chord of their action, so the handlers in
+      keyboard.rs can keep matching on the default keys
+    - Example config:
+        keybindings:
+          next_track: "ctrl+right"
+          down: ["down", "ctrl+n"]
+-------------------------- */
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyAction {
+    Quit,
+    SeekBackward,
+    SeekForward,
+    SeekBackwardLong,
+    SeekForwardLong,
+    SeekTo,
+    AbLoop,
+    ShrinkPane,
+    WidenPane,
+    SectionLeft,
+    SectionRight,
+    NextTrack,
+    PreviousTrack,
+    PlayPause,
+    Stop,
+    Reset,
+    ToggleTranscoding,
+    VolumeUp,
+    VolumeDown,
+    SpeedUp,
+    SpeedDown,
+    SpeedReset,
+    NextSection,
+    PreviousSection,
+    Down,
+    Up,
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
+    NextAlbum,
+    PreviousAlbum,
+    Select,
+    Enqueue,
+    PlayNext,
+    Favorite,
+    Download,
+    Repeat,
+    Popup,
+    GlobalPopup,
+    RemoveFromQueue,
+    Shuffle,
+    GlobalShuffle,
+    ClearQueue,
+    UndoQueue,
+    RedoQueue,
+    MoveDown,
+    MoveUp,
+    VisualSelect,
+    ToggleMark,
+    TrackInfo,
+    Help,
+    Back,
+    TabHome,
+    TabLibrary,
+    TabAlbums,
+    TabPlaylists,
+    TabSearch,
+    TabStats,
+    TabGenres,
+    Search,
+}
+
+/// (action, name in config.yaml, default chords). The first chord is the one keyboard.rs handles.
+const ACTIONS: &[(KeyAction, &str, &[&str])] = &[
+    (KeyAction::Quit, "quit", &["q"]),
+    (KeyAction::SeekBackward, "seek_backward", &["left"]),
+    (KeyAction::SeekForward, "seek_forward", &["right"]),
+    (KeyAction::SeekBackwardLong, "seek_backward_long", &[","]),
+    (KeyAction::SeekForwardLong, "seek_forward_long", &["."]),
+    (KeyAction::SeekTo, "seek_to", &["t"]),
+    (KeyAction::AbLoop, "ab_loop", &["b"]),
+    (KeyAction::ShrinkPane, "shrink_pane", &["ctrl+left", "ctrl+h"]),
+    (KeyAction::WidenPane, "widen_pane", &["ctrl+right", "ctrl+l"]),
+    (KeyAction::SectionLeft, "section_left", &["h"]),
+    (KeyAction::SectionRight, "section_right", &["l"]),
+    (KeyAction::NextTrack, "next_track", &["n"]),
+    (KeyAction::PreviousTrack, "previous_track", &["N"]),
+    (KeyAction::PlayPause, "play_pause", &["space"]),
+    (KeyAction::Stop, "stop", &["x"]),
+    (KeyAction::Reset, "reset", &["X"]),
+    (KeyAction::ToggleTranscoding, "toggle_transcoding", &["T"]),
+    (KeyAction::VolumeUp, "volume_up", &["+"]),
+    (KeyAction::VolumeDown, "volume_down", &["-"]),
+    (KeyAction::SpeedUp, "speed_up", &["]"]),
+    (KeyAction::SpeedDown, "speed_down", &["["]),
+    (KeyAction::SpeedReset, "speed_reset", &["\\"]),
+    (KeyAction::NextSection, "next_section", &["tab"]),
+    (KeyAction::PreviousSection, "previous_section", &["shift+tab"]),
+    (KeyAction::Down, "down", &["down", "j"]),
+    (KeyAction::Up, "up", &["up", "k"]),
+    (KeyAction::PageUp, "page_up", &["pageup"]),
+    (KeyAction::PageDown, "page_down", &["pagedown"]),
+    (KeyAction::Top, "top", &["g", "home"]),
+    (KeyAction::Bottom, "bottom", &["G", "end"]),
+    (KeyAction::NextAlbum, "next_album", &["a"]),
+    (KeyAction::PreviousAlbum, "previous_album", &["A"]),
+    (KeyAction::Select, "select", &["enter"]),
+    (KeyAction::Enqueue, "enqueue", &["e", "shift+enter"]),
+    (KeyAction::PlayNext, "play_next", &["ctrl+e", "ctrl+enter"]),
+    (KeyAction::Favorite, "favorite", &["f"]),
+    (KeyAction::Download, "download", &["d"]),
+    (KeyAction::Repeat, "repeat", &["r"]),
+    (KeyAction::Popup, "popup", &["p"]),
+    (KeyAction::GlobalPopup, "global_popup", &["P"]),
+    (KeyAction::RemoveFromQueue, "remove_from_queue", &["delete"]),
+    (KeyAction::Shuffle, "shuffle", &["s"]),
+    (KeyAction::GlobalShuffle, "global_shuffle", &["ctrl+s"]),
+    (KeyAction::ClearQueue, "clear_queue", &["E"]),
+    (KeyAction::UndoQueue, "undo", &["u"]),
+    (KeyAction::RedoQueue, "redo", &["ctrl+r"]),
+    (KeyAction::MoveDown, "move_down", &["J", "shift+down"]),
+    (KeyAction::MoveUp, "move_up", &["K", "shift+up"]),
+    (KeyAction::VisualSelect, "visual_select", &["v"]),
+    (KeyAction::ToggleMark, "mark", &["m"]),
+    (KeyAction::TrackInfo, "track_info", &["i"]),
+    (KeyAction::Help, "help", &["?"]),
+    (KeyAction::Back, "back", &["esc"]),
+    (KeyAction::TabHome, "tab_home", &["0"]),
+    (KeyAction::TabLibrary, "tab_library", &["1", "f1"]),
+    (KeyAction::TabAlbums, "tab_albums", &["2", "f2"]),
+    (KeyAction::TabPlaylists, "tab_playlists", &["3", "f3"]),
+    (KeyAction::TabSearch, "tab_search", &["4", "f4"]),
+    (KeyAction::TabStats, "tab_stats", &["5", "f5"]),
+    (KeyAction::TabGenres, "tab_genres", &["6", "f6"]),
+    (KeyAction::Search, "search", &["/"]),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let mut modifiers =
+            modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        // shift is already part of the character (and of BackTab)
+        if matches!(code, KeyCode::Char(_) | KeyCode::BackTab) {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Self { code, modifiers }
+    }
+
+    /// Parses chords such as "n", "N", "shift+n", "ctrl+s", "space", "f1" or "ctrl++"
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        let (mods, key) = if s == "+" {
+            ("", "+")
+        } else if let Some(mods) = s.strip_suffix("++") {
+            (mods, "+")
+        } else {
+            s.rsplit_once('+').unwrap_or(("", s))
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+        for m in mods.split('+').filter(|m| !m.is_empty()) {
+            match m.to_lowercase().as_str() {
+                "ctrl" | "control" => modifiers |= KeyModifiers::CONTROL,
+                "alt" => modifiers |= KeyModifiers::ALT,
+                "shift" => modifiers |= KeyModifiers::SHIFT,
+                _ => return Err(format!("unknown modifier '{}' in '{}'", m, s)),
+            }
+        }
+
+        let code = match key.to_lowercase().as_str() {
+            "space" => KeyCode::Char(' '),
+            "enter" | "return" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "tab" if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "insert" => KeyCode::Insert,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            f if f.len() > 1 && f.starts_with('f') && f[1..].parse::<u8>().is_ok() => {
+                KeyCode::F(f[1..].parse().unwrap_or(1))
+            }
+            _ => {
+                let mut chars = key.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) if modifiers.contains(KeyModifiers::SHIFT) => {
+                        KeyCode::Char(c.to_ascii_uppercase())
+                    }
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => return Err(format!("unknown key '{}'", s)),
+                }
+            }
+        };
+
+        Ok(Self::new(code, modifiers))
+    }
+
+    pub fn to_event(self) -> KeyEvent {
+        let modifiers = match self.code {
+            KeyCode::Char(c) if c.is_ascii_uppercase() => self.modifiers | KeyModifiers::SHIFT,
+            KeyCode::BackTab => self.modifiers | KeyModifiers::SHIFT,
+            _ => self.modifiers,
+        };
+        KeyEvent::new(self.code, modifiers)
+    }
+}
+
+impl From<&KeyEvent> for KeyChord {
+    fn from(event: &KeyEvent) -> Self {
+        Self::new(event.code, event.modifiers)
+    }
+}
+
+impl std::fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "shift+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            KeyCode::BackTab => write!(f, "shift+tab"),
+            KeyCode::PageUp => write!(f, "pageup"),
+            KeyCode::PageDown => write!(f, "pagedown"),
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            KeyCode::Enter => write!(f, "enter"),
+            KeyCode::Esc => write!(f, "esc"),
+            KeyCode::Tab => write!(f, "tab"),
+            KeyCode::Backspace => write!(f, "backspace"),
+            KeyCode::Delete => write!(f, "delete"),
+            KeyCode::Insert => write!(f, "insert"),
+            KeyCode::Home => write!(f, "home"),
+            KeyCode::End => write!(f, "end"),
+            other => write!(f, "{:?}", other),
+        }
+    }
+}
+
+pub struct KeyBindings {
+    bindings: HashMap<KeyChord, KeyAction>,
+    chords: HashMap<KeyAction, Vec<KeyChord>>,
+    default_chords: HashMap<KeyChord, KeyAction>,
+    pub conflicts: Vec<String>,
+}
+
+impl KeyBindings {
+    /// Builds the active bindings from the defaults and the `keybindings:` section of the config.
+    /// Any problems (unknown actions, invalid chords, a chord configured for two actions) end up
+    /// in `conflicts`. A configured chord silently takes over the default of another action.
+    pub fn from_config(config: &serde_yaml::Value) -> Self {
+        let mut conflicts = vec![];
+        let mut chords: HashMap<KeyAction, Vec<KeyChord>> = HashMap::new();
+        let mut default_chords = HashMap::new();
+
+        for (action, _, defaults) in ACTIONS {
+            let parsed =
+                defaults.iter().filter_map(|c| KeyChord::parse(c).ok()).collect::<Vec<_>>();
+            for chord in &parsed {
+                default_chords.insert(*chord, *action);
+            }
+            chords.insert(*action, parsed);
+        }
+
+        // user bindings replace all default chords of the action
+        let mut user_actions = vec![];
+        if let Some(mapping) = config.get("keybindings").and_then(|k| k.as_mapping()) {
+            for (name, value) in mapping {
+                let Some(name) = name.as_str() else {
+                    continue;
+                };
+                let Some(action) = Self::action_by_name(name) else {
+                    conflicts.push(format!("unknown action '{}'", name));
+                    continue;
+                };
+                let values = match value {
+                    serde_yaml::Value::String(s) => vec![s.clone()],
+                    serde_yaml::Value::Sequence(seq) => {
+                        seq.iter().filter_map(|v| v.as_str().map(String::from)).collect()
+                    }
+                    _ => {
+                        conflicts.push(format!("'{}' must be a key or a list of keys", name));
+                        continue;
+                    }
+                };
+                let mut parsed = vec![];
+                for v in values {
+                    match KeyChord::parse(&v) {
+                        Ok(chord) => parsed.push(chord),
+                        Err(e) => conflicts.push(format!("{}: {}", name, e)),
+                    }
+                }
+                chords.insert(action, parsed);
+                user_actions.push(action);
+            }
+        }
+
+        // user bindings go first so they replace the defaults they collide with
+        let mut order = user_actions.clone();
+        order.extend(ACTIONS.iter().map(|(a, ..)| *a).filter(|a| !user_actions.contains(a)));
+
+        let mut bindings = HashMap::new();
+        for action in order {
+            let mut kept = vec![];
+            for chord in chords.get(&action).cloned().unwrap_or_default() {
+                match bindings.get(&chord) {
+                    // a default losing its chord to a user binding is the point of remapping
+                    Some(other) if *other != action => {
+                        if user_actions.contains(&action) {
+                            conflicts.push(format!(
+                                "'{}' is bound to both {} and {}, using {}",
+                                chord,
+                                Self::name(*other),
+                                Self::name(action),
+                                Self::name(*other),
+                            ));
+                        }
+                    }
+                    _ => {
+                        bindings.insert(chord, action);
+                        kept.push(chord);
+                    }
+                }
+            }
+            chords.insert(action, kept);
+        }
+
+        Self { bindings, chords, default_chords, conflicts }
+    }
+
+    fn action_by_name(name: &str) -> Option<KeyAction> {
+        ACTIONS.iter().find(|(_, n, _)| *n == name).map(|(a, ..)| *a)
+    }
+
+    pub fn name(action: KeyAction) -> &'static str {
+        ACTIONS.iter().find(|(a, ..)| *a == action).map_or("", |(_, n, _)| n)
+    }
+
+    /// The key event keyboard.rs expects for an action
+    pub fn default_event(action: KeyAction) -> Option<KeyEvent> {
+        let (_, _, defaults) = ACTIONS.iter().find(|(a, ..)| *a == action)?;
+        KeyChord::parse(defaults.first()?).ok().map(KeyChord::to_event)
+    }
+
+    /// Translates a pressed key into the default key of its action.
+    /// Returns None for default keys whose action has been bound elsewhere, other keys pass through.
+    pub fn translate(&self, event: KeyEvent) -> Option<KeyEvent> {
+        let chord = KeyChord::from(&event);
+        match self.bindings.get(&chord) {
+            Some(action) => Self::default_event(*action),
+            None if self.default_chords.contains_key(&chord) => None,
+            None => Some(event),
+        }
+    }
+
+    /// The action a pressed key is bound to
+    pub fn action(&self, event: &KeyEvent) -> Option<KeyAction> {
+        self.bindings.get(&KeyChord::from(event)).copied()
+    }
+
+    /// Human readable list of the chords bound to an action, used in the help pages
+    pub fn label(&self, action: KeyAction) -> String {
+        match self.chords.get(&action) {
+            Some(chords) if !chords.is_empty() => {
+                chords.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(" / ")
+            }
+            _ => String::from("(unbound)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chord(code: KeyCode, modifiers: KeyModifiers) -> KeyChord {
+        KeyChord { code, modifiers }
+    }
+
+    fn bindings(yaml: &str) -> KeyBindings {
+        KeyBindings::from_config(&serde_yaml::from_str(yaml).unwrap())
+    }
+
+    #[test]
+    fn parses_modifiers() {
+        assert_eq!(KeyChord::parse("n"), Ok(chord(KeyCode::Char('n'), KeyModifiers::NONE)));
+        assert_eq!(KeyChord::parse("ctrl+s"), Ok(chord(KeyCode::Char('s'), KeyModifiers::CONTROL)));
+        assert_eq!(
+            KeyChord::parse("Ctrl+Alt+x"),
+            Ok(chord(KeyCode::Char('x'), KeyModifiers::CONTROL | KeyModifiers::ALT))
+        );
+        // shift is folded into the character
+        assert_eq!(KeyChord::parse("shift+n"), KeyChord::parse("N"));
+        assert_eq!(KeyChord::parse("shift+n"), Ok(chord(KeyCode::Char('N'), KeyModifiers::NONE)));
+        assert_eq!(KeyChord::parse("shift+up"), Ok(chord(KeyCode::Up, KeyModifiers::SHIFT)));
+        assert_eq!(KeyChord::parse("+"), Ok(chord(KeyCode::Char('+'), KeyModifiers::NONE)));
+        assert_eq!(KeyChord::parse("ctrl++"), Ok(chord(KeyCode::Char('+'), KeyModifiers::CONTROL)));
+    }
+
+    #[test]
+    fn parses_named_keys() {
+        assert_eq!(KeyChord::parse("space"), Ok(chord(KeyCode::Char(' '), KeyModifiers::NONE)));
+        assert_eq!(KeyChord::parse("Enter"), Ok(chord(KeyCode::Enter, KeyModifiers::NONE)));
+        assert_eq!(KeyChord::parse("escape"), Ok(chord(KeyCode::Esc, KeyModifiers::NONE)));
+        assert_eq!(KeyChord::parse("shift+tab"), Ok(chord(KeyCode::BackTab, KeyModifiers::NONE)));
+        assert_eq!(KeyChord::parse("pagedown"), Ok(chord(KeyCode::PageDown, KeyModifiers::NONE)));
+        assert_eq!(KeyChord::parse("f5"), Ok(chord(KeyCode::F(5), KeyModifiers::NONE)));
+        assert_eq!(KeyChord::parse("F12"), Ok(chord(KeyCode::F(12), KeyModifiers::NONE)));
+        assert_eq!(KeyChord::parse(" del "), Ok(chord(KeyCode::Delete, KeyModifiers::NONE)));
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        assert!(KeyChord::parse("").is_err());
+        assert!(KeyChord::parse("ctrl+").is_err());
+        assert!(KeyChord::parse("super+a").is_err());
+        assert!(KeyChord::parse("nope").is_err());
+        assert!(KeyChord::parse("f").is_ok());
+        assert!(KeyChord::parse("fx").is_err());
+    }
+
+    #[test]
+    fn user_binding_takes_over_a_default_silently() {
+        // "q" is the default of quit
+        let keys = bindings("keybindings:\n  next_track: q\n");
+        assert!(keys.conflicts.is_empty());
+        assert_eq!(keys.action(&KeyEvent::from(KeyCode::Char('q'))), Some(KeyAction::NextTrack));
+        assert_eq!(keys.label(KeyAction::Quit), "(unbound)");
+        // the old default of next_track now does nothing
+        assert_eq!(keys.translate(KeyEvent::from(KeyCode::Char('n'))), None);
+        assert_eq!(
+            keys.translate(KeyEvent::from(KeyCode::Char('q'))),
+            Some(KeyEvent::from(KeyCode::Char('n')))
+        );
+    }
+
+    #[test]
+    fn conflicts_only_between_configured_bindings() {
+        let keys =
+            bindings("keybindings:\n  next_track: ctrl+n\n  previous_track: [ctrl+n, ctrl+p]\n");
+        assert_eq!(keys.conflicts.len(), 1);
+        assert!(keys.conflicts[0].contains("next_track"));
+        assert!(keys.conflicts[0].contains("previous_track"));
+        // the first configured binding wins
+        let ctrl_n = KeyEvent::new(KeyCode::Char('n'), KeyModifiers::CONTROL);
+        assert_eq!(keys.action(&ctrl_n), Some(KeyAction::NextTrack));
+        assert_eq!(keys.label(KeyAction::PreviousTrack), "ctrl+p");
+    }
+
+    #[test]
+    fn reports_unknown_actions_and_bad_chords() {
+        let keys = bindings("keybindings:\n  fly: x\n  quit: hyper+q\n  stop: 5\n");
+        assert_eq!(keys.conflicts.len(), 3);
+        assert!(keys.conflicts.iter().any(|c| c.contains("unknown action 'fly'")));
+        assert!(keys.conflicts.iter().any(|c| c.starts_with("quit:")));
+        assert!(keys.conflicts.iter().any(|c| c.contains("'stop' must be a key")));
+    }
+}
@@ -13,6 +13,7 @@ use crate::{
     },
     helpers::{self, State},
     popup::PopupMenu,
+    smart_playlists::is_smart_playlist,
     sort,
     tui::{App, Repeat},
 };
@@ -21,12 +22,21 @@ use crate::database::extension::{
     get_discography, get_tracks, set_favorite_album, set_favorite_artist, set_favorite_playlist,
     set_favorite_track,
 };
+use crate::keybindings::KeyAction;
 use crate::mpv::SeekFlag;
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use crate::player::SPEED_STEP;
+use crossterm::event::{
+    self, Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::layout::{Position, Rect};
 use serde::{Deserialize, Serialize};
 use std::io;
 use std::time::Duration;
 
+pub const TAB_TITLES: [&str; ActiveTab::ALL.len()] =
+    ["Home", "Library", "Albums", "Playlists", "Search", "Stats", "Genres"];
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(400);
+
 pub trait Searchable {
     fn id(&self) -> &str;
     fn name(&self) -> &str;
@@ -102,7 +112,7 @@ impl App {
                 }
                 Event::Mouse(m) => {
                     self.recent_input_activity = tokio::time::Instant::now();
-                    self.handle_mouse_event(m);
+                    self.handle_mouse_event(m).await;
                 }
                 Event::Resize(c, r) => {
                     let (_, picker) = App::init_theme_and_picker(&self.config, &self.theme);
@@ -384,6 +394,10 @@ impl App {
             return;
         }
 
+        if self.locally_searching && self.state.active_tab == ActiveTab::Genres {
+            self.handle_genre_search_key(key_event);
+            return;
+        }
         if self.locally_searching {
             match key_event.code {
                 KeyCode::Esc | KeyCode::F(1) => {
@@ -620,11 +634,35 @@ impl App {
             return;
         }
 
+        if let Some(tab) = self.tab_for_key(&key_event) {
+            self.open_tab(tab).await;
+            return;
+        }
+
         if self.state.active_tab == ActiveTab::Search {
             self.handle_search_tab_events(key_event).await;
             return;
         }
 
+        let Some(key_event) = self.keybindings.translate(key_event) else {
+            return;
+        };
+        self.handle_normal_key_event(key_event).await;
+    }
+
+    /// Expects key events already translated to the default bindings
+    ///
+    async fn handle_normal_key_event(&mut self, key_event: KeyEvent) {
+        if self.state.active_tab == ActiveTab::Home
+            && self.handle_dashboard_key_event(key_event).await
+        {
+            return;
+        }
+        if self.state.active_tab == ActiveTab::Genres
+            && self.handle_genres_key_event(key_event).await
+        {
+            return;
+        }
         match key_event.code {
             KeyCode::Char('q') => self.exit().await,
             // Seek backward
@@ -636,12 +674,13 @@ impl App {
                 if self.stopped {
                     return;
                 }
+                let step = self.seek_steps.short;
                 self.state.current_playback_state.position =
-                    f64::max(0.0, self.state.current_playback_state.position - 5.0);
+                    f64::max(0.0, self.state.current_playback_state.position - step);
                 self.update_mpris_position(self.state.current_playback_state.position);
                 let _ = self.handle_discord(false).await;
 
-                self.mpv_handle.seek(-5.0, SeekFlag::Relative).await;
+                self.mpv_handle.seek(-step, SeekFlag::Relative).await;
             }
             // Seek forward
             KeyCode::Right => {
@@ -652,15 +691,16 @@ impl App {
                 if self.stopped {
                     return;
                 }
+                let step = self.seek_steps.short;
                 self.state.current_playback_state.position = f64::min(
-                    self.state.current_playback_state.position + 5.0,
+                    self.state.current_playback_state.position + step,
                     self.state.current_playback_state.duration,
                 );
 
                 self.update_mpris_position(self.state.current_playback_state.position);
                 let _ = self.handle_discord(false).await;
 
-                self.mpv_handle.seek(5.0, SeekFlag::Relative).await;
+                self.mpv_handle.seek(step, SeekFlag::Relative).await;
             }
             KeyCode::Char('h') => {
                 if key_event.modifiers.contains(KeyModifiers::CONTROL) {
@@ -680,22 +720,34 @@ impl App {
                 if self.stopped {
                     return;
                 }
+                let step = self.seek_steps.long;
                 self.state.current_playback_state.position =
-                    f64::max(0.0, self.state.current_playback_state.position - 60.0);
-                self.mpv_handle.seek(-60.0, SeekFlag::Relative).await;
+                    f64::max(0.0, self.state.current_playback_state.position - step);
+                self.mpv_handle.seek(-step, SeekFlag::Relative).await;
                 let _ = self.handle_discord(true).await;
             }
             KeyCode::Char('.') => {
                 if self.stopped {
                     return;
                 }
+                let step = self.seek_steps.long;
                 self.state.current_playback_state.position = f64::min(
                     self.state.current_playback_state.duration,
-                    self.state.current_playback_state.position + 60.0,
+                    self.state.current_playback_state.position + step,
                 );
-                self.mpv_handle.seek(60.0, SeekFlag::Relative).await;
+                self.mpv_handle.seek(step, SeekFlag::Relative).await;
                 let _ = self.handle_discord(true).await;
             }
+            // A-B loop: set A, set B, clear
+            KeyCode::Char('b') => {
+                self.cycle_ab_loop().await;
+            }
+            KeyCode::Char('t') => {
+                if self.stopped {
+                    return;
+                }
+                self.open_seek_popup();
+            }
             // Next track
             KeyCode::Char('n') => {
                 self.next().await;
@@ -734,6 +786,7 @@ impl App {
                 self.tracks.clear();
                 self.album_tracks.clear();
                 self.playlist_tracks.clear();
+                self.refresh_dashboard();
             }
             KeyCode::Char('T') => {
                 if self.client.is_none() {
@@ -771,6 +824,22 @@ impl App {
                     }
                 }
             }
+            // Playback speed
+            KeyCode::Char(']') => {
+                self.set_speed(self.state.current_playback_state.speed + SPEED_STEP).await;
+            }
+            KeyCode::Char('[') => {
+                self.set_speed(self.state.current_playback_state.speed - SPEED_STEP).await;
+            }
+            KeyCode::Char('\\') => {
+                self.set_speed(1.0).await;
+            }
+            KeyCode::Tab if self.state.active_tab == ActiveTab::Stats => {
+                self.cycle_stats_window(true).await;
+            }
+            KeyCode::BackTab if self.state.active_tab == ActiveTab::Stats => {
+                self.cycle_stats_window(false).await;
+            }
             KeyCode::Tab => {
                 self.toggle_section(true);
             }
@@ -842,7 +911,10 @@ impl App {
                                 self.playlist_select_by_index(next);
                                 return;
                             }
-                            ActiveTab::Search => {
+                            ActiveTab::Home
+                            | ActiveTab::Search
+                            | ActiveTab::Stats
+                            | ActiveTab::Genres => {
                                 // handle_search_tab_events()
                             }
                         }
@@ -947,7 +1019,10 @@ impl App {
                                 let prev = move_up(self.state.selected_playlist.selected());
                                 self.playlist_select_by_index(prev);
                             }
-                            ActiveTab::Search => {
+                            ActiveTab::Home
+                            | ActiveTab::Search
+                            | ActiveTab::Stats
+                            | ActiveTab::Genres => {
                                 // handle_search_tab_events()
                             }
                         }
@@ -1758,6 +1833,27 @@ impl App {
                                     }
                                 }
                             }
+                            ActiveTab::Genres => {
+                                let selected =
+                                    self.state.selected_genre_track.selected().unwrap_or(0);
+                                if let Some(track) = self.genre_tracks.get_mut(selected) {
+                                    let _ = client
+                                        .set_favorite(&track.id, !track.user_data.is_favorite)
+                                        .await;
+                                    let _ = set_favorite_track(
+                                        &self.db.pool,
+                                        &track.id,
+                                        !track.user_data.is_favorite,
+                                    )
+                                    .await;
+                                    track.user_data.is_favorite = !track.user_data.is_favorite;
+                                    if let Some(tr) =
+                                        self.state.queue.iter_mut().find(|t| t.id == track.id)
+                                    {
+                                        tr.is_favorite = !tr.is_favorite;
+                                    }
+                                }
+                            }
                             _ => {}
                         }
                     }
@@ -1935,6 +2031,42 @@ impl App {
                                 }
                             }
                         }
+                        ActiveTab::Genres => {
+                            let selected = self.state.selected_genre_track.selected().unwrap_or(0);
+                            if let Some(track) = self.genre_tracks.get_mut(selected) {
+                                let id = track.id.clone();
+                                match track.download_status {
+                                    DownloadStatus::NotDownloaded => {
+                                        let _ = self
+                                            .db
+                                            .cmd_tx
+                                            .send(Command::Download(DownloadCommand::Track {
+                                                track: track.clone(),
+                                                playlist_id: None,
+                                            }))
+                                            .await;
+                                    }
+                                    _ => {
+                                        track.download_status = DownloadStatus::NotDownloaded;
+                                        let _ = self
+                                            .db
+                                            .cmd_tx
+                                            .send(Command::Remove(RemoveCommand::Track {
+                                                track: track.clone(),
+                                            }))
+                                            .await;
+                                        if self.client.is_none() {
+                                            self.genre_tracks.retain(|t| t.id != id);
+                                            self.genre_track_select_by_index(selected);
+                                            self.tracks.retain(|t| t.id != id);
+                                            self.album_tracks.retain(|t| t.id != id);
+                                            self.playlist_tracks.retain(|t| t.id != id);
+                                            let _ = self.remove_from_queue_by_id(id).await;
+                                        }
+                                    }
+                                }
+                            }
+                        }
                         _ => {}
                     },
                     _ => {}
@@ -1957,6 +2089,7 @@ impl App {
                 if self.tracks.is_empty()
                     && self.album_tracks.is_empty()
                     && self.playlist_tracks.is_empty()
+                    && self.state.active_tab != ActiveTab::Genres
                 {
                     self.state.active_section = ActiveSection::List;
                     self.state.active_tab = ActiveTab::Library;
@@ -1966,6 +2099,12 @@ impl App {
                 }
                 return;
             }
+            KeyCode::Char('u') => {
+                self.undo_queue().await;
+            }
+            KeyCode::Char('r') if key_event.modifiers == KeyModifiers::CONTROL => {
+                self.redo_queue().await;
+            }
             KeyCode::Char('r') => {
                 match self.preferences.repeat {
                     Repeat::None => {
@@ -2049,6 +2188,15 @@ impl App {
                     self.move_queue_item_up().await;
                 }
             }
+            KeyCode::Char('v') => {
+                self.visual_select();
+            }
+            KeyCode::Char('m') => {
+                self.toggle_mark();
+            }
+            KeyCode::Char('i') => {
+                self.show_track_info(self.state.active_section).await;
+            }
             KeyCode::Char('?') => {
                 self.show_help = !self.show_help;
                 self.dirty_clear = true;
@@ -2059,6 +2207,9 @@ impl App {
                     self.dirty_clear = true;
                     return;
                 }
+                if self.clear_selection() {
+                    return;
+                }
                 let artist_id = self.get_id_of_selected(&self.artists, Selectable::Artist);
                 let album_id = self.get_id_of_selected(&self.albums, Selectable::Album);
                 let album_track_id =
@@ -2110,30 +2261,12 @@ impl App {
                         self.search_term = String::from("");
                         self.state.active_tab = ActiveTab::Library;
                     }
+                    ActiveTab::Stats | ActiveTab::Genres => {
+                        self.state.active_tab = ActiveTab::Library;
+                    }
+                    ActiveTab::Home => {}
                 }
             }
-            KeyCode::F(1) | KeyCode::Char('1') => {
-                self.state.active_tab = ActiveTab::Library;
-                if self.tracks.is_empty() {
-                    self.state.active_section = ActiveSection::List;
-                }
-            }
-            KeyCode::F(2) | KeyCode::Char('2') => {
-                self.state.active_tab = ActiveTab::Albums;
-                if self.album_tracks.is_empty() {
-                    self.state.active_section = ActiveSection::List;
-                }
-            }
-            KeyCode::F(3) | KeyCode::Char('3') => {
-                self.state.active_tab = ActiveTab::Playlists;
-                if self.playlist_tracks.is_empty() {
-                    self.state.active_section = ActiveSection::List;
-                }
-            }
-            KeyCode::F(4) | KeyCode::Char('4') => {
-                self.state.active_tab = ActiveTab::Search;
-                self.searching = true;
-            }
             KeyCode::Char('/') => {
                 self.locally_searching = true;
             }
@@ -2142,28 +2275,24 @@ impl App {
     }
 
     async fn handle_search_tab_events(&mut self, key_event: KeyEvent) {
+        // while typing every key is part of the search term
+        let key_event = match self.searching {
+            true => key_event,
+            false => match self.keybindings.translate(key_event) {
+                Some(key_event) => key_event,
+                None => return,
+            },
+        };
         match key_event.code {
-            KeyCode::Esc | KeyCode::F(1) => {
+            KeyCode::Esc => {
                 if self.searching {
                     self.searching = false;
                     return;
                 }
-                self.state.active_tab = ActiveTab::Library;
-            }
-            KeyCode::F(2) => {
-                self.state.active_tab = ActiveTab::Albums;
-                if self.playlist_tracks.is_empty() {
-                    self.state.active_section = ActiveSection::List;
-                }
-            }
-            KeyCode::F(3) => {
-                self.state.active_tab = ActiveTab::Playlists;
-                if self.playlist_tracks.is_empty() {
-                    self.state.active_section = ActiveSection::List;
+                if self.clear_selection() {
+                    return;
                 }
-            }
-            KeyCode::F(4) => {
-                self.searching = true;
+                self.state.active_tab = ActiveTab::Library;
             }
             KeyCode::Backspace => {
                 self.search_term.pop();
@@ -2188,18 +2317,6 @@ impl App {
                     return;
                 }
                 match key_event.code {
-                    KeyCode::Char('1') => {
-                        self.state.active_tab = ActiveTab::Library;
-                    }
-                    KeyCode::Char('2') => {
-                        self.state.active_tab = ActiveTab::Albums;
-                    }
-                    KeyCode::Char('3') => {
-                        self.state.active_tab = ActiveTab::Playlists;
-                    }
-                    KeyCode::Char('4') => {
-                        self.searching = true;
-                    }
                     KeyCode::Down | KeyCode::Char('j') => match self.state.search_section {
                         SearchSection::Artists => {
                             self.state.selected_search_artist.select_next();
@@ -2265,15 +2382,320 @@ impl App {
                     KeyCode::Char('/') => {
                         self.searching = true;
                     }
+                    KeyCode::Char('v') => {
+                        self.visual_select();
+                    }
+                    KeyCode::Char('m') => {
+                        self.toggle_mark();
+                    }
+                    KeyCode::Char('i') => {
+                        self.show_track_info(self.state.active_section).await;
+                    }
+                    // the search tab only has a popup for selected tracks
+                    KeyCode::Char('p') if self.active_selection().is_some() => {
+                        self.popup.global = false;
+                        self.state.last_section = self.state.active_section;
+                        self.state.active_section = ActiveSection::Popup;
+                    }
                     _ => {}
                 }
             }
         }
     }
 
-    fn handle_mouse_event(&mut self, _mouse_event: crossterm::event::MouseEvent) {
-        // println!("Mouse event: {:?}", _mouse_event);
-        // self.dirty = true;
+    /// Mouse events are resolved against the areas recorded in `layout_areas` during the last render
+    ///
+    async fn handle_mouse_event(&mut self, mouse_event: MouseEvent) {
+        if self.locally_searching || self.playlist_editing {
+            return;
+        }
+        let (x, y) = (mouse_event.column, mouse_event.row);
+
+        match mouse_event.kind {
+            MouseEventKind::ScrollDown => self.handle_mouse_scroll(x, y, true).await,
+            MouseEventKind::ScrollUp => self.handle_mouse_scroll(x, y, false).await,
+            MouseEventKind::Down(MouseButton::Left) => self.handle_mouse_click(x, y).await,
+            MouseEventKind::Drag(MouseButton::Left) => {
+                let Some(border) = self.dragged_border else {
+                    return;
+                };
+                let columns = &self.layout_areas.columns;
+                let total_width = columns.iter().map(|c| c.width).sum::<u16>();
+                if total_width == 0 {
+                    return;
+                }
+                let position =
+                    (x.saturating_sub(columns[0].x) as u32 * 100 / total_width as u32) as u16;
+                self.preferences.move_column_border(border, position);
+            }
+            MouseEventKind::Up(MouseButton::Left) => {
+                if self.dragged_border.take().is_some() {
+                    if let Err(e) = self.preferences.save() {
+                        log::error!("Failed to save preferences: {}", e);
+                    }
+                }
+            }
+            _ => return,
+        }
+        self.dirty = true;
+    }
+
+    async fn handle_mouse_scroll(&mut self, x: u16, y: u16, down: bool) {
+        if self.state.active_section == ActiveSection::Popup {
+            if down {
+                self.popup.selected.select_next();
+            } else {
+                self.popup.selected.select_previous();
+            }
+            return;
+        }
+
+        let position = Position::new(x, y);
+        let section = if self.layout_areas.list.contains(position) {
+            ActiveSection::List
+        } else if self.layout_areas.tracks.contains(position) {
+            ActiveSection::Tracks
+        } else if self.layout_areas.queue.contains(position) {
+            ActiveSection::Queue
+        } else if self.layout_areas.lyrics.contains(position) {
+            ActiveSection::Lyrics
+        } else {
+            return;
+        };
+
+        // scrolling behaves exactly like j/k in the hovered section
+        self.focus_section(section);
+        let code = if down { KeyCode::Down } else { KeyCode::Up };
+        self.handle_normal_key_event(KeyEvent::new(code, KeyModifiers::NONE)).await;
+    }
+
+    async fn handle_mouse_click(&mut self, x: u16, y: u16) {
+        let double_click = self.last_click.is_some_and(|(at, last_x, last_y)| {
+            at.elapsed() < DOUBLE_CLICK_INTERVAL && last_x == x && last_y == y
+        });
+        self.last_click =
+            if double_click { None } else { Some((tokio::time::Instant::now(), x, y)) };
+
+        let position = Position::new(x, y);
+
+        if self.layout_areas.tabs.contains(position) {
+            if let Some(tab) = self.tab_at(x) {
+                self.open_tab(tab).await;
+            }
+            return;
+        }
+
+        if self.state.active_section == ActiveSection::Popup {
+            return;
+        }
+
+        if self.layout_areas.progress_bar.contains(position) {
+            self.seek_to_progress_bar(x).await;
+            return;
+        }
+
+        // the borders of the three columns can be dragged around to resize them
+        let columns = &self.layout_areas.columns;
+        for border in 0..2 {
+            let (left, right) = (columns[border], columns[border + 1]);
+            if y >= left.y
+                && y < left.bottom()
+                && (x == left.right().saturating_sub(1) || x == right.x)
+            {
+                self.dragged_border = Some(border);
+                return;
+            }
+        }
+
+        if self.layout_areas.list.contains(position) {
+            self.focus_section(ActiveSection::List);
+            let Some(row) = list_row(self.layout_areas.list, y, 0) else {
+                return;
+            };
+            match self.state.active_tab {
+                ActiveTab::Library => {
+                    self.artist_select_by_index(self.state.selected_artist.offset() + row)
+                }
+                ActiveTab::Albums => {
+                    self.album_select_by_index(self.state.selected_album.offset() + row)
+                }
+                ActiveTab::Playlists => {
+                    self.playlist_select_by_index(self.state.selected_playlist.offset() + row)
+                }
+                ActiveTab::Genres => {
+                    self.genre_select_by_index(self.state.selected_genre.offset() + row)
+                }
+                _ => return,
+            }
+        } else if self.layout_areas.tracks.contains(position) {
+            self.focus_section(ActiveSection::Tracks);
+            // the first row of the table is the header
+            let Some(row) = list_row(self.layout_areas.tracks, y, 1) else {
+                return;
+            };
+            match self.state.active_tab {
+                ActiveTab::Library => {
+                    self.track_select_by_index(self.state.selected_track.offset() + row)
+                }
+                ActiveTab::Albums => {
+                    self.album_track_select_by_index(self.state.selected_album_track.offset() + row)
+                }
+                ActiveTab::Playlists => self.playlist_track_select_by_index(
+                    self.state.selected_playlist_track.offset() + row,
+                ),
+                ActiveTab::Genres => {
+                    self.genre_track_select_by_index(self.state.selected_genre_track.offset() + row)
+                }
+                _ => return,
+            }
+        } else if self.layout_areas.queue.contains(position) {
+            self.focus_section(ActiveSection::Queue);
+            let Some(row) = list_row(self.layout_areas.queue, y, 0) else {
+                return;
+            };
+            let index = self.state.selected_queue_item.offset() + row;
+            if index >= self.state.queue.len() {
+                return;
+            }
+            self.state.selected_queue_item_manual_override = true;
+            self.state.selected_queue_item.select(Some(index));
+        } else if self.layout_areas.lyrics.contains(position) {
+            self.focus_section(ActiveSection::Lyrics);
+            let Some(mut row) = list_row(self.layout_areas.lyrics, y, 0) else {
+                return;
+            };
+            let offset = self.state.selected_lyric.offset();
+            let mut index = None;
+            for (i, height) in self.layout_areas.lyric_heights.iter().enumerate().skip(offset) {
+                if row < *height {
+                    index = Some(i);
+                    break;
+                }
+                row -= height;
+            }
+            let Some(index) = index else {
+                return;
+            };
+            self.state.selected_lyric_manual_override = true;
+            self.state.selected_lyric.select(Some(index));
+        } else {
+            return;
+        }
+
+        // double click does the same as pressing enter on the selected item
+        if double_click {
+            self.handle_normal_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)).await;
+        }
+    }
+
+    /// Focuses a section the same way keyboard navigation would
+    ///
+    fn focus_section(&mut self, section: ActiveSection) {
+        let current = self.state.active_section;
+        if current == section {
+            return;
+        }
+        match current {
+            ActiveSection::Queue => self.state.selected_queue_item_manual_override = false,
+            ActiveSection::Lyrics => self.state.selected_lyric_manual_override = false,
+            ActiveSection::List | ActiveSection::Tracks => {
+                if matches!(section, ActiveSection::Queue | ActiveSection::Lyrics) {
+                    self.state.last_section = current;
+                }
+            }
+            _ => {}
+        }
+        self.state.active_section = section;
+    }
+
+    /// Tabs are rendered as " title " separated by a single character divider
+    ///
+    fn tab_at(&self, x: u16) -> Option<ActiveTab> {
+        let mut start = self.layout_areas.tabs.x;
+        for (tab, title) in ActiveTab::ALL.into_iter().zip(TAB_TITLES) {
+            let width = title.chars().count() as u16 + 2;
+            if x >= start && x < start + width {
+                return Some(tab);
+            }
+            start += width + 1;
+        }
+        None
+    }
+
+    /// The tab a key is bound to. While typing a search, keys that type a character are left to
+    /// the search term
+    ///
+    fn tab_for_key(&self, key_event: &KeyEvent) -> Option<ActiveTab> {
+        let typing = self.state.active_tab == ActiveTab::Search && self.searching;
+        if typing
+            && matches!(key_event.code, KeyCode::Char(_))
+            && !key_event.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+        {
+            return None;
+        }
+        ActiveTab::for_action(self.keybindings.action(key_event)?)
+    }
+
+    async fn open_tab(&mut self, tab: ActiveTab) {
+        self.state.active_tab = tab;
+        self.searching = tab == ActiveTab::Search;
+        match tab {
+            ActiveTab::Home => {
+                self.refresh_dashboard();
+                self.state.active_section = ActiveSection::List;
+            }
+            ActiveTab::Library => {
+                if self.tracks.is_empty() {
+                    self.state.active_section = ActiveSection::List;
+                }
+            }
+            ActiveTab::Albums => {
+                if self.album_tracks.is_empty() {
+                    self.state.active_section = ActiveSection::List;
+                }
+            }
+            ActiveTab::Playlists => {
+                if self.playlist_tracks.is_empty() {
+                    self.state.active_section = ActiveSection::List;
+                }
+            }
+            ActiveTab::Search => {}
+            ActiveTab::Stats => {
+                self.refresh_stats().await;
+            }
+            ActiveTab::Genres => {
+                self.refresh_genres().await;
+                if self.genre_tracks.is_empty()
+                    || self.state.active_section != ActiveSection::Tracks
+                {
+                    self.state.active_section = ActiveSection::List;
+                }
+            }
+        }
+    }
+
+    async fn seek_to_progress_bar(&mut self, x: u16) {
+        if self.stopped {
+            return;
+        }
+        let area = self.layout_areas.progress_bar;
+        let duration = self
+            .state
+            .queue
+            .get(self.state.current_playback_state.current_index)
+            .map(|s| s.run_time_ticks as f64 / 10_000_000.0)
+            .unwrap_or(self.state.current_playback_state.duration);
+        if area.width == 0 || duration <= 0.0 {
+            return;
+        }
+
+        let ratio = (x.saturating_sub(area.x) as f64 / area.width as f64).clamp(0.0, 1.0);
+        let target = duration * ratio;
+
+        self.state.current_playback_state.position = target;
+        self.update_mpris_position(target);
+        self.mpv_handle.seek(target, SeekFlag::Absolute).await;
+        let _ = self.handle_discord(true).await;
     }
 
     fn toggle_section(&mut self, forwards: bool) {
@@ -2540,6 +2962,10 @@ impl App {
             // this is an online-only feature
             return;
         }
+        // smart playlists are ordered by their rules
+        if is_smart_playlist(&self.state.current_playlist.id) {
+            return;
+        }
         // make sure we don't let the user edit while a fetch is ongoing
         if self.playlist_incomplete || self.playlist_stale {
             return;
@@ -2946,15 +3372,55 @@ fn move_up(selected: Option<usize>) -> usize {
     selected.unwrap_or(0).saturating_sub(1)
 }
 
+/// Returns the visible row under `y` inside a bordered list or table, skipping `header` rows
+fn list_row(area: Rect, y: u16, header: u16) -> Option<usize> {
+    let top = area.y + 1 + header;
+    let bottom = area.bottom().saturating_sub(1);
+    if y < top || y >= bottom {
+        return None;
+    }
+    Some((y - top) as usize)
+}
+
 /// Enum types for section switching
 /// Active global tab
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
 pub enum ActiveTab {
+    Home,
+    // states saved before the Home tab existed keep opening on the Library
     #[default]
     Library,
     Albums,
     Playlists,
     Search,
+    Stats,
+    Genres,
+}
+
+impl ActiveTab {
+    /// In the order of the tab bar, `TAB_TITLES` and `active_tab as usize` follow it
+    pub const ALL: [ActiveTab; 7] = [
+        ActiveTab::Home,
+        ActiveTab::Library,
+        ActiveTab::Albums,
+        ActiveTab::Playlists,
+        ActiveTab::Search,
+        ActiveTab::Stats,
+        ActiveTab::Genres,
+    ];
+
+    fn for_action(action: KeyAction) -> Option<ActiveTab> {
+        match action {
+            KeyAction::TabHome => Some(ActiveTab::Home),
+            KeyAction::TabLibrary => Some(ActiveTab::Library),
+            KeyAction::TabAlbums => Some(ActiveTab::Albums),
+            KeyAction::TabPlaylists => Some(ActiveTab::Playlists),
+            KeyAction::TabSearch => Some(ActiveTab::Search),
+            KeyAction::TabStats => Some(ActiveTab::Stats),
+            KeyAction::TabGenres => Some(ActiveTab::Genres),
+            _ => None,
+        }
+    }
 }
 
 // Music - active "section"
@@ -12,6 +12,7 @@ Main Library tab
 -------------------------- */
 
 use crate::database::extension::DownloadStatus;
+use crate::selection::SelectionList;
 use crate::tui::App;
 use crate::{helpers, keyboard::*};
 
@@ -81,6 +82,13 @@ impl App {
             .constraints(lyrics_slot_constraints)
             .split(outer_layout[2]);
 
+        self.layout_areas.columns = [outer_layout[0], outer_layout[1], outer_layout[2]];
+        self.layout_areas.tracks = center[0];
+        self.layout_areas.queue = right[1];
+        if show_panel {
+            self.layout_areas.lyrics = right[0];
+        }
+
         self.render_library_left(frame, outer_layout);
         self.render_library_center(frame, &center);
         self.render_player(frame, &center);
@@ -142,6 +150,8 @@ impl App {
                 .split(outer_layout[0])
         };
 
+        self.layout_areas.list = left[0];
+
         match self.state.active_tab {
             ActiveTab::Library => {
                 self.render_library_artists(frame, left);
@@ -568,6 +578,9 @@ impl App {
                     })
                     .collect::<Vec<ListItem>>();
 
+                // wrapped lyrics span multiple rows, clicks need to know how many
+                self.layout_areas.lyric_heights = items.iter().map(|i| i.height()).collect();
+
                 let list = List::new(items)
                     .block(
                         lyrics_block
@@ -609,6 +622,8 @@ impl App {
             current.saturating_sub(1).min(total.saturating_sub(height))
         };
 
+        let marked = self.marked_rows(SelectionList::Queue);
+
         let items = self
             .state
             .queue
@@ -666,6 +681,9 @@ impl App {
                     Style::default().fg(artist_fg),
                 ));
 
+                if marked.contains(&index) {
+                    return ListItem::new(text).style(self.marked_style());
+                }
                 ListItem::new(text)
             })
             .collect::<Vec<ListItem>>();
@@ -695,6 +713,19 @@ impl App {
                         Line::from("(shuffle)").fg(queue_title_color).right_aligned()
                     } else {
                         Line::from("")
+                    })
+                    .title_bottom(match &self.state.radio {
+                        Some(radio) => Line::from(format!("(radio: {})", radio.seed.name()))
+                            .fg(queue_title_color)
+                            .left_aligned(),
+                        None => Line::from(""),
+                    })
+                    .title_bottom(if marked.is_empty() {
+                        Line::from("")
+                    } else {
+                        Line::from(format!("({} selected)", marked.len()))
+                            .fg(queue_title_color)
+                            .centered()
                     }),
             )
             .highlight_symbol(">>")
@@ -843,6 +874,7 @@ impl App {
 
         let terminal_height = frame.area().height as usize;
         let selection = self.state.selected_track.selected().unwrap_or(0);
+        let marked = self.marked_rows(SelectionList::Tracks);
 
         let items = tracks
             .iter()
@@ -923,6 +955,9 @@ impl App {
                     {
                         row = row.bg(album_header_background);
                     }
+                    if marked.contains(&i) {
+                        row = row.style(self.marked_style());
+                    }
                     return row;
                 }
 
@@ -1024,7 +1059,7 @@ impl App {
                     Style::default().fg(self.theme.resolve(&self.theme.foreground))
                 };
 
-                Row::new(cells).style(style)
+                Row::new(cells).style(if marked.contains(&i) { self.marked_style() } else { style })
             })
             .collect::<Vec<Row>>();
 
@@ -1034,6 +1069,8 @@ impl App {
             " Quit ".fg(self.theme.resolve(&self.theme.section_title)),
             "<^C> ".fg(self.theme.primary_color).bold(),
         ]);
+        let track_instructions =
+            self.selection_instructions(SelectionList::Tracks).unwrap_or(track_instructions);
 
         let mut widths: Vec<Constraint> = vec![
             Constraint::Length(4),
@@ -1175,6 +1212,7 @@ impl App {
 
         let terminal_height = frame.area().height as usize;
         let selection = self.state.selected_album_track.selected().unwrap_or(0);
+        let marked = self.marked_rows(SelectionList::AlbumTracks);
 
         let items = tracks
             .iter()
@@ -1275,7 +1313,9 @@ impl App {
                     hours_optional_text, minutes, seconds
                 )));
 
-                Row::new(cells).style(if track.id == self.active_song_id {
+                Row::new(cells).style(if marked.contains(&i) {
+                    self.marked_style()
+                } else if track.id == self.active_song_id {
                     Style::default().fg(self.theme.primary_color).italic()
                 } else if track.disliked {
                     Style::default().fg(self.theme.resolve(&self.theme.foreground_dim))
@@ -1291,6 +1331,8 @@ impl App {
             " Quit ".fg(self.theme.resolve(&self.theme.section_title)),
             "<^C> ".fg(self.theme.primary_color).bold(),
         ]);
+        let track_instructions =
+            self.selection_instructions(SelectionList::AlbumTracks).unwrap_or(track_instructions);
 
         let mut widths: Vec<Constraint> = vec![
             Constraint::Length(items.len().to_string().len() as u16 + 2),
@@ -1490,6 +1532,37 @@ impl App {
 
                     out.push(Span::styled(flags.join(" "), Style::default().fg(fg)));
                 }
+
+                let speed = self.state.current_playback_state.speed;
+                if speed != 1.0 {
+                    out.push(Span::styled(
+                        " › ",
+                        Style::default().fg(fg).add_modifier(Modifier::DIM),
+                    ));
+                    out.push(Span::styled(
+                        format!("{:.1}x", speed),
+                        Style::default().fg(self.theme.primary_color),
+                    ));
+                }
+
+                if let Some(ab_loop) = self.ab_loop.label() {
+                    out.push(Span::styled(
+                        " › ",
+                        Style::default().fg(fg).add_modifier(Modifier::DIM),
+                    ));
+                    out.push(Span::styled(ab_loop, Style::default().fg(self.theme.primary_color)));
+                }
+
+                if let Some(timer) = &self.state.sleep_timer {
+                    out.push(Span::styled(
+                        " › ",
+                        Style::default().fg(fg).add_modifier(Modifier::DIM),
+                    ));
+                    out.push(Span::styled(
+                        format!("☾ {}", timer.label()),
+                        Style::default().fg(self.theme.primary_color),
+                    ));
+                }
                 out
             })
             .unwrap_or_else(|| {
@@ -1646,6 +1719,27 @@ impl App {
         let percentage =
             if total_seconds > 0.0 { (visible_position / total_seconds) * 100.0 } else { 0.0 };
 
+        let label = Line::from(format!(
+            "{}   {:.0}% ",
+            if self.buffering {
+                self.spinner_stages[self.spinner]
+            } else if self.paused {
+                "⏸︎"
+            } else {
+                "►"
+            },
+            percentage,
+        ));
+
+        // the gauge itself starts after the label and a single space
+        let label_width = label.width() as u16 + 1;
+        self.layout_areas.progress_bar = Rect {
+            x: progress_bar_area[0].x + label_width,
+            y: progress_bar_area[0].y,
+            width: progress_bar_area[0].width.saturating_sub(label_width),
+            height: 1,
+        };
+
         frame.render_widget(
             LineGauge::default()
                 .block(Block::bordered().borders(Borders::NONE))
@@ -1663,20 +1757,26 @@ impl App {
                 )
                 .style(Style::default().fg(self.theme.resolve(&self.theme.foreground)))
                 .ratio(percentage.clamp(0.0, 100.0) / 100.0)
-                .label(Line::from(format!(
-                    "{}   {:.0}% ",
-                    if self.buffering {
-                        self.spinner_stages[self.spinner]
-                    } else if self.paused {
-                        "⏸︎"
-                    } else {
-                        "►"
-                    },
-                    percentage,
-                ))),
+                .label(label),
             progress_bar_area[0],
         );
 
+        // A-B loop markers on top of the gauge
+        let bar = self.layout_areas.progress_bar;
+        if total_seconds > 0.0 && bar.width > 0 {
+            let points = [(self.ab_loop.a, "A"), (self.ab_loop.b, "B")];
+            for (point, symbol) in points {
+                let Some(point) = point else {
+                    continue;
+                };
+                let offset = ((point / total_seconds).clamp(0.0, 1.0) * bar.width as f64) as u16;
+                let x = bar.x + offset.min(bar.width - 1);
+                frame.buffer_mut()[(x, bar.y)].set_symbol(symbol).set_style(
+                    Style::default().fg(self.theme.primary_color).add_modifier(Modifier::BOLD),
+                );
+            }
+        }
+
         frame.render_widget(
             Paragraph::new(Line::from(metadata_spans))
                 .centered()
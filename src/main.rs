@@ -1,22 +1,41 @@
 #![cfg_attr(target_os = "macos", allow(unexpected_cfgs))]
+mod ab_loop;
+mod audio_output;
 mod client;
 mod config;
+mod dashboard;
 mod database;
 mod discord;
+mod equalizer;
+mod fader;
+mod genres;
 mod help;
 mod helpers;
+mod ipc;
+mod keybindings;
 mod keyboard;
 mod library;
 mod macos;
 mod mpris;
 mod mpv;
 mod player;
+mod playlist_files;
 mod playlists;
 mod popup;
 mod queue;
+mod queue_history;
+mod radio;
+mod remote;
+mod saved_queues;
 mod search;
+mod selection;
+mod sleep_timer;
+mod smart_playlists;
+mod smart_shuffle;
 mod sort;
+mod stats;
 mod themes;
+mod track_info;
 mod tui;
 
 use dirs::data_dir;
@@ -35,7 +54,8 @@ use crossterm::{
 };
 // keyboard enhancement flags are used to allow for certain normally blocked key combinations... e.g. ctrl+enter...
 use crossterm::event::{
-    KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    DisableMouseCapture, EnableMouseCapture, KeyboardEnhancementFlags, PopKeyboardEnhancementFlags,
+    PushKeyboardEnhancementFlags,
 };
 use libmpv2::{MPV_CLIENT_API_MAJOR, MPV_CLIENT_API_MINOR, MPV_CLIENT_API_VERSION};
 use ratatui::prelude::{CrosstermBackend, Terminal};
@@ -60,6 +80,9 @@ async fn main() {
             print_help();
             return;
         }
+        if args[1] == "ctl" {
+            std::process::exit(ipc::run_ctl(&args[2..]));
+        }
     }
 
     let _lockfile = check_single_instance();
@@ -92,6 +115,7 @@ async fn main() {
         panicked_clone.store(true, Ordering::SeqCst);
         let _ = disable_raw_mode();
         let _ = execute!(stdout(), PopKeyboardEnhancementFlags);
+        let _ = execute!(stdout(), DisableMouseCapture);
         let _ = execute!(stdout(), LeaveAlternateScreen);
         let bt = Backtrace::force_capture();
         log::error!("Panic occurred: {}", info);
@@ -142,6 +166,7 @@ async fn main() {
         stdout(),
         PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
     );
+    let _ = execute!(stdout(), EnableMouseCapture);
 
     let mut terminal = Terminal::new(CrosstermBackend::new(stdout())).unwrap();
 
@@ -160,7 +185,9 @@ async fn main() {
         if app.exit || panicked.load(Ordering::SeqCst) {
             let _ = disable_raw_mode();
             let _ = execute!(stdout(), PopKeyboardEnhancementFlags);
+            let _ = execute!(stdout(), DisableMouseCapture);
             let _ = execute!(stdout(), LeaveAlternateScreen);
+            ipc::remove_socket();
             break;
         }
         // draw() renders the app state to the terminal
@@ -207,6 +234,7 @@ fn check_single_instance() -> File {
 fn print_help() {
     println!("jellyfin-tui {}", env!("CARGO_PKG_VERSION"));
     println!("Usage: jellyfin-tui [OPTIONS]");
+    println!("       jellyfin-tui ctl <COMMAND>");
     println!("\nArguments:");
     println!("  --version\t\tPrint version information");
     println!("  --help\t\tPrint this help message");
@@ -214,6 +242,9 @@ fn print_help() {
     println!("  --select-server\tForce server selection on startup");
     println!("  --offline\t\tStart in offline mode");
 
+    println!("\nRemote control:");
+    println!("  ctl <COMMAND>\t\tControl a running instance, see `jellyfin-tui ctl` for commands");
+
     println!("\nControls:");
     println!("  For a list of controls, press '?' in the application.");
 }
@@ -63,6 +63,8 @@ impl App {
 
     pub async fn handle_mpris_events(&mut self) {
         while let Ok(event) = self.mpris_rx.try_recv() {
+            // each command is an undo step of its own
+            self.queue_history.end_step();
             match event {
                 MediaControlEvent::Toggle => {
                     if self.paused {
@@ -1,37 +1,178 @@
+use crate::config::Transitions;
+use crate::fader::Fader;
 use crate::tui::{MpvPlaybackState, Repeat};
+use libmpv2::events::{Event, PropertyData};
 use libmpv2::{Format, Mpv};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::mpsc::{Receiver, Sender};
+use std::sync::mpsc::{Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
 use std::thread;
 use std::time::Duration;
 use tokio::sync::oneshot;
 use tokio::time::Instant;
 
+/// How many times in a row the app tries to bring a dead mpv back before giving up
+pub const MPV_MAX_RESTARTS: u32 = 3;
+
 pub struct MpvHandle {
     tx: Sender<MpvCommand>,
+    wakeup: Arc<Wakeup>,
     pub dead: AtomicBool,
+    thread: thread::JoinHandle<()>,
+}
+
+/// Wakes the runtime thread when a command was queued or mpv has new events.
+/// Kept apart from the command channel, so that channel disconnects once the handle is dropped
+#[derive(Default)]
+struct Wakeup {
+    pending: Mutex<bool>,
+    condvar: Condvar,
+}
+
+impl Wakeup {
+    fn notify(&self) {
+        *self.pending.lock().unwrap_or_else(PoisonError::into_inner) = true;
+        self.condvar.notify_one();
+    }
+
+    /// Sleeps until notified or the timeout passed, without a timeout only a notification wakes it
+    fn wait(&self, timeout: Option<Duration>) {
+        let pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut pending = match timeout {
+            Some(timeout) => {
+                self.condvar
+                    .wait_timeout_while(pending, timeout, |pending| !*pending)
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .0
+            }
+            None => self
+                .condvar
+                .wait_while(pending, |pending| !*pending)
+                .unwrap_or_else(PoisonError::into_inner),
+        };
+        *pending = false;
+    }
+}
+
+/// Everything mpv tells us through observed properties. Position is not observed since it changes
+/// constantly, it is read on the tick and after seeks instead.
+#[derive(Default)]
+struct Observed {
+    duration: f64,
+    current_index: usize,
+    volume: i64,
+    speed: f64,
+    audio_bitrate: i64,
+    audio_samplerate: i64,
+    hr_channels: String,
+    file_format: String,
+    idle_active: bool,
+    paused_for_cache: bool,
+    seeking: bool,
+    paused: bool,
+}
+
+impl Observed {
+    fn update(&mut self, name: &str, change: PropertyData) {
+        match (name, change) {
+            ("duration", PropertyData::Double(v)) => self.duration = v,
+            // -1 while nothing is playing, keep the previous index then
+            ("playlist-pos", PropertyData::Int64(v)) if v >= 0 => self.current_index = v as usize,
+            ("volume", PropertyData::Int64(v)) => self.volume = v,
+            ("speed", PropertyData::Double(v)) => self.speed = v,
+            ("audio-bitrate", PropertyData::Int64(v)) => self.audio_bitrate = v,
+            ("audio-params/samplerate", PropertyData::Int64(v)) => self.audio_samplerate = v,
+            ("audio-params/hr-channels", PropertyData::Str(v)) => self.hr_channels = v.to_string(),
+            ("file-format", PropertyData::Str(v)) => self.file_format = v.to_string(),
+            ("idle-active", PropertyData::Flag(v)) => self.idle_active = v,
+            ("paused-for-cache", PropertyData::Flag(v)) => self.paused_for_cache = v,
+            ("seeking", PropertyData::Flag(v)) => self.seeking = v,
+            ("pause", PropertyData::Flag(v)) => self.paused = v,
+            _ => {}
+        }
+    }
 }
 
+const OBSERVED_PROPERTIES: [(&str, Format); 12] = [
+    ("duration", Format::Double),
+    ("playlist-pos", Format::Int64),
+    ("volume", Format::Int64),
+    ("speed", Format::Double),
+    ("audio-bitrate", Format::Int64),
+    ("audio-params/samplerate", Format::Int64),
+    ("audio-params/hr-channels", Format::String),
+    ("file-format", Format::String),
+    ("idle-active", Format::Flag),
+    ("paused-for-cache", Format::Flag),
+    ("seeking", Format::Flag),
+    ("pause", Format::Flag),
+];
+
 /// The thread that keeps in sync with the mpv thread
 fn t_mpv_runtime(
-    mpv: Mpv,
+    mut mpv: Mpv,
     sender: Sender<MpvPlaybackState>,
     command_rx: Receiver<MpvCommand>,
+    wakeup: Arc<Wakeup>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let _ = mpv.command("playlist_clear", &["force"]);
 
     // this is for resume on launch // filename, target
     let mut pending_resume = None;
+    let version = mpv.get_property::<String>("mpv-version").unwrap_or_default();
+    let indexed_loadfile = loadfile_takes_index(&version);
+    let mut fader = Fader::new(indexed_loadfile);
 
-    const POLL_INTERVAL: Duration = Duration::from_millis(200);
-    let mut last = MpvPlaybackState::default();
-    let mut next_poll = Instant::now();
+    // position is only pushed this often, everything else as soon as it changes
+    const TICK: Duration = Duration::from_secs(1);
+    // pending resume retries its seek at this rate
+    const RESUME_INTERVAL: Duration = Duration::from_millis(50);
 
-    // This loop polls for commands from the UI, intentionally without immediate latency.
-    // the UI conversely polls for MpvPlaybackState
+    let mut observed = Observed { speed: 1.0, ..Default::default() };
+    let mut position = 0.0;
+    let mut last = MpvPlaybackState::default();
+    let mut next_tick = Instant::now();
+    let mut was_paused = false;
+    // set while the fader wants to be called more often than the tick
+    let mut fade_timeout = None;
+
+    // The thread sleeps until either the UI sends a command or mpv signals new events through
+    // the wakeup callback, or the position tick is due. While paused or idle the position doesn't
+    // move, so only those wake it. It exits once the handle is dropped.
     loop {
-        while let Ok(cmd) = command_rx.try_recv() {
-            handle_command(&mpv, cmd, &mut pending_resume);
+        let playing = !observed.paused && !observed.idle_active;
+        let mut timeout = playing.then(|| next_tick.saturating_duration_since(Instant::now()));
+        if pending_resume.is_some() {
+            timeout = Some(timeout.map_or(RESUME_INTERVAL, |t| t.min(RESUME_INTERVAL)));
+        }
+        if let Some(fade_timeout) = fade_timeout {
+            timeout = Some(timeout.map_or(fade_timeout, |t| t.min(fade_timeout)));
+        }
+
+        wakeup.wait(timeout);
+        loop {
+            match command_rx.try_recv() {
+                Ok(cmd) => {
+                    handle_command(&mpv, cmd, &mut pending_resume, &mut fader, indexed_loadfile)
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return Ok(()),
+            }
+        }
+
+        // playback restarts after every seek and new file, the position jumped, so it shouldn't wait for the tick
+        let mut position_jumped = false;
+        // draining an empty event queue is cheap, wakeups from mpv and the UI aren't told apart
+        while let Some(event) = mpv.wait_event(0.0) {
+            match event {
+                Ok(Event::PropertyChange { name, change, .. }) => {
+                    observed.update(name, change);
+                }
+                Ok(Event::PlaybackRestart) => position_jumped = true,
+                Ok(Event::Shutdown) => return Ok(()),
+                Ok(_) => {}
+                Err(e) => log::error!("mpv event error: {:?}", e),
+            }
         }
 
         if let Some(resume) = &mut pending_resume {
@@ -47,58 +188,61 @@ fn t_mpv_runtime(
                     pending_resume = None;
                 }
             }
+            position_jumped = true;
         }
 
-        // timed MPV poll
-        if Instant::now() >= next_poll {
-            let position = mpv.get_property("time-pos").unwrap_or(0.0);
-            let duration = mpv.get_property("duration").unwrap_or(0.0);
-            let current_index = match mpv.get_property::<i64>("playlist-pos") {
-                Ok(i) if i >= 0 => i as usize,
-                _ => 0, // or keep previous value
-            };
-            let volume = mpv.get_property("volume").unwrap_or(0);
-            let audio_bitrate = mpv.get_property("audio-bitrate").unwrap_or(0);
-            let audio_samplerate = mpv.get_property("audio-params/samplerate").unwrap_or(0);
-            let hr_channels: String =
-                mpv.get_property("audio-params/hr-channels").unwrap_or_default();
-            let file_format: String = mpv.get_property("file-format").unwrap_or_default();
-
-            let idle_active = mpv.get_property("idle-active").unwrap_or(false);
-
-            let paused_for_cache = mpv.get_property("paused-for-cache").unwrap_or(false);
-            let seeking = mpv.get_property("seeking").unwrap_or(false);
-            let seek_active = pending_resume.is_some();
-            let buffering = paused_for_cache || seeking || seek_active;
-
-            if (position - last.position).abs() >= 0.95
-                || (duration - last.duration).abs() >= 0.95
-                || current_index != last.current_index
-                || volume != last.volume
-                || seek_active != last.seek_active
-                || buffering != last.buffering
-            {
-                last = MpvPlaybackState {
-                    position,
-                    duration,
-                    current_index,
-                    volume,
-                    audio_bitrate,
-                    audio_samplerate,
-                    hr_channels,
-                    file_format,
-                    buffering,
-                    seek_active,
-                    idle_active,
-                };
-
-                let _ = sender.send(last.clone());
-            }
-
-            next_poll = Instant::now() + POLL_INTERVAL;
+        fade_timeout = fader.tick(
+            &mpv,
+            observed.current_index,
+            observed.paused,
+            observed.duration,
+            observed.speed,
+            position_jumped,
+        );
+
+        let tick = Instant::now() >= next_tick;
+        // no more ticks until it resumes, so the position is read once more where it stopped
+        let just_paused = observed.paused && !was_paused;
+        was_paused = observed.paused;
+        if tick || position_jumped || just_paused {
+            position = mpv.get_property("time-pos").unwrap_or(0.0);
+        }
+        if tick {
+            next_tick = Instant::now() + TICK;
         }
 
-        thread::sleep(Duration::from_millis(2));
+        let seek_active = pending_resume.is_some();
+        let current = MpvPlaybackState {
+            position,
+            duration: observed.duration,
+            current_index: observed.current_index,
+            volume: observed.volume,
+            speed: observed.speed,
+            audio_bitrate: observed.audio_bitrate,
+            audio_samplerate: observed.audio_samplerate,
+            hr_channels: observed.hr_channels.clone(),
+            file_format: observed.file_format.clone(),
+            buffering: observed.paused_for_cache || observed.seeking || seek_active,
+            seek_active,
+            idle_active: observed.idle_active,
+        };
+
+        if current.position != last.position
+            || current.duration != last.duration
+            || current.current_index != last.current_index
+            || current.volume != last.volume
+            || current.speed != last.speed
+            || current.audio_bitrate != last.audio_bitrate
+            || current.audio_samplerate != last.audio_samplerate
+            || current.hr_channels != last.hr_channels
+            || current.file_format != last.file_format
+            || current.buffering != last.buffering
+            || current.seek_active != last.seek_active
+            || current.idle_active != last.idle_active
+        {
+            let _ = sender.send(current.clone());
+            last = current;
+        }
     }
 }
 
@@ -118,11 +262,47 @@ enum MpvCommand {
     PlaylistMoveNoReply { from: usize, to: usize },
     SetVolume { volume: i64, reply: Reply },
     SetRepeat { repeat: Repeat, reply: Reply },
-    LoadFiles { urls: Vec<String>, flag: LoadFileFlag, index: Option<i64>, reply: Reply },
+    SetSpeed { speed: f64, pitch_correction: bool, reply: Reply },
+    SetAbLoop { a: Option<f64>, b: Option<f64>, reply: Reply },
+    Position { reply: oneshot::Sender<Option<f64>> },
+    SetAudioFilter { label: &'static str, filter: Option<String>, reply: Reply },
+    AudioFilterCommand { label: &'static str, command: &'static str, arg: String, reply: Reply },
+    SetTransitions { transitions: Transitions, reply: Reply },
+    SetAudioDevice { name: String, reply: Reply },
+    AudioDevices { reply: oneshot::Sender<Vec<AudioDevice>> },
+    LoadFiles { files: Vec<MpvFile>, flag: LoadFileFlag, index: Option<i64>, reply: Reply },
     Await { reply: Reply },
 }
 
-fn handle_command(mpv: &Mpv, cmd: MpvCommand, pending_resume: &mut Option<PendingResume>) {
+impl MpvCommand {
+    /// Commands that move playback somewhere else, or stop it
+    fn interrupts_crossfade(&self) -> bool {
+        matches!(
+            self,
+            MpvCommand::Pause { .. }
+                | MpvCommand::Stop { .. }
+                | MpvCommand::Next { .. }
+                | MpvCommand::Previous { .. }
+                | MpvCommand::Seek { .. }
+                | MpvCommand::HardSeek { .. }
+                | MpvCommand::PlayIndex { .. }
+                | MpvCommand::LoadFiles { flag: LoadFileFlag::Replace, .. }
+        )
+    }
+}
+
+fn handle_command(
+    mpv: &Mpv,
+    cmd: MpvCommand,
+    pending_resume: &mut Option<PendingResume>,
+    fader: &mut Fader,
+    indexed_loadfile: bool,
+) {
+    // a pause or stop that is still fading out happens now, before anything else
+    fader.finish(mpv);
+    if cmd.interrupts_crossfade() {
+        fader.interrupt(mpv);
+    }
     match cmd {
         MpvCommand::Play { reply } => {
             if let Some(resume) = pending_resume.as_mut() {
@@ -140,18 +320,10 @@ fn handle_command(mpv: &Mpv, cmd: MpvCommand, pending_resume: &mut Option<Pendin
                 let _ = reply.send(true);
                 return;
             }
-            let res = mpv.set_property("pause", true);
-            if let Err(e) = &res {
-                log::error!("mpv pause failed: {:?}", e);
-            }
-            let _ = reply.send(res.is_ok());
+            let _ = reply.send(fader.pause(mpv));
         }
         MpvCommand::Stop { reply } => {
-            let res = mpv.command("stop", &[]);
-            if let Err(e) = &res {
-                log::error!("mpv stop failed: {:?}", e);
-            }
-            let _ = reply.send(res.is_ok());
+            let _ = reply.send(fader.stop(mpv));
         }
         MpvCommand::Next { reply } => {
             let res = mpv.command("playlist_next", &["force"]);
@@ -225,19 +397,76 @@ fn handle_command(mpv: &Mpv, cmd: MpvCommand, pending_resume: &mut Option<Pendin
             }
             let _ = reply.send(ok);
         }
-        MpvCommand::LoadFiles { urls, flag, index, reply } => {
+        MpvCommand::SetSpeed { speed, pitch_correction, reply } => {
+            let res = mpv
+                .set_property("audio-pitch-correction", pitch_correction)
+                .and_then(|_| mpv.set_property("speed", speed));
+            if let Err(e) = &res {
+                log::error!("mpv failed to set the speed: {:?}", e);
+            }
+            let _ = reply.send(res.is_ok());
+        }
+        MpvCommand::SetAbLoop { a, b, reply } => {
+            let set = |name: &str, point: Option<f64>| match point {
+                Some(seconds) => mpv.set_property(name, seconds),
+                None => mpv.set_property(name, "no"),
+            };
+            let res = set("ab-loop-a", a).and_then(|_| set("ab-loop-b", b));
+            if let Err(e) = &res {
+                log::error!("mpv failed to set the A-B loop: {:?}", e);
+            }
+            let _ = reply.send(res.is_ok());
+        }
+        MpvCommand::Position { reply } => {
+            let _ = reply.send(mpv.get_property::<f64>("time-pos").ok());
+        }
+        MpvCommand::SetAudioFilter { label, filter, reply } => {
+            // fails if the filter isn't there yet, that's fine
+            let _ = mpv.command("af", &["remove", &format!("@{}", label)]);
+            let res = match filter {
+                Some(filter) => mpv.command("af", &["add", &format!("@{}:{}", label, filter)]),
+                None => Ok(()),
+            };
+            if let Err(e) = &res {
+                log::error!("mpv af add failed: {:?}", e);
+            }
+            let _ = reply.send(res.is_ok());
+        }
+        MpvCommand::AudioFilterCommand { label, command, arg, reply } => {
+            let res = mpv.command("af-command", &[label, command, &arg]);
+            let _ = reply.send(res.is_ok());
+        }
+        MpvCommand::SetAudioDevice { name, reply } => {
+            let res = mpv.set_property("audio-device", name.as_str());
+            if let Err(e) = &res {
+                log::error!("mpv failed to switch to audio device '{}': {:?}", name, e);
+            }
+            let _ = reply.send(res.is_ok());
+        }
+        MpvCommand::AudioDevices { reply } => {
+            // node properties read as a string come back as JSON
+            let list = mpv.get_property::<String>("audio-device-list").unwrap_or_default();
+            let devices = serde_json::from_str::<Vec<AudioDevice>>(&list).unwrap_or_else(|e| {
+                log::error!("Failed to parse the mpv audio device list: {}", e);
+                vec![]
+            });
+            let _ = reply.send(devices);
+        }
+        MpvCommand::SetTransitions { transitions, reply } => {
+            let _ = reply.send(fader.set_transitions(mpv, transitions));
+        }
+        MpvCommand::LoadFiles { files, flag, index, reply } => {
             let mut ok = true;
             let flag = flag.as_str();
 
-            for url in urls {
-                let res = match index {
-                    Some(i) => mpv.command("loadfile", &[&url, flag, &i.to_string()]),
-                    None => mpv.command("loadfile", &[&url, flag]),
-                };
+            for file in files {
+                fader.remember(&file);
+                let options = file.options();
+                let res = loadfile(mpv, &file.url, flag, index, &options, indexed_loadfile);
 
                 if res.is_err() {
                     ok = false;
-                    log::error!("mpv loadfile failed for '{}'", url);
+                    log::error!("mpv loadfile failed for '{}'", file.url);
                 }
             }
 
@@ -249,16 +478,61 @@ fn handle_command(mpv: &Mpv, cmd: MpvCommand, pending_resume: &mut Option<Pendin
     }
 }
 
+/// Whether this mpv takes `loadfile <url> <flag> <index> <options>`, added in 0.38.
+/// Older versions expect the options right after the flag and have no index
+///
+fn loadfile_takes_index(version: &str) -> bool {
+    let version = version.trim_start_matches("mpv").trim().trim_start_matches('v');
+    let mut numbers = version.split(|c: char| !c.is_ascii_digit()).map(str::parse::<u32>);
+    match (numbers.next(), numbers.next()) {
+        (Some(Ok(major)), Some(Ok(minor))) => (major, minor) >= (0, 38),
+        // untagged git builds don't carry a version, they are newer than any release anyway
+        _ => true,
+    }
+}
+
+pub(crate) fn loadfile(
+    mpv: &Mpv,
+    url: &str,
+    flag: &str,
+    index: Option<i64>,
+    options: &str,
+    indexed: bool,
+) -> Result<(), libmpv2::Error> {
+    match (index, options.is_empty()) {
+        (Some(i), true) => mpv.command("loadfile", &[url, flag, &i.to_string()]),
+        (None, true) => mpv.command("loadfile", &[url, flag]),
+        // the index is only used by insert-at, but it has to be present to pass options
+        (i, false) if indexed => {
+            mpv.command("loadfile", &[url, flag, &i.unwrap_or(-1).to_string(), options])
+        }
+        (_, false) => mpv.command("loadfile", &[url, flag, options]),
+    }
+}
+
 impl MpvHandle {
     pub fn new(config: &serde_yaml::Value, sender: Sender<MpvPlaybackState>) -> MpvHandle {
-        let mpv = Mpv::with_initializer(|mpv| {
-            mpv.set_option("msg-level", "ffmpeg/demuxer=no").unwrap();
+        Self::try_new(config, sender).unwrap_or_else(|e| panic!(" [XX] {}", e))
+    }
+
+    /// Same as `new`, but doesn't panic. Used to bring mpv back after it died
+    ///
+    pub fn try_new(
+        config: &serde_yaml::Value,
+        sender: Sender<MpvPlaybackState>,
+    ) -> Result<MpvHandle, Box<dyn std::error::Error>> {
+        let mpv_err = |what: String| move |e: libmpv2::Error| format!("{what}: {:?}", e);
+
+        let mut mpv = Mpv::with_initializer(|mpv| {
+            mpv.set_option("msg-level", "ffmpeg/demuxer=no")?;
             Ok(())
         })
-        .expect(" [XX] Failed to initiate mpv context");
-        mpv.set_property("vo", "null").unwrap();
-        mpv.set_property("volume", 100).unwrap();
-        mpv.set_property("prefetch-playlist", "yes").unwrap(); // gapless playback
+        .map_err(mpv_err("Failed to initiate mpv context".into()))?;
+        mpv.set_property("vo", "null").map_err(mpv_err("Failed to set vo".into()))?;
+        mpv.set_property("volume", 100).map_err(mpv_err("Failed to set volume".into()))?;
+        // gapless playback
+        mpv.set_property("prefetch-playlist", "yes")
+            .map_err(mpv_err("Failed to set prefetch-playlist".into()))?;
 
         // no console output (it shifts the tui around)
         let _ = mpv.set_property("quiet", "yes");
@@ -269,9 +543,8 @@ impl MpvHandle {
             if let Some(mpv_config) = mpv_config.as_mapping() {
                 for (key, value) in mpv_config {
                     if let (Some(key), Some(value)) = (key.as_str(), value.as_str()) {
-                        mpv.set_property(key, value).unwrap_or_else(|e| {
-                            panic!("This is not a valid mpv property {key}: {:?}", e)
-                        });
+                        mpv.set_property(key, value)
+                            .map_err(mpv_err(format!("This is not a valid mpv property {key}")))?;
                         log::info!("Set mpv property: {} = {}", key, value);
                     }
                 }
@@ -280,19 +553,42 @@ impl MpvHandle {
             }
         }
 
-        mpv.disable_deprecated_events().unwrap();
-        mpv.observe_property("volume", Format::Int64, 0).unwrap();
-        mpv.observe_property("demuxer-cache-state", Format::Node, 0).unwrap();
+        mpv.disable_deprecated_events().map_err(mpv_err("Failed to disable events".into()))?;
+        for (name, format) in OBSERVED_PROPERTIES {
+            mpv.observe_property(name, format, 0)
+                .map_err(mpv_err(format!("Failed to observe {name}")))?;
+        }
 
         let (tx, rx) = std::sync::mpsc::channel::<MpvCommand>();
 
-        thread::spawn(move || {
-            if let Err(e) = t_mpv_runtime(mpv, sender, rx) {
+        // mpv calls this from its own threads whenever new events are queued, so it only pokes
+        // the runtime thread which then reads them with wait_event
+        let wakeup = Arc::new(Wakeup::default());
+        let mpv_wakeup = Arc::clone(&wakeup);
+        mpv.set_wakeup_callback(move || mpv_wakeup.notify());
+
+        let thread_wakeup = Arc::clone(&wakeup);
+        let thread = thread::spawn(move || {
+            if let Err(e) = t_mpv_runtime(mpv, sender, rx, thread_wakeup) {
                 log::error!("Error in mpv playlist thread: {}", e);
             }
         });
 
-        Self { tx, dead: AtomicBool::new(false) }
+        Ok(Self { tx, wakeup, dead: AtomicBool::new(false), thread })
+    }
+
+    /// Queues a command and wakes the runtime thread. False if the thread is gone
+    ///
+    fn send(&self, cmd: MpvCommand) -> bool {
+        let sent = self.tx.send(cmd).is_ok();
+        self.wakeup.notify();
+        sent
+    }
+
+    /// True once a command failed to reach mpv or the runtime thread has exited (or panicked)
+    ///
+    pub fn is_dead(&self) -> bool {
+        self.dead.load(Ordering::Relaxed) || self.thread.is_finished()
     }
 
     pub async fn play(&self) {
@@ -350,7 +646,7 @@ impl MpvHandle {
         if self.dead.load(Ordering::Relaxed) {
             return;
         }
-        let _ = self.tx.send(MpvCommand::PlaylistMoveNoReply { from, to });
+        let _ = self.send(MpvCommand::PlaylistMoveNoReply { from, to });
     }
 
     pub async fn set_volume(&self, volume: i64) {
@@ -360,8 +656,74 @@ impl MpvHandle {
     pub async fn set_repeat(&self, repeat: Repeat) {
         self.call(|reply| MpvCommand::SetRepeat { repeat, reply }).await
     }
-    pub async fn load_files(&self, urls: Vec<String>, flag: LoadFileFlag, index: Option<i64>) {
-        self.call(|reply| MpvCommand::LoadFiles { urls, flag, index, reply }).await
+
+    /// Pitch correction keeps voices sounding natural, without it the pitch follows the speed
+    ///
+    pub async fn set_speed(&self, speed: f64, pitch_correction: bool) {
+        self.call(|reply| MpvCommand::SetSpeed { speed, pitch_correction, reply }).await
+    }
+
+    /// Loops between the two points of the current track, `None` for both turns it off
+    ///
+    pub async fn set_ab_loop(&self, a: Option<f64>, b: Option<f64>) {
+        self.call(|reply| MpvCommand::SetAbLoop { a, b, reply }).await
+    }
+
+    /// The exact position, the one in the playback state is only updated once a second
+    ///
+    pub async fn position(&self) -> Option<f64> {
+        if self.dead.load(Ordering::Relaxed) {
+            return None;
+        }
+        let (tx, rx) = oneshot::channel();
+        if !self.send(MpvCommand::Position { reply: tx }) {
+            self.dead.store(true, Ordering::Relaxed);
+            return None;
+        }
+        rx.await.ok().flatten()
+    }
+
+    /// Replaces the audio filter with this label, `None` removes it
+    ///
+    pub async fn set_audio_filter(&self, label: &'static str, filter: Option<String>) {
+        self.call(|reply| MpvCommand::SetAudioFilter { label, filter, reply }).await
+    }
+
+    /// Sends a runtime command to a lavfi filter, e.g. `volume` to the `volume` filter
+    ///
+    pub async fn audio_filter_command(
+        &self,
+        label: &'static str,
+        command: &'static str,
+        arg: String,
+    ) {
+        self.call(|reply| MpvCommand::AudioFilterCommand { label, command, arg, reply }).await
+    }
+
+    pub async fn set_audio_device(&self, name: String) {
+        self.call(|reply| MpvCommand::SetAudioDevice { name, reply }).await
+    }
+
+    /// The outputs mpv can play to right now. Always starts with `auto`
+    ///
+    pub async fn audio_devices(&self) -> Vec<AudioDevice> {
+        if self.dead.load(Ordering::Relaxed) {
+            return vec![];
+        }
+        let (tx, rx) = oneshot::channel();
+        if !self.send(MpvCommand::AudioDevices { reply: tx }) {
+            self.dead.store(true, Ordering::Relaxed);
+            return vec![];
+        }
+        rx.await.unwrap_or_default()
+    }
+
+    pub async fn set_transitions(&self, transitions: Transitions) {
+        self.call(|reply| MpvCommand::SetTransitions { transitions, reply }).await
+    }
+
+    pub async fn load_files(&self, files: Vec<MpvFile>, flag: LoadFileFlag, index: Option<i64>) {
+        self.call(|reply| MpvCommand::LoadFiles { files, flag, index, reply }).await
     }
 
     pub async fn await_reply(&self) {
@@ -375,7 +737,7 @@ impl MpvHandle {
 
         let (tx, rx) = oneshot::channel();
 
-        if self.tx.send(make_cmd(tx)).is_err() {
+        if !self.send(make_cmd(tx)) {
             self.dead.store(true, Ordering::Relaxed);
             log::error!("mpv thread is dead");
             return;
@@ -397,6 +759,32 @@ impl MpvHandle {
     }
 }
 
+/// An entry of mpv's `audio-device-list`
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AudioDevice {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+/// A single playlist entry for mpv along with the per-file options it should be played with
+#[derive(Debug, Clone)]
+pub struct MpvFile {
+    pub url: String,
+    pub gain: Option<f64>, // replaygain adjustment in dB
+    pub album_id: String,  // crossfades are skipped within an album
+}
+
+impl MpvFile {
+    /// Options for `loadfile`. They are file-local, so mpv restores the defaults for the next file
+    pub(crate) fn options(&self) -> String {
+        match self.gain {
+            Some(gain) => format!("volume-gain={:.2}", gain),
+            None => String::new(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
 // #[serde(rename_all = "kebab-case")]
 pub enum LoadFileFlag {
@@ -478,3 +866,19 @@ enum ResumeResult {
     Done,
     Abort,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loadfile_index_from_version() {
+        assert!(!loadfile_takes_index("mpv 0.37.0"));
+        assert!(!loadfile_takes_index("mpv v0.35.1-dirty"));
+        assert!(loadfile_takes_index("mpv 0.38.0"));
+        assert!(loadfile_takes_index("mpv v0.39.0-123-gabcdef0"));
+        assert!(loadfile_takes_index("mpv 1.0.0"));
+        assert!(loadfile_takes_index("mpv git-2024-05-01"));
+        assert!(loadfile_takes_index(""));
+    }
+}
@@ -1,5 +1,16 @@
+use crate::ab_loop::AbLoop;
+use crate::audio_output::configured_audio_device;
+use crate::config::Transitions;
 use crate::database::database::{Command, JellyfinCommand};
+use crate::mpv::{MpvHandle, MPV_MAX_RESTARTS};
 use crate::tui::App;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Playback speed limits, and how much the speed keys change it
+pub const SPEED_MIN: f64 = 0.5;
+pub const SPEED_MAX: f64 = 3.0;
+pub const SPEED_STEP: f64 = 0.1;
 
 impl App {
     pub async fn play(&mut self) {
@@ -29,6 +40,7 @@ impl App {
     }
 
     pub async fn stop(&mut self) {
+        self.remember_queue();
         self.stopped = true;
         self.paused = true;
         self.mpv_handle.stop().await;
@@ -46,18 +58,16 @@ impl App {
         self.mpv_handle.next().await;
         self.play().await;
         self.update_mpris_position(0.0);
-        if self.client.is_some() {
-            let _ = self
-                .db
-                .cmd_tx
-                .send(Command::Jellyfin(JellyfinCommand::Stopped {
-                    id: Some(self.active_song_id.clone()),
-                    position_ticks: Some(
-                        self.state.current_playback_state.position as u64 * 10_000_000,
-                    ),
-                }))
-                .await;
-        }
+        let _ = self
+            .db
+            .cmd_tx
+            .send(Command::Jellyfin(JellyfinCommand::Stopped {
+                id: Some(self.active_song_id.clone()),
+                position_ticks: Some(
+                    self.state.current_playback_state.position as u64 * 10_000_000,
+                ),
+            }))
+            .await;
     }
 
     pub async fn previous(&mut self) {
@@ -68,4 +78,117 @@ impl App {
         self.mpv_handle.previous(self.state.current_playback_state.position).await;
         self.update_mpris_position(0.0);
     }
+
+    /// Whether crossfades and fades are on, the global popup toggle wins over the config
+    ///
+    pub fn transitions_on(&self) -> bool {
+        self.preferences.transitions.unwrap_or(self.transitions_enabled)
+    }
+
+    pub async fn apply_transitions(&self) {
+        let transitions =
+            if self.transitions_on() { self.transitions } else { Transitions::default() };
+        self.mpv_handle.set_transitions(transitions).await;
+    }
+
+    /// Sets the playback speed, kept in the state so it survives a restart
+    ///
+    pub async fn set_speed(&mut self, speed: f64) {
+        // rounded, so repeated steps don't drift to 1.0000000002
+        let speed = ((speed * 10.0).round() / 10.0).clamp(SPEED_MIN, SPEED_MAX);
+        if speed == self.state.current_playback_state.speed {
+            return;
+        }
+        self.state.current_playback_state.speed = speed;
+        self.apply_speed().await;
+        // jellyfin shows the rate too
+        let _ = self.report_progress_if_needed(true).await;
+        self.dirty = true;
+    }
+
+    pub async fn apply_speed(&self) {
+        let speed = self.state.current_playback_state.speed.clamp(SPEED_MIN, SPEED_MAX);
+        self.mpv_handle.set_speed(speed, self.preferences.pitch_correction).await;
+    }
+
+    /// Recreates mpv if its thread died and puts the queue back where it was
+    ///
+    pub async fn supervise_mpv(&mut self) {
+        if !self.mpv_handle.is_dead() {
+            // it stayed up for a while, so a later crash gets the full number of attempts again
+            if self.mpv_last_restart.is_some_and(|t| t.elapsed() > Duration::from_secs(60)) {
+                self.mpv_restart_attempts = 0;
+            }
+            return;
+        }
+        if self.mpv_restart_attempts >= MPV_MAX_RESTARTS {
+            return;
+        }
+        // give it a moment between attempts, libmpv failing to start usually fails again right away
+        if self.mpv_last_restart.is_some_and(|t| t.elapsed() < Duration::from_secs(5)) {
+            return;
+        }
+        self.mpv_restart_attempts += 1;
+        self.mpv_last_restart = Some(Instant::now());
+        self.dirty = true;
+        log::warn!(
+            "mpv died, restarting it (attempt {}/{})",
+            self.mpv_restart_attempts,
+            MPV_MAX_RESTARTS
+        );
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        match MpvHandle::try_new(&self.config, sender) {
+            Ok(handle) => {
+                self.mpv_handle = handle;
+                self.receiver = receiver;
+                // the new mpv has none of the old filters and starts on the configured device
+                self.sleep_fade = None;
+                self.ab_loop = AbLoop::default();
+                self.audio_device = configured_audio_device(&self.config);
+            }
+            Err(e) => {
+                log::error!("Failed to restart mpv: {}", e);
+                return;
+            }
+        }
+
+        let was_paused = self.paused;
+        let index = self.state.current_playback_state.current_index;
+        let position = self.state.current_playback_state.position;
+
+        self.mpv_handle.set_volume(self.state.current_playback_state.volume).await;
+        self.mpv_handle.set_repeat(self.preferences.repeat).await;
+        self.restore_audio_device().await;
+        self.apply_equalizer().await;
+        self.apply_transitions().await;
+        self.apply_speed().await;
+        if self.stopped || self.state.queue.is_empty() {
+            return;
+        }
+
+        let index = match self.start_new_queue(index).await {
+            Ok(index) => index,
+            Err(e) => {
+                log::error!("Failed to reload the queue after restarting mpv: {}", e);
+                return;
+            }
+        };
+        self.mpv_handle.play_index(index).await;
+        if was_paused {
+            self.pause().await;
+        }
+
+        if let Some(song) = self.state.queue.get(index) {
+            if position > 0.1 {
+                self.hard_seek_target = Some(position);
+                self.mpv_handle.hard_seek(position, song.url.clone()).await;
+                // the pending resume only unpauses if asked to
+                if !was_paused {
+                    self.mpv_handle.play().await;
+                }
+                self.buffering = true;
+            }
+        }
+    }
 }
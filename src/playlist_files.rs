@@ -0,0 +1,777 @@
+/* --------------------------
+Playlist files
+    - Any playlist or the queue can be exported to M3U8 or XSPF, pointing either at stream urls (these
+      carry the access token) or at the downloaded files
+    - M3U, M3U8 and XSPF files can be imported. Entries are matched against the cached library, first
+      by a Jellyfin id in the path (which is how our own exports come back), then by title, artist,
+      album and duration
+    - The matches are listed for review before a Jellyfin playlist is created from them
+    - Files go to and are read from `playlist_dir` in the config, relative paths included
+-------------------------- */
+
+use crate::client::DiscographySong;
+use crate::database::database::{Command, UpdateCommand};
+use crate::database::extension::{
+    get_library_tracks, get_playlist_tracks, get_smart_playlist_tracks,
+};
+use crate::queue::make_track;
+use crate::tui::{App, Song};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+/// Fuzzy matches scoring below this are left out
+const MIN_SCORE: u8 = 70;
+/// From here on a match is shown as certain
+const SURE_SCORE: u8 = 90;
+
+static JELLYFIN_ID_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)[0-9a-f]{32}").unwrap());
+static TRACK_NUMBER_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*\d{1,3}([-.]\d{1,3})?\s*[-._)]?\s+").unwrap());
+static BRACKETS_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"[(\[][^)\]]*[)\]]").unwrap());
+static XSPF_TRACK_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<track>(.*?)</track>").unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum PlaylistFormat {
+    #[default]
+    M3u8,
+    Xspf,
+}
+
+impl PlaylistFormat {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PlaylistFormat::M3u8 => "M3U8",
+            PlaylistFormat::Xspf => "XSPF",
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            PlaylistFormat::M3u8 => "m3u8",
+            PlaylistFormat::Xspf => "xspf",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ExportSource {
+    Playlist { id: String },
+    Queue,
+}
+
+/// One entry of a playlist file, whatever the format knows about it
+///
+#[derive(Debug, Clone, Default)]
+struct FileEntry {
+    location: String,
+    title: String,
+    artist: String,
+    album: String,
+    duration_secs: Option<u64>,
+}
+
+impl FileEntry {
+    fn label(&self) -> String {
+        match (self.artist.is_empty(), self.title.is_empty()) {
+            (false, false) => format!("{} - {}", self.artist, self.title),
+            (true, false) => self.title.clone(),
+            _ => self.location.clone(),
+        }
+    }
+}
+
+/// How an entry of an imported file was matched, shown in the import report
+///
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportMatch {
+    pub entry: String,
+    pub track_id: Option<String>,
+    /// the matched track as "Artist - Title"
+    pub track: String,
+    /// 100 for a match by id, otherwise how close the metadata is
+    pub score: u8,
+    pub include: bool,
+}
+
+impl ImportMatch {
+    pub fn label(&self) -> String {
+        match &self.track_id {
+            None => format!("✗ {}", self.entry),
+            Some(_) if !self.include => format!("  {} (skipped)", self.entry),
+            Some(_) if self.score >= SURE_SCORE => format!("✓ {}", self.entry),
+            Some(_) => format!("~ {} → {} ({}%)", self.entry, self.track, self.score),
+        }
+    }
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn unescape_xml(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s.trim();
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find(';') else {
+            break;
+        };
+        let entity = &rest[start + 1..start + end];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(|dec| dec.parse::<u32>()))
+                .and_then(|n| n.ok())
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(c) => out.push(c),
+            None => out.push_str(&rest[start..=start + end]),
+        }
+        rest = &rest[start + end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn xml_field(body: &str, tag: &str) -> String {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    body.find(&open)
+        .and_then(|start| {
+            let value = &body[start + open.len()..];
+            value.find(&close).map(|end| unescape_xml(&value[..end]))
+        })
+        .unwrap_or_default()
+}
+
+fn write_m3u8(name: &str, tracks: &[FileEntry]) -> String {
+    let mut out = format!("#EXTM3U\n#PLAYLIST:{}\n", name);
+    for track in tracks {
+        out += &format!(
+            "#EXTINF:{},{} - {}\n",
+            track.duration_secs.unwrap_or(0),
+            track.artist,
+            track.title
+        );
+        if !track.album.is_empty() {
+            out += &format!("#EXTALB:{}\n", track.album);
+        }
+        out += &format!("{}\n", track.location);
+    }
+    out
+}
+
+fn write_xspf(name: &str, tracks: &[FileEntry]) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out += "<playlist version=\"1\" xmlns=\"http://xspf.org/ns/0/\">\n";
+    out += &format!("  <title>{}</title>\n  <trackList>\n", escape_xml(name));
+    for track in tracks {
+        let location = if track.location.starts_with("http") {
+            track.location.clone()
+        } else {
+            url::Url::from_file_path(&track.location)
+                .map_or(track.location.clone(), |u| u.to_string())
+        };
+        out += "    <track>\n";
+        out += &format!("      <location>{}</location>\n", escape_xml(&location));
+        out += &format!("      <title>{}</title>\n", escape_xml(&track.title));
+        out += &format!("      <creator>{}</creator>\n", escape_xml(&track.artist));
+        out += &format!("      <album>{}</album>\n", escape_xml(&track.album));
+        if let Some(secs) = track.duration_secs {
+            out += &format!("      <duration>{}</duration>\n", secs * 1000);
+        }
+        out += "    </track>\n";
+    }
+    out += "  </trackList>\n</playlist>\n";
+    out
+}
+
+/// Reads the name (if the file has one) and the entries of an M3U or M3U8 file
+///
+fn read_m3u(content: &str) -> (Option<String>, Vec<FileEntry>) {
+    let mut name = None;
+    let mut entries = vec![];
+    let mut pending = FileEntry::default();
+    for line in content.trim_start_matches('\u{feff}').lines().map(str::trim) {
+        if let Some(info) = line.strip_prefix("#EXTINF:") {
+            // #EXTINF:<seconds>,<artist> - <title>
+            let (duration, label) = info.split_once(',').unwrap_or((info, ""));
+            pending.duration_secs =
+                duration.trim().parse::<i64>().ok().filter(|d| *d > 0).map(|d| d as u64);
+            match label.split_once(" - ") {
+                Some((artist, title)) => {
+                    pending.artist = artist.trim().to_string();
+                    pending.title = title.trim().to_string();
+                }
+                None => pending.title = label.trim().to_string(),
+            }
+        } else if let Some(album) = line.strip_prefix("#EXTALB:") {
+            pending.album = album.trim().to_string();
+        } else if let Some(artist) = line.strip_prefix("#EXTART:") {
+            pending.artist = artist.trim().to_string();
+        } else if let Some(playlist) = line.strip_prefix("#PLAYLIST:") {
+            name = Some(playlist.trim().to_string());
+        } else if !line.is_empty() && !line.starts_with('#') {
+            pending.location = line.to_string();
+            entries.push(std::mem::take(&mut pending));
+        }
+    }
+    (name, entries)
+}
+
+fn read_xspf(content: &str) -> (Option<String>, Vec<FileEntry>) {
+    let head = content.split("<trackList>").next().unwrap_or("");
+    let name = Some(xml_field(head, "title")).filter(|n| !n.is_empty());
+    let entries = XSPF_TRACK_RE
+        .captures_iter(content)
+        .map(|c| {
+            let body = c.get(1).map_or("", |m| m.as_str());
+            let location = xml_field(body, "location");
+            FileEntry {
+                location: url::Url::parse(&location)
+                    .ok()
+                    .filter(|u| u.scheme() == "file")
+                    .and_then(|u| u.to_file_path().ok())
+                    .map_or(location, |p| p.to_string_lossy().to_string()),
+                title: xml_field(body, "title"),
+                artist: xml_field(body, "creator"),
+                album: xml_field(body, "album"),
+                duration_secs: xml_field(body, "duration").parse::<u64>().ok().map(|ms| ms / 1000),
+            }
+        })
+        .collect();
+    (name, entries)
+}
+
+/// Files without tags are usually laid out as Artist/Album/01 - Title.ext
+///
+fn guess_from_location(entry: &mut FileEntry) {
+    if !entry.title.is_empty() || entry.location.starts_with("http") {
+        return;
+    }
+    let location = entry.location.replace('\\', "/");
+    let path = Path::new(&location);
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let stem = TRACK_NUMBER_RE.replace(&stem, "").to_string();
+    match stem.split_once(" - ") {
+        Some((artist, title)) => {
+            entry.title = title.trim().to_string();
+            if entry.artist.is_empty() {
+                entry.artist = artist.trim().to_string();
+            }
+        }
+        None => entry.title = stem.trim().to_string(),
+    }
+    let mut parents = path.ancestors().skip(1).filter_map(|p| p.file_name());
+    if let Some(album) = parents.next() {
+        if entry.album.is_empty() {
+            entry.album = album.to_string_lossy().to_string();
+        }
+    }
+    if let Some(artist) = parents.next() {
+        if entry.artist.is_empty() {
+            entry.artist = artist.to_string_lossy().to_string();
+        }
+    }
+}
+
+/// Lowercase words without punctuation or bracketed extras like "(Remastered 2011)"
+///
+fn normalize(s: &str) -> String {
+    BRACKETS_RE
+        .replace_all(&s.to_lowercase(), " ")
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/// 1.0 for equal strings, going down with the edit distance
+///
+fn similarity(a: &str, b: &str) -> f64 {
+    if a == b {
+        return 1.0;
+    }
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == cb { 0 } else { 1 };
+            current[j + 1] = (previous[j] + cost).min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    1.0 - previous[b.len()] as f64 / a.len().max(b.len()) as f64
+}
+
+/// Weighted closeness of an entry and a track in 0..=100. Fields the entry doesn't have don't count
+///
+fn score(title: &str, artist: &str, album: &str, entry: &FileEntry, track: &DiscographySong) -> u8 {
+    let mut total = 0.5 * similarity(title, &normalize(&track.name));
+    let mut weight = 0.5;
+    if !artist.is_empty() {
+        let best = std::iter::once(&track.album_artist)
+            .chain(track.artists.iter())
+            .map(|a| similarity(artist, &normalize(a)))
+            .fold(0.0, f64::max);
+        total += 0.3 * best;
+        weight += 0.3;
+    }
+    if !album.is_empty() {
+        total += 0.1 * similarity(album, &normalize(&track.album));
+        weight += 0.1;
+    }
+    if let Some(secs) = entry.duration_secs {
+        let difference = secs.abs_diff(track.run_time_ticks / 10_000_000);
+        total += match difference {
+            0..=3 => 0.1,
+            4..=10 => 0.05,
+            _ => 0.0,
+        };
+        weight += 0.1;
+    }
+    (total / weight * 100.0).round() as u8
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().map_or(PathBuf::from(path), |home| home.join(rest)),
+        None => PathBuf::from(path),
+    }
+}
+
+fn match_entries(entries: Vec<FileEntry>, library: &[DiscographySong]) -> Vec<ImportMatch> {
+    let by_id: HashMap<&str, &DiscographySong> =
+        library.iter().map(|t| (t.id.as_str(), t)).collect();
+    // only tracks sharing a word of the title are scored
+    let mut by_word: HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, track) in library.iter().enumerate() {
+        for word in normalize(&track.name).split(' ').filter(|w| !w.is_empty()) {
+            by_word.entry(word.to_string()).or_default().push(i);
+        }
+    }
+    let display = |track: &DiscographySong| format!("{} - {}", track.album_artist, track.name);
+
+    entries
+        .into_iter()
+        .map(|mut entry| {
+            // the last id in the path is the track, the ones before are the server and the album
+            let by_path = JELLYFIN_ID_RE
+                .find_iter(&entry.location)
+                .collect::<Vec<_>>()
+                .into_iter()
+                .rev()
+                .find_map(|m| by_id.get(m.as_str().to_lowercase().as_str()).copied());
+            if let Some(track) = by_path {
+                return ImportMatch {
+                    entry: entry.label(),
+                    track_id: Some(track.id.clone()),
+                    track: display(track),
+                    score: 100,
+                    include: true,
+                };
+            }
+
+            guess_from_location(&mut entry);
+            let (title, artist, album) =
+                (normalize(&entry.title), normalize(&entry.artist), normalize(&entry.album));
+            let candidates = title
+                .split(' ')
+                .filter(|w| !w.is_empty())
+                .filter_map(|w| by_word.get(w))
+                .flatten()
+                .copied()
+                .collect::<HashSet<usize>>();
+            let best = candidates
+                .into_iter()
+                .map(|i| (score(&title, &artist, &album, &entry, &library[i]), &library[i]))
+                .max_by_key(|(score, _)| *score)
+                .filter(|(score, _)| *score >= MIN_SCORE);
+
+            ImportMatch {
+                entry: entry.label(),
+                track_id: best.map(|(_, t)| t.id.clone()),
+                track: best.map(|(_, t)| display(t)).unwrap_or_default(),
+                score: best.map_or(0, |(score, _)| score),
+                include: best.is_some(),
+            }
+        })
+        .collect()
+}
+
+impl App {
+    /// Where playlist files are exported to and imported from, unless given an absolute path
+    ///
+    pub fn playlist_dir(&self) -> PathBuf {
+        match self.config.get("playlist_dir").and_then(|d| d.as_str()) {
+            Some(dir) => expand_home(dir),
+            None => dirs::data_dir().unwrap().join("jellyfin-tui").join("playlists"),
+        }
+    }
+
+    fn resolve_playlist_path(&self, input: &str) -> PathBuf {
+        let path = expand_home(input.trim().trim_matches(|c| c == '"' || c == '\''));
+        if path.is_absolute() {
+            path
+        } else {
+            self.playlist_dir().join(path)
+        }
+    }
+
+    /// Writes a playlist or the queue to a file in `playlist_dir`.
+    /// Returns the file and how many tracks were written and left out (not downloaded, or offline)
+    ///
+    pub async fn export_playlist_file(
+        &self,
+        name: &str,
+        source: &ExportSource,
+        format: PlaylistFormat,
+        local_paths: bool,
+    ) -> Result<(PathBuf, usize, usize), Box<dyn std::error::Error>> {
+        let songs: Vec<Song> = match source {
+            ExportSource::Queue => self.state.queue.clone(),
+            ExportSource::Playlist { id } => {
+                let tracks = match self.smart_playlist_rules.iter().find(|r| r.id() == *id) {
+                    Some(rules) => {
+                        get_smart_playlist_tracks(&self.db.pool, rules, self.client.is_some())
+                            .await?
+                    }
+                    None => get_playlist_tracks(&self.db.pool, id, self.client.as_ref()).await?,
+                };
+                tracks
+                    .iter()
+                    .map(|track| {
+                        make_track(
+                            self.client.as_ref(),
+                            &self.downloads_dir,
+                            track,
+                            false,
+                            &self.transcoding,
+                        )
+                    })
+                    .collect()
+            }
+        };
+
+        let total = songs.len();
+        let entries = songs
+            .into_iter()
+            .filter_map(|song| {
+                let location = match (local_paths, &self.client) {
+                    (true, _) if !song.url.starts_with("http") => song.url.clone(),
+                    (false, Some(client)) => client.song_url_sync(&song.id, &self.transcoding),
+                    _ => return None,
+                };
+                Some(FileEntry {
+                    location,
+                    title: song.name,
+                    artist: song.artist,
+                    album: song.album,
+                    duration_secs: Some(song.run_time_ticks / 10_000_000),
+                })
+            })
+            .collect::<Vec<FileEntry>>();
+
+        let file_name = name
+            .chars()
+            .map(|c| if "/\\:*?\"<>|".contains(c) { '_' } else { c })
+            .collect::<String>();
+        let file_name =
+            if file_name.trim().is_empty() { "playlist".to_string() } else { file_name };
+        let dir = self.playlist_dir();
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("{}.{}", file_name.trim(), format.extension()));
+        let content = match format {
+            PlaylistFormat::M3u8 => write_m3u8(name, &entries),
+            PlaylistFormat::Xspf => write_xspf(name, &entries),
+        };
+        std::fs::write(&path, content)?;
+
+        Ok((path, entries.len(), total - entries.len()))
+    }
+
+    /// Reads a playlist file and matches its entries against the library.
+    /// Returns the name for the new playlist and one match per entry
+    ///
+    pub async fn read_playlist_file(
+        &self,
+        input: &str,
+    ) -> Result<(String, Vec<ImportMatch>), Box<dyn std::error::Error>> {
+        let path = self.resolve_playlist_path(input);
+        // older m3u files aren't always utf-8
+        let content = String::from_utf8_lossy(&std::fs::read(&path)?).to_string();
+        let is_xspf = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("xspf"))
+            || content.trim_start().starts_with("<?xml");
+        let (name, entries) = if is_xspf { read_xspf(&content) } else { read_m3u(&content) };
+        let name = name.unwrap_or_else(|| {
+            path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default()
+        });
+
+        let library = get_library_tracks(&self.db.pool).await?;
+        Ok((name, match_entries(entries, &library)))
+    }
+
+    /// Creates a private Jellyfin playlist from the included matches of an import
+    ///
+    pub async fn create_imported_playlist(
+        &self,
+        name: &str,
+        matches: &[ImportMatch],
+    ) -> Result<usize, reqwest::Error> {
+        let Some(client) = self.client.as_ref() else {
+            return Ok(0);
+        };
+        let ids = matches
+            .iter()
+            .filter(|m| m.include)
+            .filter_map(|m| m.track_id.clone())
+            .collect::<Vec<String>>();
+        let playlist_id = client.create_playlist(&name.to_string(), false).await?;
+        client.add_to_playlist_chunked(&ids, &playlist_id).await?;
+        let _ = self.db.cmd_tx.send(Command::Update(UpdateCommand::Library)).await;
+        Ok(ids.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(id: &str, name: &str, artist: &str, album: &str, secs: u64) -> DiscographySong {
+        serde_json::from_value(serde_json::json!({
+            "Id": id,
+            "Name": name,
+            "AlbumArtist": artist,
+            "Artists": [artist],
+            "Album": album,
+            "RunTimeTicks": secs * 10_000_000,
+        }))
+        .unwrap()
+    }
+
+    fn entry(title: &str) -> FileEntry {
+        FileEntry {
+            location: String::from("somewhere.flac"),
+            title: title.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn fields(entry: &FileEntry) -> (&str, &str, &str, &str, Option<u64>) {
+        (&entry.location, &entry.title, &entry.artist, &entry.album, entry.duration_secs)
+    }
+
+    #[test]
+    fn reads_extended_m3u() {
+        let content = "#EXTM3U\n#PLAYLIST:Road trip\n#EXTINF:354,Queen - Bohemian Rhapsody\n#EXTALB:A Night at the Opera\n/music/Queen/01 - Bohemian Rhapsody.flac\n\n#EXTINF:-1,Untitled\nhttp://radio.example/stream\n";
+        let (name, entries) = read_m3u(content);
+        assert_eq!(name.as_deref(), Some("Road trip"));
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            fields(&entries[0]),
+            (
+                "/music/Queen/01 - Bohemian Rhapsody.flac",
+                "Bohemian Rhapsody",
+                "Queen",
+                "A Night at the Opera",
+                Some(354)
+            )
+        );
+        // unknown lengths are written as -1, and a label without " - " is only a title
+        assert_eq!(fields(&entries[1]), ("http://radio.example/stream", "Untitled", "", "", None));
+    }
+
+    #[test]
+    fn reads_plain_m3u() {
+        let content = "# made by hand\n/music/a.mp3\r\n\r\n../b.ogg\n";
+        let (name, entries) = read_m3u(content);
+        assert_eq!(name, None);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(fields(&entries[0]), ("/music/a.mp3", "", "", "", None));
+        assert_eq!(fields(&entries[1]), ("../b.ogg", "", "", "", None));
+    }
+
+    #[test]
+    fn m3u_byte_order_mark_is_ignored() {
+        let (_, entries) = read_m3u("\u{feff}#EXTINF:200,Björk - Jóga\n/music/joga.flac\n");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(fields(&entries[0]), ("/music/joga.flac", "Jóga", "Björk", "", Some(200)));
+
+        // a BOM in front of a bare path isn't part of it either
+        let (_, entries) = read_m3u("\u{feff}/music/joga.flac\n");
+        assert_eq!(entries[0].location, "/music/joga.flac");
+    }
+
+    #[test]
+    fn reads_xspf_with_entities_and_file_locations() {
+        let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<playlist version="1" xmlns="http://xspf.org/ns/0/">
+  <title>Rock &amp; Roll</title>
+  <trackList>
+    <track>
+      <location>file:///music/Simon%20%26%20Garfunkel/01%20-%20The%20Boxer.flac</location>
+      <title>The Boxer</title>
+      <creator>Simon &amp; Garfunkel</creator>
+      <album>Bridge over Troubled Water</album>
+      <duration>308000</duration>
+    </track>
+    <track>
+      <location>http://server/Audio/1/universal?a=1&amp;b=2</location>
+      <title>Caf&#233; &#x27;n&#39; &lt;Bar&gt; &unknown;</title>
+    </track>
+  </trackList>
+</playlist>
+"#;
+        let (name, entries) = read_xspf(content);
+        assert_eq!(name.as_deref(), Some("Rock & Roll"));
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            fields(&entries[0]),
+            (
+                "/music/Simon & Garfunkel/01 - The Boxer.flac",
+                "The Boxer",
+                "Simon & Garfunkel",
+                "Bridge over Troubled Water",
+                Some(308)
+            )
+        );
+        assert_eq!(entries[1].location, "http://server/Audio/1/universal?a=1&b=2");
+        assert_eq!(entries[1].title, "Café 'n' <Bar> &unknown;");
+        assert_eq!(entries[1].duration_secs, None);
+    }
+
+    #[test]
+    fn m3u8_round_trip() {
+        let tracks = vec![
+            FileEntry {
+                location: String::from("/music/Queen/01 - Bohemian Rhapsody.flac"),
+                title: String::from("Bohemian Rhapsody"),
+                artist: String::from("Queen"),
+                album: String::from("A Night at the Opera"),
+                duration_secs: Some(354),
+            },
+            FileEntry {
+                location: String::from("http://server/Audio/1/universal?api_key=x"),
+                title: String::from("Jóga"),
+                artist: String::from("Björk"),
+                album: String::new(),
+                duration_secs: Some(305),
+            },
+        ];
+        let (name, entries) = read_m3u(&write_m3u8("Mix", &tracks));
+        assert_eq!(name.as_deref(), Some("Mix"));
+        assert_eq!(
+            entries.iter().map(fields).collect::<Vec<_>>(),
+            tracks.iter().map(fields).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn xspf_round_trip() {
+        let tracks = vec![FileEntry {
+            location: String::from("/music/Simon & Garfunkel/01 - The <Boxer>.flac"),
+            title: String::from("The \"Boxer\""),
+            artist: String::from("Simon & Garfunkel"),
+            album: String::from("Bridge over Troubled Water"),
+            duration_secs: Some(308),
+        }];
+        let (name, entries) = read_xspf(&write_xspf("Rock & 'Roll'", &tracks));
+        assert_eq!(name.as_deref(), Some("Rock & 'Roll'"));
+        assert_eq!(
+            entries.iter().map(fields).collect::<Vec<_>>(),
+            tracks.iter().map(fields).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn matches_by_the_last_known_id_in_the_path() {
+        let server = "0123456789abcdef0123456789abcdef";
+        let id = "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+        let library = vec![track(id, "Bohemian Rhapsody", "Queen", "A Night at the Opera", 354)];
+        let entries = vec![
+            // our own exports, the title doesn't have to agree
+            FileEntry {
+                location: format!("/downloads/{}/{}/{}.flac", server, "b".repeat(32), id),
+                title: String::from("Something else"),
+                ..Default::default()
+            },
+            FileEntry {
+                location: format!("http://server/Audio/{}/universal", id.to_uppercase()),
+                ..Default::default()
+            },
+        ];
+        let matches = match_entries(entries, &library);
+        for m in &matches {
+            assert_eq!(m.track_id.as_deref(), Some(id));
+            assert_eq!(m.score, 100);
+            assert!(m.include);
+        }
+    }
+
+    #[test]
+    fn matches_by_metadata_and_path() {
+        let library = vec![
+            track("1", "Bohemian Rhapsody (Remastered 2011)", "Queen", "A Night at the Opera", 354),
+            track("2", "Bohemian Like You", "The Dandy Warhols", "Thirteen Tales", 211),
+        ];
+        let entries = vec![
+            FileEntry {
+                duration_secs: Some(355),
+                artist: String::from("Queen"),
+                ..entry("Bohemian Rhapsody")
+            },
+            // no tags at all, everything comes from Artist/Album/01 - Title
+            FileEntry {
+                location: String::from(
+                    "/music/The Dandy Warhols/Thirteen Tales/03 - Bohemian Like You.mp3",
+                ),
+                ..Default::default()
+            },
+        ];
+        let matches = match_entries(entries, &library);
+        assert_eq!(matches[0].track_id.as_deref(), Some("1"));
+        assert!(matches[0].score >= SURE_SCORE);
+        assert_eq!(matches[1].track_id.as_deref(), Some("2"));
+        assert_eq!(matches[1].entry, "The Dandy Warhols - Bohemian Like You");
+    }
+
+    #[test]
+    fn fuzzy_matches_below_min_score_are_left_out() {
+        let library = vec![track("1", "Dancing Queen", "ABBA", "Arrival", 231)];
+        // 4 of 13 characters off
+        let below = entry("Dancing Qxxxx");
+        let (title, artist, album) = (normalize(&below.title), String::new(), String::new());
+        assert_eq!(score(&title, &artist, &album, &below, &library[0]), MIN_SCORE - 1);
+        // 3 of 13
+        let above = entry("Dancing Quxxx");
+
+        let matches = match_entries(vec![below, above], &library);
+        assert_eq!(matches[0].track_id, None);
+        assert_eq!(matches[0].score, 0);
+        assert!(!matches[0].include);
+        assert_eq!(matches[1].track_id.as_deref(), Some("1"));
+        assert!(matches[1].score >= MIN_SCORE && matches[1].score < SURE_SCORE);
+        assert!(matches[1].include);
+    }
+}
@@ -3,6 +3,8 @@ The playlists tab is rendered here.
 -------------------------- */
 
 use crate::keyboard::*;
+use crate::selection::SelectionList;
+use crate::smart_playlists::is_smart_playlist;
 use crate::tui::App;
 use crate::{database::extension::DownloadStatus, helpers};
 
@@ -129,6 +131,14 @@ impl App {
             .constraints(lyrics_slot_constraints)
             .split(outer_layout[2]);
 
+        self.layout_areas.columns = [outer_layout[0], outer_layout[1], outer_layout[2]];
+        self.layout_areas.list = left[0];
+        self.layout_areas.tracks = center[0];
+        self.layout_areas.queue = right[1];
+        if show_panel {
+            self.layout_areas.lyrics = right[0];
+        }
+
         let playlist_block = match self.state.active_section {
             ActiveSection::List => Block::new()
                 .borders(Borders::ALL)
@@ -189,6 +199,12 @@ impl App {
                         Style::default().fg(self.theme.primary_color),
                     ));
                 }
+                if is_smart_playlist(&playlist.id) {
+                    item.push_span(Span::styled(
+                        "✦ ",
+                        Style::default().fg(self.theme.resolve(&self.theme.foreground_dim)),
+                    ));
+                }
 
                 let all_subsequences = crate::helpers::find_all_subsequences(
                     &self.state.playlists_search_term.to_lowercase(),
@@ -304,6 +320,7 @@ impl App {
 
         let terminal_height = frame.area().height as usize;
         let selection = self.state.selected_playlist_track.selected().unwrap_or(0);
+        let marked = self.marked_rows(SelectionList::PlaylistTracks);
 
         // dynamic pageup/down height calc
         let table_block_inner = track_block.inner(center[0]);
@@ -408,7 +425,9 @@ impl App {
                     hours_optional_text, minutes, seconds
                 )));
 
-                Row::new(cells).style(if track.id == self.active_song_id {
+                Row::new(cells).style(if marked.contains(&i) {
+                    self.marked_style()
+                } else if track.id == self.active_song_id {
                     Style::default().fg(self.theme.primary_color).italic()
                 } else if track.disliked {
                     Style::default().fg(self.theme.resolve(&self.theme.foreground_dim))
@@ -426,6 +445,10 @@ impl App {
                 " Cancel ".fg(self.theme.resolve(&self.theme.section_title)),
                 "<ESC> ".fg(self.theme.primary_color).bold(),
             ])
+        } else if let Some(instructions) =
+            self.selection_instructions(SelectionList::PlaylistTracks)
+        {
+            instructions
         } else {
             Line::from(vec![
                 " Help ".fg(self.theme.resolve(&self.theme.section_title)),
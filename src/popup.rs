@@ -25,13 +25,21 @@ use crate::database::database::{
     UpdateCommand,
 };
 use crate::database::extension::{get_album_tracks, set_selected_libraries, DownloadStatus};
+use crate::equalizer::{EqSettings, EQ_BANDS, EQ_CUSTOM, EQ_MAX_GAIN};
 use crate::keyboard::{search_ranked_indices, search_ranked_refs, Searchable};
+use crate::mpv::AudioDevice;
+use crate::playlist_files::{ExportSource, ImportMatch, PlaylistFormat};
+use crate::saved_queues::SavedQueue;
+use crate::selection::SelectionList;
+use crate::sleep_timer::SleepTimer;
+use crate::smart_playlists::is_smart_playlist;
+use crate::smart_shuffle::ShuffleMode;
 use crate::themes::theme::Theme;
 use crate::{
     client::{Artist, Playlist, ScheduledTask},
     helpers,
     keyboard::{ActiveSection, ActiveTab, Selectable},
-    tui::{Filter, Sort},
+    tui::{Filter, Song, Sort},
 };
 
 /// helper function to create a centered rect using up certain percentage of the available rect `r`
@@ -56,6 +64,22 @@ pub enum PopupMenu {
     GlobalRoot {
         large_art: bool,
         downloading: bool,
+        #[serde(default)]
+        scrobbles_pending: i64,
+        #[serde(default)]
+        scrobbles_replayed: usize,
+        #[serde(default)]
+        radio: Option<String>,
+        #[serde(default)]
+        crossfade: Option<f64>,
+        #[serde(default)]
+        sleep_timer: Option<String>,
+        #[serde(default)]
+        pitch_correction: bool,
+        #[serde(default)]
+        shuffle_mode: ShuffleMode,
+        #[serde(default)]
+        smart_shuffle_favorites: bool,
     },
     GlobalRunScheduledTask {
         tasks: Vec<ScheduledTask>,
@@ -71,15 +95,51 @@ pub enum PopupMenu {
     GlobalSetThemes {
         themes: Vec<crate::themes::theme::Theme>,
     },
+    GlobalSleepTimer {
+        active: Option<String>,
+        custom_minutes: u64,
+        tracks_n: usize,
+    },
+    GlobalAudioDevice {
+        devices: Vec<AudioDevice>,
+        current: String,
+        per_device_volume: bool,
+    },
+    GlobalSeekTo {
+        time: String,
+    },
+    GlobalEqualizer {
+        device: String,
+        settings: EqSettings,
+        presets: Vec<String>,
+    },
     GlobalSelectLibraries {
         libraries: Vec<LibraryView>,
     },
+    GlobalQueue {
+        tracks_n: usize,
+        saved: Vec<SavedQueue>,
+    },
+    GlobalQueueName {
+        name: String,
+        /// save as a Jellyfin playlist instead of locally
+        playlist: bool,
+    },
+    GlobalQueueToPlaylist {
+        playlists: Vec<Playlist>,
+    },
+    GlobalSavedQueue {
+        saved: SavedQueue,
+    },
     /**
      * Playlist related popups
      */
     PlaylistRoot {
         playlist_name: String,
     },
+    SmartPlaylistRoot {
+        playlist_name: String,
+    },
     PlaylistSetName {
         playlist_name: String,
         new_name: String,
@@ -96,6 +156,19 @@ pub enum PopupMenu {
     },
     PlaylistsChangeSort {},
     PlaylistsChangeFilter {},
+    PlaylistExport {
+        name: String,
+        source: ExportSource,
+        format: PlaylistFormat,
+        local_paths: bool,
+    },
+    PlaylistImport {
+        path: String,
+    },
+    PlaylistImportReport {
+        name: String,
+        matches: Vec<ImportMatch>,
+    },
     /**
      * Track related popups
      */
@@ -105,6 +178,10 @@ pub enum PopupMenu {
         parent_id: String,
         disliked: bool,
     },
+    TrackInfo {
+        track_name: String,
+        rows: Vec<(String, String)>,
+    },
     TrackAddToPlaylist {
         track_name: String,
         track_id: String,
@@ -158,6 +235,29 @@ pub enum PopupMenu {
         track_name: String,
         disliked: bool,
     },
+    /**
+     * Genre related popups
+     */
+    GenreRoot {
+        genre_name: String,
+    },
+    /**
+     * Tracks selected in visual mode
+     */
+    BulkRoot {
+        list: SelectionList,
+        count: usize,
+        favorite: bool,
+        disliked: bool,
+    },
+    BulkAddToPlaylist {
+        count: usize,
+        playlists: Vec<Playlist>,
+    },
+    BulkRemoveFromPlaylist {
+        count: usize,
+        playlist_name: String,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -213,6 +313,35 @@ pub enum Action {
     Custom,
     SetCustomTheme { theme: crate::themes::theme::Theme },
     Dislike,
+    ReplayScrobbles,
+    SaveAsPlaylist,
+    Shuffle,
+    StartRadio,
+    StopRadio,
+    Equalizer,
+    ToggleTransitions,
+    TogglePitchCorrection,
+    SeekTo,
+    SleepTimer,
+    AudioDevices,
+    SetAudioDevice { name: String },
+    SleepIn { minutes: u64 },
+    SleepInCustom { minutes: u64 },
+    SleepAfterTrack,
+    SleepAfterAlbum,
+    SleepAfterTracks { tracks: usize },
+    EqBand { band: usize },
+    SetEqPreset { name: String },
+    PlayNext,
+    Favorite,
+    SavedQueues,
+    SavedQueue { id: i64 },
+    Export,
+    Import,
+    ChangeShuffleMode,
+    TrackInfo,
+    ChangeFormat,
+    ImportEntry { index: usize },
 }
 
 #[derive(Clone, Debug)]
@@ -252,16 +381,44 @@ impl PopupMenu {
             PopupMenu::GlobalSetThemes { .. } => "Set Theme".to_string(),
             PopupMenu::GlobalPickTheme { .. } => "Pick variant".to_string(),
             PopupMenu::GlobalSelectLibraries { .. } => "Select Libraries".to_string(),
+            PopupMenu::GlobalSleepTimer { active, .. } => match active {
+                Some(label) => format!("Sleep timer ({})", label),
+                None => "Sleep timer".to_string(),
+            },
+            PopupMenu::GlobalAudioDevice { .. } => "Audio output".to_string(),
+            PopupMenu::GlobalSeekTo { .. } => "Seek to".to_string(),
+            PopupMenu::GlobalQueue { tracks_n, .. } => format!("Queue ({} tracks)", tracks_n),
+            PopupMenu::GlobalQueueName { playlist, .. } => {
+                if *playlist {
+                    "New playlist from the queue".to_string()
+                } else {
+                    "Save queue as".to_string()
+                }
+            }
+            PopupMenu::GlobalQueueToPlaylist { .. } => "Add the queue to".to_string(),
+            PopupMenu::GlobalSavedQueue { saved } => saved.name.to_string(),
+            PopupMenu::GlobalEqualizer { device, settings, .. } => {
+                format!("Equalizer: {} ({})", settings.preset, device)
+            }
             // ---------- Playlists ---------- //
             PopupMenu::PlaylistRoot { playlist_name, .. } => playlist_name.to_string(),
+            PopupMenu::SmartPlaylistRoot { playlist_name } => format!("{} (smart)", playlist_name),
             PopupMenu::PlaylistSetName { .. } => "Type to change name".to_string(),
             PopupMenu::PlaylistConfirmRename { .. } => "Confirm Rename".to_string(),
             PopupMenu::PlaylistConfirmDelete { .. } => "Confirm Delete".to_string(),
             PopupMenu::PlaylistCreate { .. } => "Create Playlist".to_string(),
             PopupMenu::PlaylistsChangeSort {} => "Change sort order".to_string(),
             PopupMenu::PlaylistsChangeFilter {} => "Change filter".to_string(),
+            PopupMenu::PlaylistExport { name, .. } => format!("Export {}", name),
+            PopupMenu::PlaylistImport { .. } => "Import a playlist file".to_string(),
+            PopupMenu::PlaylistImportReport { matches, .. } => format!(
+                "Import: {} of {} matched",
+                matches.iter().filter(|m| m.track_id.is_some()).count(),
+                matches.len()
+            ),
             // ---------- Tracks ---------- //
             PopupMenu::TrackRoot { track_name, .. } => track_name.to_string(),
+            PopupMenu::TrackInfo { track_name, .. } => track_name.to_string(),
             PopupMenu::TrackAddToPlaylist { track_name, .. } => track_name.to_string(),
             PopupMenu::TrackAlbumsChangeSort {} => "Change album order".to_string(),
             // ---------- Playlist tracks ---------- //
@@ -281,6 +438,12 @@ impl PopupMenu {
             PopupMenu::AlbumsChangeSort {} => "Change sort".to_string(),
             // ---------- Album tracks ---------- //
             PopupMenu::AlbumTrackRoot { track_name, .. } => track_name.to_string(),
+            // ---------- Genres ---------- //
+            PopupMenu::GenreRoot { genre_name } => genre_name.to_string(),
+            // ---------- Selected tracks ---------- //
+            PopupMenu::BulkRoot { count, .. } => format!("{} tracks selected", count),
+            PopupMenu::BulkAddToPlaylist { count, .. } => format!("Add {} tracks to", count),
+            PopupMenu::BulkRemoveFromPlaylist { playlist_name, .. } => playlist_name.to_string(),
         }
     }
 
@@ -292,79 +455,414 @@ impl PopupMenu {
                 PopupAction::new("Ok".to_string(), Action::Ok, Style::default(), false),
             ],
             // ---------- Global commands ---------- //
-            PopupMenu::GlobalRoot { large_art, downloading } => vec![
-                PopupAction::new(
-                    "Synchronize with Jellyfin (runs every 10 minutes)".to_string(),
-                    Action::Refresh,
-                    Style::default(),
-                    true,
-                ),
+            PopupMenu::GlobalRoot {
+                large_art,
+                downloading,
+                scrobbles_pending,
+                scrobbles_replayed,
+                radio,
+                crossfade,
+                sleep_timer,
+                pitch_correction,
+                shuffle_mode,
+                smart_shuffle_favorites,
+            } => {
+                let mut actions = vec![
+                    PopupAction::new(
+                        "Synchronize with Jellyfin (runs every 10 minutes)".to_string(),
+                        Action::Refresh,
+                        Style::default(),
+                        true,
+                    ),
+                    PopupAction::new(
+                        "Run a Jellyfin task".to_string(),
+                        Action::RunScheduledTasks,
+                        Style::default(),
+                        true,
+                    ),
+                    PopupAction::new(
+                        if *large_art {
+                            "Switch to small artwork".to_string()
+                        } else {
+                            "Switch to large artwork".to_string()
+                        },
+                        Action::ChangeCoverArtLayout,
+                        Style::default(),
+                        false,
+                    ),
+                    PopupAction::new(
+                        "Theme".to_string(),
+                        Action::GlobalSetTheme,
+                        Style::default(),
+                        false,
+                    ),
+                    PopupAction::new(
+                        match sleep_timer {
+                            Some(label) => format!("Sleep timer: {}", label),
+                            None => "Sleep timer".to_string(),
+                        },
+                        Action::SleepTimer,
+                        Style::default(),
+                        false,
+                    ),
+                    PopupAction::new(
+                        "Seek to a timestamp".to_string(),
+                        Action::SeekTo,
+                        Style::default(),
+                        false,
+                    ),
+                    PopupAction::new(
+                        "Save or restore the queue".to_string(),
+                        Action::SavedQueues,
+                        Style::default(),
+                        false,
+                    ),
+                    PopupAction::new(
+                        "Audio output device".to_string(),
+                        Action::AudioDevices,
+                        Style::default(),
+                        false,
+                    ),
+                    PopupAction::new(
+                        "Equalizer".to_string(),
+                        Action::Equalizer,
+                        Style::default(),
+                        false,
+                    ),
+                    PopupAction::new(
+                        match crossfade {
+                            Some(seconds) if *seconds > 0.0 => {
+                                format!("Crossfade and fades: on ({}s)", seconds)
+                            }
+                            Some(_) => "Crossfade: off, fades: on".to_string(),
+                            None => "Crossfade and fades: off".to_string(),
+                        },
+                        Action::ToggleTransitions,
+                        Style::default(),
+                        false,
+                    ),
+                    PopupAction::new(
+                        if *pitch_correction {
+                            "Pitch correction at other speeds: on".to_string()
+                        } else {
+                            "Pitch correction at other speeds: off".to_string()
+                        },
+                        Action::TogglePitchCorrection,
+                        Style::default(),
+                        false,
+                    ),
+                    PopupAction::new(
+                        match (shuffle_mode, smart_shuffle_favorites) {
+                            (ShuffleMode::Random, _) => "Shuffle: random".to_string(),
+                            (ShuffleMode::Smart, false) => {
+                                "Shuffle: smart (spreads artists, recent plays last)".to_string()
+                            }
+                            (ShuffleMode::Smart, true) => {
+                                "Shuffle: smart, favorites first".to_string()
+                            }
+                        },
+                        Action::ChangeShuffleMode,
+                        Style::default(),
+                        false,
+                    ),
+                    PopupAction::new(
+                        "Select music libraries".to_string(),
+                        Action::SelectLibraries,
+                        Style::default(),
+                        false,
+                    ),
+                    PopupAction::new(
+                        "Repair offline downloads (could take a minute)".to_string(),
+                        Action::OfflineRepair,
+                        Style::default(),
+                        false,
+                    ),
+                    PopupAction::new(
+                        "Stop downloading and abort queued".to_string(),
+                        Action::CancelDownloads,
+                        Style::default().fg(if *downloading {
+                            style::Color::Red
+                        } else {
+                            style::Color::DarkGray
+                        }),
+                        true,
+                    ),
+                    PopupAction::new(
+                        "Reset section widths".to_string(),
+                        Action::ResetSectionWidths,
+                        Style::default(),
+                        false,
+                    ),
+                    PopupAction::new(
+                        match (*scrobbles_pending, *scrobbles_replayed) {
+                            (0, 0) => "Offline scrobbles: none waiting".to_string(),
+                            (0, replayed) => {
+                                format!("Offline scrobbles: all sent ({} this session)", replayed)
+                            }
+                            (pending, _) => {
+                                format!("Offline scrobbles: {} waiting, send now", pending)
+                            }
+                        },
+                        Action::ReplayScrobbles,
+                        Style::default().fg(if *scrobbles_pending > 0 {
+                            style::Color::Yellow
+                        } else {
+                            style::Color::DarkGray
+                        }),
+                        false,
+                    ),
+                ];
+                if let Some(name) = radio {
+                    actions.insert(
+                        0,
+                        PopupAction::new(
+                            format!("Stop radio: {}", name),
+                            Action::StopRadio,
+                            Style::default(),
+                            false,
+                        ),
+                    );
+                }
+                actions
+            }
+            PopupMenu::GlobalRunScheduledTask { tasks } => {
+                let mut actions = vec![];
+                let mut categories =
+                    tasks.iter().map(|t| t.category.clone()).collect::<Vec<String>>();
+                categories.sort();
+                categories.dedup();
+                for category in categories {
+                    for task in tasks.iter().filter(|t| t.category == category) {
+                        actions.push(PopupAction::new(
+                            format!("{}: {} ({})", category, task.name, task.description),
+                            Action::RunScheduledTask { task: Some(task.clone()) },
+                            Style::default(),
+                            true,
+                        ));
+                    }
+                }
+                actions
+            }
+            PopupMenu::GlobalSleepTimer { active, custom_minutes, tracks_n } => {
+                let mut actions = vec![];
+                for minutes in [15, 30, 60] {
+                    actions.push(PopupAction::new(
+                        format!("Pause in {} minutes", minutes),
+                        Action::SleepIn { minutes },
+                        Style::default(),
+                        false,
+                    ));
+                }
+                actions.extend([
+                    PopupAction::new(
+                        format!("Pause in {} minutes. +/- to change", custom_minutes),
+                        Action::SleepInCustom { minutes: *custom_minutes },
+                        Style::default(),
+                        false,
+                    ),
+                    PopupAction::new(
+                        "Pause after this track".to_string(),
+                        Action::SleepAfterTrack,
+                        Style::default(),
+                        false,
+                    ),
+                    PopupAction::new(
+                        "Pause after this album".to_string(),
+                        Action::SleepAfterAlbum,
+                        Style::default(),
+                        false,
+                    ),
+                    PopupAction::new(
+                        format!("Pause after {} tracks. +/- to change", tracks_n),
+                        Action::SleepAfterTracks { tracks: *tracks_n },
+                        Style::default(),
+                        false,
+                    ),
+                ]);
+                if active.is_some() {
+                    actions.push(PopupAction::new(
+                        "Turn off the sleep timer".to_string(),
+                        Action::Cancel,
+                        Style::default().fg(style::Color::Red),
+                        false,
+                    ));
+                }
+                actions
+            }
+            PopupMenu::GlobalSeekTo { time } => vec![
                 PopupAction::new(
-                    "Run a Jellyfin task".to_string(),
-                    Action::RunScheduledTasks,
+                    if time.is_empty() {
+                        "Type a time, like 1:30".into()
+                    } else {
+                        format!("Time: {}", time)
+                    },
+                    Action::Type,
                     Style::default(),
-                    true,
+                    false,
                 ),
+                PopupAction::new("Seek".to_string(), Action::Confirm, Style::default(), false),
+                PopupAction::new("Cancel".to_string(), Action::Cancel, Style::default(), false),
+            ],
+            PopupMenu::GlobalQueue { tracks_n, saved } => {
+                let style = if *tracks_n == 0 {
+                    Style::default().fg(style::Color::DarkGray)
+                } else {
+                    Style::default()
+                };
+                let mut actions = vec![
+                    PopupAction::new(
+                        "Save as a new Jellyfin playlist".to_string(),
+                        Action::SaveAsPlaylist,
+                        style,
+                        true,
+                    ),
+                    PopupAction::new(
+                        "Add to a Jellyfin playlist".to_string(),
+                        Action::AddToPlaylist { playlist_id: String::new() },
+                        style,
+                        true,
+                    ),
+                    PopupAction::new("Save locally".to_string(), Action::Create, style, false),
+                    PopupAction::new("Export to a file".to_string(), Action::Export, style, false),
+                ];
+                for saved in saved {
+                    actions.push(PopupAction::new(
+                        format!("Saved: {}", saved.label()),
+                        Action::SavedQueue { id: saved.id },
+                        Style::default(),
+                        false,
+                    ));
+                }
+                actions
+            }
+            PopupMenu::GlobalQueueName { name, playlist } => vec![
                 PopupAction::new(
-                    if *large_art {
-                        "Switch to small artwork".to_string()
+                    if name.is_empty() {
+                        "Type in a name".into()
                     } else {
-                        "Switch to large artwork".to_string()
+                        format!("Name: {}", name)
                     },
-                    Action::ChangeCoverArtLayout,
+                    Action::Type,
                     Style::default(),
-                    false,
+                    *playlist,
                 ),
+                PopupAction::new("Save".to_string(), Action::Confirm, Style::default(), *playlist),
+                PopupAction::new("Cancel".to_string(), Action::Cancel, Style::default(), false),
+            ],
+            PopupMenu::GlobalQueueToPlaylist { playlists } => playlists
+                .iter()
+                .map(|playlist| {
+                    PopupAction::new(
+                        format!(
+                            "{}{} ({})",
+                            if playlist.user_data.is_favorite { "♥ " } else { "" },
+                            playlist.name,
+                            playlist.child_count
+                        ),
+                        Action::AddToPlaylist { playlist_id: playlist.id.clone() },
+                        Style::default(),
+                        true,
+                    )
+                })
+                .collect(),
+            PopupMenu::GlobalSavedQueue { saved } => vec![
                 PopupAction::new(
-                    "Theme".to_string(),
-                    Action::GlobalSetTheme,
+                    format!("Play ({} tracks, replaces the queue)", saved.tracks),
+                    Action::Play,
                     Style::default(),
                     false,
                 ),
                 PopupAction::new(
-                    "Select music libraries".to_string(),
-                    Action::SelectLibraries,
-                    Style::default(),
+                    "Delete".to_string(),
+                    Action::Delete,
+                    Style::default().fg(style::Color::Red),
                     false,
                 ),
-                PopupAction::new(
-                    "Repair offline downloads (could take a minute)".to_string(),
-                    Action::OfflineRepair,
+                PopupAction::new("Cancel".to_string(), Action::Cancel, Style::default(), false),
+            ],
+            PopupMenu::GlobalAudioDevice { devices, current, per_device_volume } => {
+                let mut actions = devices
+                    .iter()
+                    .map(|device| {
+                        let label = if device.description.is_empty() {
+                            device.name.clone()
+                        } else {
+                            format!("{} ({})", device.description, device.name)
+                        };
+                        PopupAction::new(
+                            if device.name == *current {
+                                format!("✓ {}", label)
+                            } else {
+                                format!("  {}", label)
+                            },
+                            Action::SetAudioDevice { name: device.name.clone() },
+                            Style::default(),
+                            false,
+                        )
+                    })
+                    .collect::<Vec<PopupAction>>();
+                actions.push(PopupAction::new(
+                    if *per_device_volume {
+                        "Remember volume per device: on".to_string()
+                    } else {
+                        "Remember volume per device: off".to_string()
+                    },
+                    Action::Toggle,
                     Style::default(),
                     false,
-                ),
-                PopupAction::new(
-                    "Stop downloading and abort queued".to_string(),
-                    Action::CancelDownloads,
-                    Style::default().fg(if *downloading {
-                        style::Color::Red
+                ));
+                actions
+            }
+            PopupMenu::GlobalEqualizer { settings, presets, .. } => {
+                let mut actions = vec![PopupAction::new(
+                    if settings.bypass {
+                        "Bypass: on (equalizer disabled)".to_string()
                     } else {
-                        style::Color::DarkGray
-                    }),
-                    true,
-                ),
-                PopupAction::new(
-                    "Reset section widths".to_string(),
-                    Action::ResetSectionWidths,
+                        "Bypass: off".to_string()
+                    },
+                    Action::Toggle,
                     Style::default(),
                     false,
-                ),
-            ],
-            PopupMenu::GlobalRunScheduledTask { tasks } => {
-                let mut actions = vec![];
-                let mut categories =
-                    tasks.iter().map(|t| t.category.clone()).collect::<Vec<String>>();
-                categories.sort();
-                categories.dedup();
-                for category in categories {
-                    for task in tasks.iter().filter(|t| t.category == category) {
-                        actions.push(PopupAction::new(
-                            format!("{}: {} ({})", category, task.name, task.description),
-                            Action::RunScheduledTask { task: Some(task.clone()) },
-                            Style::default(),
-                            true,
-                        ));
-                    }
+                )];
+                let dim = if settings.bypass {
+                    Style::default().fg(style::Color::DarkGray)
+                } else {
+                    Style::default()
+                };
+                for (band, (freq, gain)) in EQ_BANDS.iter().zip(settings.gains.iter()).enumerate() {
+                    let freq =
+                        if *freq >= 1000 { format!("{}k", freq / 1000) } else { freq.to_string() };
+                    // -12 dB on the left, +12 dB on the right
+                    let slot = ((gain + EQ_MAX_GAIN) / (EQ_MAX_GAIN * 2.0) * 16.0).round() as usize;
+                    let slider = (0..=16)
+                        .map(|i| {
+                            if i == slot {
+                                '●'
+                            } else if i == 8 {
+                                '┼'
+                            } else {
+                                '─'
+                            }
+                        })
+                        .collect::<String>();
+                    actions.push(PopupAction::new(
+                        format!("{:>4} Hz {} {:+5.1} dB", freq, slider, gain),
+                        Action::EqBand { band },
+                        dim,
+                        false,
+                    ));
+                }
+                for preset in presets {
+                    actions.push(PopupAction::new(
+                        if *preset == settings.preset {
+                            format!("✓ Preset: {}", preset)
+                        } else {
+                            format!("  Preset: {}", preset)
+                        },
+                        Action::SetEqPreset { name: preset.clone() },
+                        Style::default(),
+                        false,
+                    ));
                 }
                 actions
             }
@@ -509,6 +1007,18 @@ impl PopupMenu {
                     Style::default(),
                     true,
                 ),
+                PopupAction::new(
+                    "Export to a file".to_string(),
+                    Action::Export,
+                    Style::default(),
+                    false,
+                ),
+                PopupAction::new(
+                    "Import a playlist file".to_string(),
+                    Action::Import,
+                    Style::default(),
+                    true,
+                ),
                 PopupAction::new(
                     "Change filter".to_string(),
                     Action::ChangeFilter,
@@ -528,6 +1038,57 @@ impl PopupMenu {
                     true,
                 ),
             ],
+            PopupMenu::SmartPlaylistRoot { .. } => vec![
+                PopupAction::new("Play".to_string(), Action::Play, Style::default(), false),
+                PopupAction::new(
+                    "Append to main queue".to_string(),
+                    Action::Append,
+                    Style::default(),
+                    false,
+                ),
+                PopupAction::new(
+                    "Append to temporary queue".to_string(),
+                    Action::AppendTemporary,
+                    Style::default(),
+                    false,
+                ),
+                PopupAction::new(
+                    "Download all tracks".to_string(),
+                    Action::Download,
+                    Style::default(),
+                    true,
+                ),
+                PopupAction::new(
+                    "Remove downloaded tracks".to_string(),
+                    Action::RemoveDownload,
+                    Style::default(),
+                    true,
+                ),
+                PopupAction::new(
+                    "Save as a Jellyfin playlist".to_string(),
+                    Action::SaveAsPlaylist,
+                    Style::default(),
+                    true,
+                ),
+                PopupAction::new(
+                    "Export to a file".to_string(),
+                    Action::Export,
+                    Style::default(),
+                    false,
+                ),
+                PopupAction::new(
+                    "Change filter".to_string(),
+                    Action::ChangeFilter,
+                    Style::default(),
+                    false,
+                ),
+                PopupAction::new(
+                    "Change sort order".to_string(),
+                    Action::ChangeOrder,
+                    Style::default(),
+                    false,
+                ),
+            ],
             PopupMenu::PlaylistSetName { new_name, .. } => {
                 vec![
                     PopupAction::new(
@@ -611,6 +1172,73 @@ impl PopupMenu {
                 ),
                 PopupAction::new("Random".to_string(), Action::Random, Style::default(), false),
             ],
+            PopupMenu::PlaylistExport { format, local_paths, .. } => vec![
+                PopupAction::new(
+                    format!("Format: {}", format.name()),
+                    Action::ChangeFormat,
+                    Style::default(),
+                    false,
+                ),
+                PopupAction::new(
+                    if *local_paths {
+                        "Paths: downloaded files (the rest is left out)".to_string()
+                    } else {
+                        "Paths: stream urls".to_string()
+                    },
+                    Action::Toggle,
+                    Style::default(),
+                    false,
+                ),
+                PopupAction::new("Export".to_string(), Action::Export, Style::default(), false),
+                PopupAction::new("Cancel".to_string(), Action::Cancel, Style::default(), false),
+            ],
+            PopupMenu::PlaylistImport { path } => vec![
+                PopupAction::new(
+                    if path.is_empty() {
+                        "Type the path of an .m3u, .m3u8 or .xspf file".into()
+                    } else {
+                        format!("File: {}", path)
+                    },
+                    Action::Type,
+                    Style::default(),
+                    true,
+                ),
+                PopupAction::new("Import".to_string(), Action::Import, Style::default(), true),
+                PopupAction::new("Cancel".to_string(), Action::Cancel, Style::default(), true),
+            ],
+            PopupMenu::PlaylistImportReport { name, matches } => {
+                let mut actions = vec![
+                    PopupAction::new(
+                        format!("Name: {}", name),
+                        Action::Type,
+                        Style::default(),
+                        true,
+                    ),
+                    PopupAction::new(
+                        format!(
+                            "Create the playlist with {} tracks",
+                            matches.iter().filter(|m| m.include).count()
+                        ),
+                        Action::Create,
+                        Style::default(),
+                        true,
+                    ),
+                    PopupAction::new("Cancel".to_string(), Action::Cancel, Style::default(), true),
+                ];
+                for (index, entry) in matches.iter().enumerate() {
+                    actions.push(PopupAction::new(
+                        entry.label(),
+                        Action::ImportEntry { index },
+                        match (&entry.track_id, entry.include) {
+                            (None, _) => Style::default().fg(style::Color::Red),
+                            (Some(_), false) => Style::default().fg(style::Color::DarkGray),
+                            (Some(_), true) => Style::default(),
+                        },
+                        true,
+                    ));
+                }
+                actions
+            }
             PopupMenu::PlaylistsChangeFilter {} => vec![
                 PopupAction::new("Normal".to_string(), Action::Normal, Style::default(), false),
                 PopupAction::new(
@@ -621,6 +1249,26 @@ impl PopupMenu {
                 ),
             ],
             // ---------- Tracks ---------- //
+            PopupMenu::TrackInfo { rows, .. } => {
+                let mut actions = rows
+                    .iter()
+                    .map(|(label, value)| {
+                        PopupAction::new(
+                            format!("{:<14}{}", label, value),
+                            Action::None,
+                            Style::default(),
+                            false,
+                        )
+                    })
+                    .collect::<Vec<PopupAction>>();
+                actions.push(PopupAction::new(
+                    "Close".to_string(),
+                    Action::Ok,
+                    Style::default(),
+                    false,
+                ));
+                actions
+            }
             PopupMenu::TrackRoot { disliked, .. } => vec![
                 PopupAction::new(
                     "Jump to currently playing track".to_string(),
@@ -640,6 +1288,18 @@ impl PopupMenu {
                     Style::default(),
                     false,
                 ),
+                PopupAction::new(
+                    "Start radio from this track".to_string(),
+                    Action::StartRadio,
+                    Style::default(),
+                    false,
+                ),
+                PopupAction::new(
+                    "Track info".to_string(),
+                    Action::TrackInfo,
+                    Style::default(),
+                    false,
+                ),
                 PopupAction::new(
                     "Add to playlist".to_string(),
                     Action::AddToPlaylist { playlist_id: String::new() },
@@ -735,13 +1395,25 @@ impl PopupMenu {
                     Style::default(),
                     false,
                 ),
-                PopupAction::new("Random".to_string(), Action::Random, Style::default(), false),
-            ],
-            // ---------- Playlist tracks ---------- //
-            PopupMenu::PlaylistTracksRoot { disliked, .. } => vec![
+                PopupAction::new("Random".to_string(), Action::Random, Style::default(), false),
+            ],
+            // ---------- Playlist tracks ---------- //
+            PopupMenu::PlaylistTracksRoot { disliked, .. } => vec![
+                PopupAction::new(
+                    "Jump to album".to_string(),
+                    Action::GoAlbum,
+                    Style::default(),
+                    false,
+                ),
+                PopupAction::new(
+                    "Start radio from this track".to_string(),
+                    Action::StartRadio,
+                    Style::default(),
+                    false,
+                ),
                 PopupAction::new(
-                    "Jump to album".to_string(),
-                    Action::GoAlbum,
+                    "Track info".to_string(),
+                    Action::TrackInfo,
                     Style::default(),
                     false,
                 ),
@@ -818,6 +1490,12 @@ impl PopupMenu {
                         false,
                     ));
                 }
+                actions.push(PopupAction::new(
+                    "Start artist radio".to_string(),
+                    Action::StartRadio,
+                    Style::default(),
+                    false,
+                ));
                 actions.push(PopupAction::new(
                     "Change filter".to_string(),
                     Action::ChangeFilter,
@@ -906,6 +1584,12 @@ impl PopupMenu {
                     Style::default(),
                     false,
                 ),
+                PopupAction::new(
+                    "Start album radio".to_string(),
+                    Action::StartRadio,
+                    Style::default(),
+                    false,
+                ),
                 PopupAction::new(
                     "Change filter".to_string(),
                     Action::ChangeFilter,
@@ -969,6 +1653,18 @@ impl PopupMenu {
                     Style::default(),
                     false,
                 ),
+                PopupAction::new(
+                    "Start radio from this track".to_string(),
+                    Action::StartRadio,
+                    Style::default(),
+                    false,
+                ),
+                PopupAction::new(
+                    "Track info".to_string(),
+                    Action::TrackInfo,
+                    Style::default(),
+                    false,
+                ),
                 PopupAction::new(
                     "Add to playlist".to_string(),
                     Action::AddToPlaylist { playlist_id: String::new() },
@@ -986,6 +1682,146 @@ impl PopupMenu {
                     false,
                 ),
             ],
+            // ---------- Genres ---------- //
+            PopupMenu::GenreRoot { .. } => vec![
+                PopupAction::new("Play".to_string(), Action::Play, Style::default(), false),
+                PopupAction::new(
+                    "Shuffle play".to_string(),
+                    Action::Shuffle,
+                    Style::default(),
+                    false,
+                ),
+                PopupAction::new(
+                    "Start genre radio".to_string(),
+                    Action::StartRadio,
+                    Style::default(),
+                    false,
+                ),
+                PopupAction::new(
+                    "Append to main queue".to_string(),
+                    Action::Append,
+                    Style::default(),
+                    false,
+                ),
+                PopupAction::new(
+                    "Append to temporary queue".to_string(),
+                    Action::AppendTemporary,
+                    Style::default(),
+                    false,
+                ),
+            ],
+            // ---------- Selected tracks ---------- //
+            PopupMenu::BulkRoot { list, favorite, disliked, .. } => {
+                let mut actions = vec![];
+                if *list != SelectionList::Queue {
+                    actions.push(PopupAction::new(
+                        "Play next".to_string(),
+                        Action::PlayNext,
+                        Style::default(),
+                        false,
+                    ));
+                    actions.push(PopupAction::new(
+                        "Append to main queue".to_string(),
+                        Action::Append,
+                        Style::default(),
+                        false,
+                    ));
+                }
+                actions.push(PopupAction::new(
+                    "Add to playlist".to_string(),
+                    Action::AddToPlaylist { playlist_id: String::new() },
+                    Style::default(),
+                    true,
+                ));
+                if *list != SelectionList::Queue {
+                    actions.push(PopupAction::new(
+                        "Download".to_string(),
+                        Action::Download,
+                        Style::default(),
+                        true,
+                    ));
+                    actions.push(PopupAction::new(
+                        "Remove downloads".to_string(),
+                        Action::RemoveDownload,
+                        Style::default(),
+                        false,
+                    ));
+                }
+                actions.push(PopupAction::new(
+                    if *favorite {
+                        "Remove from favorites".to_string()
+                    } else {
+                        "Add to favorites".to_string()
+                    },
+                    Action::Favorite,
+                    Style::default(),
+                    true,
+                ));
+                actions.push(PopupAction::new(
+                    if *disliked {
+                        "Remove dislike".to_string()
+                    } else {
+                        "Dislike tracks".to_string()
+                    },
+                    Action::Dislike,
+                    Style::default(),
+                    false,
+                ));
+                match list {
+                    SelectionList::PlaylistTracks => actions.push(PopupAction::new(
+                        "Remove from this playlist".to_string(),
+                        Action::Delete,
+                        Style::default().fg(style::Color::Red),
+                        true,
+                    )),
+                    SelectionList::Queue => actions.push(PopupAction::new(
+                        "Remove from queue".to_string(),
+                        Action::Delete,
+                        Style::default().fg(style::Color::Red),
+                        false,
+                    )),
+                    _ => {}
+                }
+                actions.push(PopupAction::new(
+                    "Clear selection".to_string(),
+                    Action::Cancel,
+                    Style::default(),
+                    false,
+                ));
+                actions
+            }
+            PopupMenu::BulkAddToPlaylist { playlists, .. } => {
+                let mut actions = vec![];
+                for playlist in playlists {
+                    actions.push(PopupAction::new(
+                        format!(
+                            "{}{} ({})",
+                            if playlist.user_data.is_favorite { "♥ " } else { "" },
+                            playlist.name,
+                            playlist.child_count
+                        ),
+                        Action::AddToPlaylist { playlist_id: playlist.id.clone() },
+                        Style::default(),
+                        true,
+                    ));
+                }
+                actions
+            }
+            PopupMenu::BulkRemoveFromPlaylist { count, .. } => vec![
+                PopupAction::new(
+                    format!("Remove {} tracks from playlist?", count),
+                    Action::None,
+                    Style::default().fg(style::Color::Red),
+                    true,
+                ),
+                PopupAction::new(
+                    "Yes".to_string(),
+                    Action::Yes,
+                    Style::default().fg(style::Color::Red),
+                    true,
+                ),
+                PopupAction::new("No".to_string(), Action::No, Style::default(), true),
+            ],
         }
     }
 }
@@ -1013,6 +1849,23 @@ impl crate::tui::App {
                 Some(PopupMenu::PlaylistCreate { name, .. }) => {
                     *name = self.popup.editing_new.clone();
                 }
+                Some(PopupMenu::GlobalSeekTo { time }) => {
+                    *time = self.popup.editing_new.clone();
+                    // so a second enter seeks
+                    if !self.popup.editing {
+                        self.popup.selected.select(Some(1));
+                    }
+                }
+                Some(PopupMenu::GlobalQueueName { name, .. })
+                | Some(PopupMenu::PlaylistImport { path: name }) => {
+                    *name = self.popup.editing_new.clone();
+                    if !self.popup.editing {
+                        self.popup.selected.select(Some(1));
+                    }
+                }
+                Some(PopupMenu::PlaylistImportReport { name, .. }) => {
+                    *name = self.popup.editing_new.clone();
+                }
                 _ => {}
             }
             return;
@@ -1021,6 +1874,9 @@ impl crate::tui::App {
             self.handle_search(key_event).await;
             return;
         }
+        let Some(key_event) = self.keybindings.translate(key_event) else {
+            return;
+        };
         self.handle_special_keys(key_event).await;
         self.handle_navigational_keys(key_event).await;
     }
@@ -1053,6 +1909,26 @@ impl crate::tui::App {
             KeyCode::Char('/') => {
                 self.locally_searching = true;
             }
+            KeyCode::Char('+') | KeyCode::Char('l') | KeyCode::Right
+                if matches!(self.popup.current_menu, Some(PopupMenu::GlobalEqualizer { .. })) =>
+            {
+                self.adjust_eq_band(1.0).await;
+            }
+            KeyCode::Char('-') | KeyCode::Char('h') | KeyCode::Left
+                if matches!(self.popup.current_menu, Some(PopupMenu::GlobalEqualizer { .. })) =>
+            {
+                self.adjust_eq_band(-1.0).await;
+            }
+            KeyCode::Char('+')
+                if matches!(self.popup.current_menu, Some(PopupMenu::GlobalSleepTimer { .. })) =>
+            {
+                self.adjust_sleep_timer_popup(true);
+            }
+            KeyCode::Char('-')
+                if matches!(self.popup.current_menu, Some(PopupMenu::GlobalSleepTimer { .. })) =>
+            {
+                self.adjust_sleep_timer_popup(false);
+            }
             KeyCode::Char('+') => {
                 if let Some(PopupMenu::GlobalShuffle {
                     tracks_n,
@@ -1187,18 +2063,53 @@ impl crate::tui::App {
             None => return,
         };
 
-        if let PopupMenu::GenericMessage { .. } = menu {
+        if let PopupMenu::GenericMessage { .. } | PopupMenu::TrackInfo { .. } = menu {
             if let Action::Ok = action {
                 self.close_popup();
             }
             return;
         }
 
+        if let Action::TrackInfo = action {
+            self.show_track_info(self.state.last_section).await;
+            return;
+        }
+
+        // playlist files are reached from both the playlists and the global popup (for the queue)
+        if matches!(
+            menu,
+            PopupMenu::PlaylistExport { .. }
+                | PopupMenu::PlaylistImport { .. }
+                | PopupMenu::PlaylistImportReport { .. }
+        ) {
+            self.apply_playlist_file_action(&action, menu.clone()).await;
+            return;
+        }
+
         if self.popup.global {
             self.apply_global_action(&action, menu.clone()).await;
             return;
         }
 
+        if matches!(
+            menu,
+            PopupMenu::BulkRoot { .. }
+                | PopupMenu::BulkAddToPlaylist { .. }
+                | PopupMenu::BulkRemoveFromPlaylist { .. }
+        ) {
+            self.apply_bulk_action(&action, menu.clone()).await;
+            return;
+        }
+
+        // the radio can be started from anything, the seed is taken from the selection
+        if let Action::StartRadio = action {
+            self.close_popup();
+            if let Some((seed, profile)) = self.radio_seed_from_selection().await {
+                self.start_radio(seed, profile).await;
+            }
+            return;
+        }
+
         match self.state.active_tab {
             ActiveTab::Library => match self.state.last_section {
                 ActiveSection::Tracks => {
@@ -1229,15 +2140,146 @@ impl crate::tui::App {
                 }
                 _ => {}
             },
+            ActiveTab::Genres => {
+                self.apply_genre_action(&action, menu.clone()).await;
+            }
             _ => {}
         }
     }
 
     /// Following functions separate actions based on UI sections
     ///
+    async fn apply_playlist_file_action(&mut self, action: &Action, menu: PopupMenu) -> Option<()> {
+        match menu {
+            PopupMenu::PlaylistExport { name, source, format, local_paths } => match action {
+                Action::ChangeFormat => {
+                    let format = match format {
+                        PlaylistFormat::M3u8 => PlaylistFormat::Xspf,
+                        PlaylistFormat::Xspf => PlaylistFormat::M3u8,
+                    };
+                    self.popup.current_menu =
+                        Some(PopupMenu::PlaylistExport { name, source, format, local_paths });
+                }
+                Action::Toggle => {
+                    // stream urls need the server
+                    let local_paths = !local_paths || self.client.is_none();
+                    self.popup.current_menu =
+                        Some(PopupMenu::PlaylistExport { name, source, format, local_paths });
+                }
+                Action::Export => {
+                    match self.export_playlist_file(&name, &source, format, local_paths).await {
+                        Ok((path, written, 0)) => self.set_generic_message(
+                            "Playlist exported",
+                            &format!("Wrote {} tracks to {}", written, path.display()),
+                        ),
+                        Ok((path, written, left_out)) => self.set_generic_message(
+                            "Playlist exported",
+                            &format!(
+                                "Wrote {} tracks to {}, {} not downloaded were left out",
+                                written,
+                                path.display(),
+                                left_out
+                            ),
+                        ),
+                        Err(e) => {
+                            log::error!("Failed to export {}: {}", name, e);
+                            self.set_generic_message(
+                                "Error exporting playlist",
+                                &format!("Failed to export {}: {}", name, e),
+                            );
+                        }
+                    }
+                }
+                _ => {
+                    self.close_popup();
+                }
+            },
+            PopupMenu::PlaylistImport { path } => match action {
+                Action::Type => {
+                    self.popup.editing = true;
+                }
+                Action::Import => {
+                    if path.trim().is_empty() {
+                        self.popup.editing = true;
+                        self.popup.selected.select_first();
+                        return None;
+                    }
+                    match self.read_playlist_file(&path).await {
+                        Ok((_, matches)) if matches.is_empty() => self.set_generic_message(
+                            "Nothing to import",
+                            &format!("No tracks found in {}.", path),
+                        ),
+                        Ok((name, matches)) => {
+                            self.popup.editing_original = name.clone();
+                            self.popup.editing_new = name.clone();
+                            self.popup.current_menu =
+                                Some(PopupMenu::PlaylistImportReport { name, matches });
+                            self.popup.selected.select(Some(1));
+                        }
+                        Err(e) => {
+                            log::error!("Failed to read playlist file {}: {}", path, e);
+                            self.set_generic_message(
+                                "Error reading playlist file",
+                                &format!("Failed to read {}: {}", path, e),
+                            );
+                        }
+                    }
+                }
+                _ => {
+                    self.close_popup();
+                }
+            },
+            PopupMenu::PlaylistImportReport { name, mut matches } => match action {
+                Action::Type => {
+                    self.popup.editing = true;
+                    self.popup.selected.select_first();
+                }
+                Action::ImportEntry { index } => {
+                    let entry = matches.get_mut(*index)?;
+                    // unmatched entries can't be included
+                    entry.track_id.as_ref()?;
+                    entry.include = !entry.include;
+                    self.popup.current_menu =
+                        Some(PopupMenu::PlaylistImportReport { name, matches });
+                }
+                Action::Create => {
+                    if name.trim().is_empty() {
+                        self.popup.editing = true;
+                        self.popup.selected.select_first();
+                        return None;
+                    }
+                    match self.create_imported_playlist(name.trim(), &matches).await {
+                        Ok(added) => self.set_generic_message(
+                            "Playlist imported",
+                            &format!(
+                                "Playlist {} created with {} of {} tracks.",
+                                name.trim(),
+                                added,
+                                matches.len()
+                            ),
+                        ),
+                        Err(_) => self.set_generic_message(
+                            "Error creating playlist",
+                            &format!("Failed to create playlist {}.", name.trim()),
+                        ),
+                    }
+                }
+                _ => {
+                    self.close_popup();
+                }
+            },
+            _ => {}
+        }
+        Some(())
+    }
+
     async fn apply_global_action(&mut self, action: &Action, menu: PopupMenu) -> Option<()> {
         match menu {
             PopupMenu::GlobalRoot { downloading, .. } => match action {
+                Action::StopRadio => {
+                    self.stop_radio();
+                    self.close_popup();
+                }
                 Action::Refresh => {
                     let _ = self.db.cmd_tx.send(Command::Update(UpdateCommand::Library)).await;
                     self.close_popup();
@@ -1266,16 +2308,90 @@ impl crate::tui::App {
                         self.popup.selected.select_last();
                     }
                 }
-                Action::RunScheduledTasks => {
-                    let tasks = self.client.as_ref()?.scheduled_tasks().await.unwrap_or(vec![]);
-                    if tasks.is_empty() {
+                Action::RunScheduledTasks => {
+                    let tasks = self.client.as_ref()?.scheduled_tasks().await.unwrap_or(vec![]);
+                    if tasks.is_empty() {
+                        self.set_generic_message(
+                            "No scheduled tasks",
+                            "You may not have permissions to run tasks.",
+                        );
+                        return None;
+                    }
+                    self.popup.current_menu = Some(PopupMenu::GlobalRunScheduledTask { tasks });
+                    self.popup.selected.select_first();
+                }
+                Action::ToggleTransitions => {
+                    self.preferences.transitions = Some(!self.transitions_on());
+                    if let Err(e) = self.preferences.save() {
+                        log::error!("Failed to save preferences: {}", e);
+                    }
+                    self.apply_transitions().await;
+                    self.close_popup();
+                }
+                Action::TogglePitchCorrection => {
+                    self.preferences.pitch_correction = !self.preferences.pitch_correction;
+                    if let Err(e) = self.preferences.save() {
+                        log::error!("Failed to save preferences: {}", e);
+                    }
+                    self.apply_speed().await;
+                    self.close_popup();
+                }
+                Action::SeekTo => {
+                    if self.stopped {
+                        self.close_popup();
+                        return None;
+                    }
+                    self.open_seek_popup();
+                }
+                Action::ChangeShuffleMode => {
+                    // random -> smart -> smart with favorites first -> random
+                    let preferences = &mut self.preferences;
+                    (preferences.shuffle_mode, preferences.smart_shuffle_favorites) =
+                        match (preferences.shuffle_mode, preferences.smart_shuffle_favorites) {
+                            (ShuffleMode::Random, _) => (ShuffleMode::Smart, false),
+                            (ShuffleMode::Smart, false) => (ShuffleMode::Smart, true),
+                            (ShuffleMode::Smart, true) => (ShuffleMode::Random, false),
+                        };
+                    if let Err(e) = self.preferences.save() {
+                        log::error!("Failed to save preferences: {}", e);
+                    }
+                    self.close_popup();
+                }
+                Action::SavedQueues => {
+                    self.popup.current_menu = Some(PopupMenu::GlobalQueue {
+                        tracks_n: self.state.queue.len(),
+                        saved: self.saved_queues().await,
+                    });
+                    self.popup.selected.select_first();
+                }
+                Action::SleepTimer => {
+                    self.popup.current_menu = Some(PopupMenu::GlobalSleepTimer {
+                        active: self.state.sleep_timer.as_ref().map(|t| t.label()),
+                        custom_minutes: 45,
+                        tracks_n: 3,
+                    });
+                    self.popup.selected.select_first();
+                }
+                Action::AudioDevices => {
+                    let devices = self.mpv_handle.audio_devices().await;
+                    if devices.is_empty() {
                         self.set_generic_message(
-                            "No scheduled tasks",
-                            "You may not have permissions to run tasks.",
+                            "No audio devices",
+                            "mpv did not report any audio output devices.",
                         );
                         return None;
                     }
-                    self.popup.current_menu = Some(PopupMenu::GlobalRunScheduledTask { tasks });
+                    let current = self.current_audio_device();
+                    let index = devices.iter().position(|d| d.name == current).unwrap_or(0);
+                    self.popup.current_menu = Some(PopupMenu::GlobalAudioDevice {
+                        devices,
+                        current,
+                        per_device_volume: self.preferences.per_device_volume,
+                    });
+                    self.popup.selected.select(Some(index));
+                }
+                Action::Equalizer => {
+                    self.popup.current_menu = Some(self.equalizer_popup());
                     self.popup.selected.select_first();
                 }
                 Action::SelectLibraries => {
@@ -1298,6 +2414,20 @@ impl crate::tui::App {
                         );
                     }
                 }
+                Action::ReplayScrobbles => {
+                    if self.scrobbles_pending == 0 {
+                        return None;
+                    }
+                    if self.client.is_none() {
+                        self.set_generic_message(
+                            "Offline scrobbles",
+                            "These will be sent to Jellyfin the next time you start online.",
+                        );
+                        return None;
+                    }
+                    let _ = self.db.cmd_tx.send(Command::ReplayScrobbles).await;
+                    self.close_popup();
+                }
                 Action::CancelDownloads => {
                     if !downloading {
                         return None;
@@ -1359,6 +2489,219 @@ impl crate::tui::App {
                     self.close_popup();
                 }
             },
+            PopupMenu::GlobalAudioDevice { devices, .. } => match action {
+                Action::SetAudioDevice { name } => {
+                    self.switch_audio_device(name.clone()).await;
+                    self.close_popup();
+                }
+                Action::Toggle => {
+                    self.preferences.per_device_volume = !self.preferences.per_device_volume;
+                    if self.preferences.per_device_volume {
+                        // start from the current level, so switching away and back keeps it
+                        self.preferences.device_volumes.insert(
+                            self.current_audio_device(),
+                            self.state.current_playback_state.volume,
+                        );
+                    }
+                    let _ = self.preferences.save();
+                    self.popup.current_menu = Some(PopupMenu::GlobalAudioDevice {
+                        devices,
+                        current: self.current_audio_device(),
+                        per_device_volume: self.preferences.per_device_volume,
+                    });
+                }
+                _ => {}
+            },
+            PopupMenu::GlobalSleepTimer { .. } => {
+                let timer = match action {
+                    Action::SleepIn { minutes } | Action::SleepInCustom { minutes } => {
+                        Some(SleepTimer::in_minutes(*minutes))
+                    }
+                    Action::SleepAfterTrack => self.sleep_after_track(),
+                    Action::SleepAfterAlbum => self.sleep_after_album(),
+                    Action::SleepAfterTracks { tracks } => {
+                        Some(SleepTimer::AfterTracks { remaining: *tracks })
+                    }
+                    Action::Cancel => None,
+                    _ => return None,
+                };
+                self.set_sleep_timer(timer).await;
+                self.close_popup();
+            }
+            PopupMenu::GlobalSeekTo { time } => match action {
+                Action::Type => {
+                    self.popup.editing = true;
+                }
+                Action::Confirm => {
+                    let Some(target) = helpers::parse_timestamp(&time) else {
+                        self.popup.editing_new.clear();
+                        self.popup.current_menu =
+                            Some(PopupMenu::GlobalSeekTo { time: String::new() });
+                        self.popup.selected.select_first();
+                        self.popup.editing = true;
+                        return None;
+                    };
+                    self.seek_to(target).await;
+                    self.close_popup();
+                }
+                Action::Cancel => {
+                    self.close_popup();
+                }
+                _ => {}
+            },
+            PopupMenu::GlobalQueue { tracks_n, saved } => match action {
+                Action::SaveAsPlaylist | Action::Create => {
+                    if tracks_n == 0 {
+                        return None;
+                    }
+                    self.popup.current_menu = Some(PopupMenu::GlobalQueueName {
+                        name: String::new(),
+                        playlist: matches!(action, Action::SaveAsPlaylist),
+                    });
+                    self.popup.editing_original = String::new();
+                    self.popup.editing_new = String::new();
+                    self.popup.selected.select_first();
+                    self.popup.editing = true;
+                }
+                Action::AddToPlaylist { .. } => {
+                    if tracks_n == 0 {
+                        return None;
+                    }
+                    self.popup.current_menu = Some(PopupMenu::GlobalQueueToPlaylist {
+                        playlists: self.server_playlists(),
+                    });
+                    self.popup.selected.select_first();
+                }
+                Action::Export => {
+                    if tracks_n == 0 {
+                        return None;
+                    }
+                    self.popup.current_menu = Some(PopupMenu::PlaylistExport {
+                        name: "Queue".to_string(),
+                        source: ExportSource::Queue,
+                        format: PlaylistFormat::default(),
+                        local_paths: self.client.is_none(),
+                    });
+                    self.popup.selected.select(Some(2));
+                }
+                Action::SavedQueue { id } => {
+                    let saved = saved.into_iter().find(|s| s.id == *id)?;
+                    self.popup.current_menu = Some(PopupMenu::GlobalSavedQueue { saved });
+                    self.popup.selected.select_first();
+                }
+                _ => {}
+            },
+            PopupMenu::GlobalQueueName { name, playlist } => match action {
+                Action::Type => {
+                    self.popup.editing = true;
+                }
+                Action::Confirm => {
+                    let name = name.trim();
+                    if name.is_empty() {
+                        self.popup.editing = true;
+                        self.popup.selected.select_first();
+                        return None;
+                    }
+                    if playlist {
+                        match self.save_queue_as_playlist(name).await {
+                            Ok(added) => self.set_generic_message(
+                                "Playlist created",
+                                &format!("Playlist {} created with {} tracks.", name, added),
+                            ),
+                            Err(_) => self.set_generic_message(
+                                "Error creating playlist",
+                                &format!("Failed to create playlist {}.", name),
+                            ),
+                        }
+                    } else {
+                        match self.save_queue_locally(name).await {
+                            Ok(()) => self.set_generic_message(
+                                "Queue saved",
+                                &format!("Saved the queue as {}.", name),
+                            ),
+                            Err(e) => {
+                                log::error!("Failed to save the queue: {}", e);
+                                self.set_generic_message(
+                                    "Error saving queue",
+                                    &format!("Failed to save the queue as {}.", name),
+                                );
+                            }
+                        }
+                    }
+                }
+                Action::Cancel => {
+                    self.close_popup();
+                }
+                _ => {}
+            },
+            PopupMenu::GlobalQueueToPlaylist { playlists } => {
+                let Action::AddToPlaylist { playlist_id } = action else {
+                    self.close_popup();
+                    return None;
+                };
+                let playlist = playlists.iter().find(|p| p.id == *playlist_id)?;
+                match self.append_queue_to_playlist(playlist_id).await {
+                    Ok(added) => self.set_generic_message(
+                        "Tracks added",
+                        &format!("{} tracks added to playlist {}.", added, playlist.name),
+                    ),
+                    Err(_) => self.set_generic_message(
+                        "Error adding tracks",
+                        &format!("Failed to add the queue to playlist {}.", playlist.name),
+                    ),
+                }
+            }
+            PopupMenu::GlobalSavedQueue { saved } => match action {
+                Action::Play => match self.restore_saved_queue(saved.id).await {
+                    Ok((0, _)) => self.set_generic_message(
+                        "Nothing to play",
+                        "None of the saved tracks are available offline.",
+                    ),
+                    Ok((_, 0)) => self.close_popup(),
+                    Ok((_, missing)) => self.set_generic_message(
+                        "Queue restored",
+                        &format!("{} tracks aren't available offline and were left out.", missing),
+                    ),
+                    Err(e) => {
+                        log::error!("Failed to restore saved queue {}: {}", saved.id, e);
+                        self.set_generic_message(
+                            "Error restoring queue",
+                            &format!("Failed to restore {}.", saved.name),
+                        );
+                    }
+                },
+                Action::Delete => {
+                    if let Err(e) = self.delete_saved_queue(saved.id).await {
+                        log::error!("Failed to delete saved queue {}: {}", saved.id, e);
+                    }
+                    self.popup.current_menu = Some(PopupMenu::GlobalQueue {
+                        tracks_n: self.state.queue.len(),
+                        saved: self.saved_queues().await,
+                    });
+                    self.popup.selected.select_first();
+                }
+                _ => {
+                    self.close_popup();
+                }
+            },
+            PopupMenu::GlobalEqualizer { mut settings, .. } => {
+                match action {
+                    Action::Toggle => settings.bypass = !settings.bypass,
+                    Action::EqBand { band } => {
+                        // enter puts the band back to zero, +/- move it
+                        settings.gains[*band] = 0.0;
+                        settings.preset = EQ_CUSTOM.to_string();
+                    }
+                    Action::SetEqPreset { name } => {
+                        let preset = self.equalizer.preset(name)?;
+                        settings.gains = preset.gains;
+                        settings.preset = preset.name.clone();
+                    }
+                    _ => return None,
+                }
+                self.set_eq_settings(settings).await;
+                self.popup.current_menu = Some(self.equalizer_popup());
+            }
             PopupMenu::GlobalSelectLibraries { libraries } => match action {
                 Action::ToggleLibrary { library_id } => {
                     let mut new_libraries = libraries.clone();
@@ -1544,7 +2887,7 @@ impl crate::tui::App {
                     self.popup.current_menu = Some(PopupMenu::TrackAddToPlaylist {
                         track_name,
                         track_id,
-                        playlists: self.playlists.clone(),
+                        playlists: self.server_playlists(),
                     });
                     self.popup.selected.select_first();
                 }
@@ -1959,7 +3302,7 @@ impl crate::tui::App {
                         self.popup.current_menu = Some(PopupMenu::TrackAddToPlaylist {
                             track_name: track.name.clone(),
                             track_id: track.id.clone(),
-                            playlists: self.playlists.clone(),
+                            playlists: self.server_playlists(),
                         });
                         self.popup.selected.select_first();
                     }
@@ -2040,6 +3383,35 @@ impl crate::tui::App {
         Some(())
     }
 
+    async fn apply_genre_action(&mut self, action: &Action, menu: PopupMenu) -> Option<()> {
+        let PopupMenu::GenreRoot { genre_name } = menu else {
+            return None;
+        };
+        if genre_name != self.state.current_genre || self.genre_tracks.is_empty() {
+            self.open_genre(&genre_name).await;
+        }
+        let tracks = self.genre_tracks.clone();
+        match action {
+            Action::Play => {
+                self.initiate_main_queue(&tracks, 0).await;
+            }
+            Action::Shuffle => {
+                // same as playing with shuffle turned on, so unshuffling restores the album order
+                self.state.shuffle = true;
+                self.initiate_main_queue(&tracks, 0).await;
+            }
+            Action::Append => {
+                self.append_to_main_queue(&tracks, 0).await;
+            }
+            Action::AppendTemporary => {
+                self.push_to_temporary_queue(&tracks, 0, tracks.len()).await;
+            }
+            _ => return None,
+        }
+        self.close_popup();
+        Some(())
+    }
+
     async fn apply_playlist_tracks_action(
         &mut self,
         action: &Action,
@@ -2103,7 +3475,7 @@ impl crate::tui::App {
                         self.popup.current_menu = Some(PopupMenu::PlaylistTrackAddToPlaylist {
                             track_name,
                             track_id,
-                            playlists: self.playlists.clone(),
+                            playlists: self.server_playlists(),
                         });
                         self.popup.selected.select_first();
                     }
@@ -2123,6 +3495,13 @@ impl crate::tui::App {
                         self.close_popup();
                     }
                     Action::Delete => {
+                        if is_smart_playlist(&self.state.current_playlist.id) {
+                            self.set_generic_message(
+                                "Smart playlist",
+                                "Its tracks follow the rules in your config, change them there.",
+                            );
+                            return None;
+                        }
                         self.popup.current_menu = Some(PopupMenu::PlaylistTracksRemove {
                             track_name,
                             track_id,
@@ -2201,12 +3580,116 @@ impl crate::tui::App {
         Some(())
     }
 
+    async fn apply_bulk_action(&mut self, action: &Action, menu: PopupMenu) -> Option<()> {
+        match menu {
+            PopupMenu::BulkRoot { list, count, favorite, disliked } => {
+                match action {
+                    Action::PlayNext => {
+                        let tracks = self.selected_tracks();
+                        self.push_all_next_to_temporary_queue(&tracks).await;
+                    }
+                    Action::Append => {
+                        let tracks = self.selected_tracks();
+                        self.append_to_main_queue(&tracks, 0).await;
+                    }
+                    Action::AddToPlaylist { .. } => {
+                        self.popup.current_menu = Some(PopupMenu::BulkAddToPlaylist {
+                            count,
+                            playlists: self.server_playlists(),
+                        });
+                        self.popup.selected.select_first();
+                        return Some(());
+                    }
+                    Action::Download => {
+                        self.bulk_download().await;
+                    }
+                    Action::RemoveDownload => {
+                        self.bulk_remove_download().await;
+                    }
+                    Action::Favorite => {
+                        self.bulk_favorite(!favorite).await;
+                    }
+                    Action::Dislike => {
+                        self.bulk_dislike(!disliked).await;
+                    }
+                    Action::Delete if list == SelectionList::Queue => {
+                        self.bulk_remove_from_queue().await;
+                    }
+                    Action::Delete => {
+                        if is_smart_playlist(&self.state.current_playlist.id) {
+                            self.set_generic_message(
+                                "Smart playlist",
+                                "Its tracks follow the rules in your config, change them there.",
+                            );
+                            return None;
+                        }
+                        self.popup.current_menu = Some(PopupMenu::BulkRemoveFromPlaylist {
+                            count,
+                            playlist_name: self.state.current_playlist.name.clone(),
+                        });
+                        self.popup.selected.select(Some(1));
+                        return Some(());
+                    }
+                    _ => {}
+                }
+                self.clear_selection();
+                self.close_popup();
+            }
+            PopupMenu::BulkAddToPlaylist { count, playlists } => {
+                let Action::AddToPlaylist { playlist_id } = action else {
+                    self.close_popup();
+                    return None;
+                };
+                let playlist = playlists.iter().find(|p| p.id == *playlist_id)?;
+                match self.bulk_add_to_playlist(playlist_id).await {
+                    Ok(added) => {
+                        self.clear_selection();
+                        self.set_generic_message(
+                            "Tracks added",
+                            &format!("{} tracks added to playlist {}.", added, playlist.name),
+                        );
+                    }
+                    Err(_) => self.set_generic_message(
+                        "Error adding tracks",
+                        &format!("Failed to add {} tracks to playlist {}.", count, playlist.name),
+                    ),
+                }
+            }
+            PopupMenu::BulkRemoveFromPlaylist { count, playlist_name } => match action {
+                Action::None => {
+                    self.popup.selected.select_next();
+                }
+                Action::Yes => match self.bulk_remove_from_playlist().await {
+                    Ok(removed) => {
+                        self.clear_selection();
+                        self.set_generic_message(
+                            &format!("{} tracks removed", removed),
+                            &format!("Successfully removed from {}.", playlist_name),
+                        );
+                    }
+                    Err(_) => self.set_generic_message(
+                        "Error removing tracks",
+                        &format!(
+                            "Failed to remove {} tracks from playlist {}.",
+                            count, playlist_name
+                        ),
+                    ),
+                },
+                _ => {
+                    self.close_popup();
+                }
+            },
+            _ => {}
+        }
+        Some(())
+    }
+
     async fn apply_playlist_action(&mut self, action: &Action, menu: PopupMenu) -> Option<()> {
         let id = self.get_id_of_selected(&self.playlists, Selectable::Playlist);
         let mut selected_playlist = self.playlists.iter().find(|p| p.id == id)?.clone();
 
         match menu {
-            PopupMenu::PlaylistRoot { .. } => {
+            PopupMenu::PlaylistRoot { .. } | PopupMenu::SmartPlaylistRoot { .. } => {
                 match action {
                     Action::Play => {
                         self.open_playlist(None).await;
@@ -2275,6 +3758,35 @@ impl crate::tui::App {
                             );
                         }
                     }
+                    Action::SaveAsPlaylist => {
+                        self.open_playlist(None).await;
+                        match self.save_smart_playlist(&selected_playlist.name).await {
+                            Ok(count) => {
+                                let _ = self
+                                    .db
+                                    .cmd_tx
+                                    .send(Command::Update(UpdateCommand::Library))
+                                    .await;
+                                self.set_generic_message(
+                                    "Playlist saved",
+                                    &format!(
+                                        "Saved {} tracks to the Jellyfin playlist {}.",
+                                        count, selected_playlist.name
+                                    ),
+                                );
+                            }
+                            Err(e) => {
+                                log::error!("Failed to save smart playlist: {}", e);
+                                self.set_generic_message(
+                                    "Error saving playlist",
+                                    &format!(
+                                        "Failed to save {} to Jellyfin.",
+                                        selected_playlist.name
+                                    ),
+                                );
+                            }
+                        }
+                    }
                     Action::Create => {
                         self.popup.current_menu =
                             Some(PopupMenu::PlaylistCreate { name: "".to_string(), public: false });
@@ -2283,6 +3795,23 @@ impl crate::tui::App {
                         self.popup.selected.select_first();
                         self.popup.editing = true;
                     }
+                    Action::Export => {
+                        self.popup.current_menu = Some(PopupMenu::PlaylistExport {
+                            name: selected_playlist.name.clone(),
+                            source: ExportSource::Playlist { id: id.clone() },
+                            format: PlaylistFormat::default(),
+                            local_paths: self.client.is_none(),
+                        });
+                        self.popup.selected.select(Some(2));
+                    }
+                    Action::Import => {
+                        self.popup.current_menu =
+                            Some(PopupMenu::PlaylistImport { path: String::new() });
+                        self.popup.editing_original = String::new();
+                        self.popup.editing_new = String::new();
+                        self.popup.selected.select_first();
+                        self.popup.editing = true;
+                    }
                     Action::Delete => {
                         self.popup.current_menu = Some(PopupMenu::PlaylistConfirmDelete {
                             playlist_name: selected_playlist.name.clone(),
@@ -2587,6 +4116,92 @@ impl crate::tui::App {
         }
     }
 
+    fn bulk_popup(&self, list: SelectionList) -> PopupMenu {
+        let (count, favorite, disliked) = match list {
+            SelectionList::Queue => {
+                let songs = self
+                    .marked_rows(list)
+                    .into_iter()
+                    .filter_map(|i| self.state.queue.get(i))
+                    .collect::<Vec<&Song>>();
+                (songs.len(), songs.iter().all(|s| s.is_favorite), songs.iter().all(|s| s.disliked))
+            }
+            _ => {
+                let tracks = self.selected_tracks();
+                (
+                    tracks.len(),
+                    tracks.iter().all(|t| t.user_data.is_favorite),
+                    tracks.iter().all(|t| t.disliked),
+                )
+            }
+        };
+        PopupMenu::BulkRoot { list, count, favorite, disliked }
+    }
+
+    fn equalizer_popup(&self) -> PopupMenu {
+        PopupMenu::GlobalEqualizer {
+            device: self.current_audio_device(),
+            settings: self.eq_settings(),
+            presets: self.equalizer.presets.iter().map(|p| p.name.clone()).collect(),
+        }
+    }
+
+    /// +/- on the custom minutes or the track count rows of the sleep timer popup
+    ///
+    fn adjust_sleep_timer_popup(&mut self, up: bool) {
+        let selected = self.popup.selected.selected().unwrap_or(0);
+        let action = self.popup.displayed_options.get(selected).map(|a| a.action.clone());
+        let Some(PopupMenu::GlobalSleepTimer { custom_minutes, tracks_n, .. }) =
+            &mut self.popup.current_menu
+        else {
+            return;
+        };
+        match action {
+            Some(Action::SleepInCustom { .. }) => {
+                *custom_minutes =
+                    if up { *custom_minutes + 5 } else { custom_minutes.saturating_sub(5).max(5) };
+            }
+            Some(Action::SleepAfterTracks { .. }) => {
+                *tracks_n = if up { *tracks_n + 1 } else { tracks_n.saturating_sub(1).max(1) };
+            }
+            _ => {}
+        }
+    }
+
+    /// Moves the selected equalizer band by `delta` dB
+    ///
+    async fn adjust_eq_band(&mut self, delta: f64) {
+        let Some(PopupMenu::GlobalEqualizer { mut settings, .. }) = self.popup.current_menu.clone()
+        else {
+            return;
+        };
+        let selected = self.popup.selected.selected().unwrap_or(0);
+        let Some(Action::EqBand { band }) =
+            self.popup.displayed_options.get(selected).map(|a| a.action.clone())
+        else {
+            return;
+        };
+        settings.gains[band] = (settings.gains[band] + delta).clamp(-EQ_MAX_GAIN, EQ_MAX_GAIN);
+        settings.preset = EQ_CUSTOM.to_string();
+        self.set_eq_settings(settings).await;
+        self.popup.current_menu = Some(self.equalizer_popup());
+    }
+
+    /// Opens the global popup straight on the seek to timestamp input
+    ///
+    pub fn open_seek_popup(&mut self) {
+        if self.state.active_section != ActiveSection::Popup {
+            self.state.last_section = self.state.active_section;
+            self.state.active_section = ActiveSection::Popup;
+        }
+        self.popup.global = true;
+        self.popup.current_menu = Some(PopupMenu::GlobalSeekTo { time: String::new() });
+        self.popup.editing_original = String::new();
+        self.popup.editing_new = String::new();
+        self.popup.selected.select_first();
+        self.popup.editing = true;
+    }
+
     /// Closes the popup including common state
     ///
     fn close_popup(&mut self) {
@@ -2623,6 +4238,14 @@ impl crate::tui::App {
                 self.popup.current_menu = Some(PopupMenu::GlobalRoot {
                     large_art: self.preferences.large_art,
                     downloading: self.download_item.is_some(),
+                    scrobbles_pending: self.scrobbles_pending,
+                    scrobbles_replayed: self.scrobbles_replayed,
+                    radio: self.state.radio.as_ref().map(|r| r.seed.name().to_string()),
+                    crossfade: self.transitions_on().then_some(self.transitions.crossfade),
+                    sleep_timer: self.state.sleep_timer.as_ref().map(|t| t.label()),
+                    pitch_correction: self.preferences.pitch_correction,
+                    shuffle_mode: self.preferences.shuffle_mode,
+                    smart_shuffle_favorites: self.preferences.smart_shuffle_favorites,
                 });
                 self.popup.selected.select_first();
             }
@@ -2630,6 +4253,30 @@ impl crate::tui::App {
             return Some(());
         }
 
+        // rows marked in visual mode take the place of the one under the cursor
+        if self.popup.current_menu.is_none() {
+            if let Some(list) = self
+                .active_selection()
+                .filter(|list| self.selection_list_for(self.state.last_section) == Some(*list))
+            {
+                self.popup.current_menu = Some(self.bulk_popup(list));
+                self.popup.selected.select_first();
+            }
+        }
+        // these don't depend on the section, the track info also opens from the queue and search
+        if matches!(
+            self.popup.current_menu,
+            Some(
+                PopupMenu::BulkRoot { .. }
+                    | PopupMenu::BulkAddToPlaylist { .. }
+                    | PopupMenu::BulkRemoveFromPlaylist { .. }
+                    | PopupMenu::TrackInfo { .. }
+            )
+        ) {
+            self.render_popup(frame);
+            return Some(());
+        }
+
         match self.state.active_tab {
             ActiveTab::Library => match self.state.last_section {
                 ActiveSection::Tracks => {
@@ -2694,8 +4341,11 @@ impl crate::tui::App {
                     if self.popup.current_menu.is_none() {
                         let id = self.get_id_of_selected(&self.playlists, Selectable::Playlist);
                         let playlist = self.playlists.iter().find(|p| p.id == id)?.clone();
-                        self.popup.current_menu =
-                            Some(PopupMenu::PlaylistRoot { playlist_name: playlist.name });
+                        self.popup.current_menu = if is_smart_playlist(&playlist.id) {
+                            Some(PopupMenu::SmartPlaylistRoot { playlist_name: playlist.name })
+                        } else {
+                            Some(PopupMenu::PlaylistRoot { playlist_name: playlist.name })
+                        };
                         self.popup.selected.select_first();
                     }
                 }
@@ -2715,6 +4365,12 @@ impl crate::tui::App {
                     self.close_popup();
                 }
             },
+            ActiveTab::Genres => {
+                if self.popup.current_menu.is_none() {
+                    self.popup.current_menu = self.genre_popup();
+                    self.popup.selected.select_first();
+                }
+            }
             _ => {
                 self.close_popup();
             }
@@ -2823,7 +4479,17 @@ impl crate::tui::App {
             let percent_height =
                 ((options.len() + 2) as f32 / window_height as f32 * 100.0).ceil() as u16;
 
-            let width = if let PopupMenu::GlobalRunScheduledTask { .. } = menu { 70 } else { 30 };
+            let width = match menu {
+                PopupMenu::GlobalRunScheduledTask { .. }
+                | PopupMenu::PlaylistImportReport { .. }
+                | PopupMenu::TrackInfo { .. } => 70,
+                PopupMenu::GlobalEqualizer { .. }
+                | PopupMenu::GlobalAudioDevice { .. }
+                | PopupMenu::GlobalQueue { .. }
+                | PopupMenu::PlaylistExport { .. }
+                | PopupMenu::PlaylistImport { .. } => 40,
+                _ => 30,
+            };
 
             let popup_area = popup_area(area, width, percent_height);
             frame.render_widget(Clear, popup_area); // clears the background
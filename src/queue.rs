@@ -1,7 +1,9 @@
 use crate::client::{Client, Transcoding};
 use crate::database::database::{Command, UpdateCommand};
+use crate::database::extension::{get_album_tracks, get_track};
 use crate::keyboard::search_ranked_refs;
-use crate::mpv::LoadFileFlag;
+use crate::mpv::{LoadFileFlag, MpvFile};
+use crate::smart_shuffle::ShuffleMode;
 use crate::{
     client::DiscographySong,
     database::extension::DownloadStatus,
@@ -15,7 +17,7 @@ use std::collections::HashMap;
 ///
 use std::sync::Arc;
 
-fn make_track(
+pub fn make_track(
     client: Option<&Arc<Client>>,
     downloads_dir: &std::path::PathBuf,
     track: &DiscographySong,
@@ -54,17 +56,36 @@ fn make_track(
         original_index: 0,
         run_time_ticks: track.run_time_ticks,
         disliked: track.disliked,
+        normalization_gain: track.normalization_gain,
     }
 }
 
 impl App {
+    /// Pairs an mpv-safe url with the replaygain adjustment for the song
+    ///
+    pub fn mpv_file(&self, song: &Song, url: String) -> MpvFile {
+        let album_gain = self
+            .original_albums
+            .iter()
+            .find(|a| a.id == song.album_id)
+            .map_or(0.0, |a| a.normalization_gain);
+        MpvFile {
+            url,
+            gain: self.replaygain.gain_db(song.normalization_gain, album_gain),
+            album_id: song.album_id.clone(),
+        }
+    }
+
     /// This is the main queue control function. It basically initiates a new queue when we play a song without modifiers
     ///
     pub async fn initiate_main_queue(&mut self, tracks: &[DiscographySong], skip: usize) {
         if tracks.is_empty() {
             return;
         }
+        self.remember_queue();
         let selected_is_album = tracks.get(skip).is_some_and(|t| t.id.starts_with("_album_"));
+        // a new queue replaces whatever the radio was playing
+        self.state.radio = None;
 
         // the playlist MPV will be getting
         self.state.queue = tracks
@@ -93,7 +114,7 @@ impl App {
             s.original_index = i as i64;
         }
 
-        if let Err(e) = self.start_new_queue().await {
+        if let Err(e) = self.start_new_queue(0).await {
             log::error!("Failed to start playlist: {}", e);
             self.set_generic_message("Failed to start playlist", &e.to_string());
             return;
@@ -114,14 +135,25 @@ impl App {
             .await;
     }
 
-    pub async fn start_new_queue(&mut self) -> std::result::Result<(), Box<dyn std::error::Error>> {
-        let songs = self.state.queue.clone();
+    /// Loads the whole queue into mpv. Songs that can't be played are dropped from the queue, so
+    /// its indices keep matching mpv's playlist. Returns where the song at `index` ended up
+    ///
+    pub async fn start_new_queue(
+        &mut self,
+        index: usize,
+    ) -> std::result::Result<usize, Box<dyn std::error::Error>> {
+        let songs = std::mem::take(&mut self.state.queue);
         let mut urls = Vec::with_capacity(songs.len());
+        let mut new_index = 0;
 
-        for song in &songs {
+        for (i, song) in songs.into_iter().enumerate() {
+            if i == index {
+                new_index = self.state.queue.len();
+            }
             match helpers::normalize_mpvsafe_url(&song.url) {
                 Ok(safe_url) => {
-                    urls.push(safe_url);
+                    urls.push(self.mpv_file(&song, safe_url));
+                    self.state.queue.push(song);
                 }
                 Err(e) => {
                     log::error!("Failed to normalize URL '{}': {:?}", song.url, e);
@@ -145,7 +177,7 @@ impl App {
         self.paused = false;
         self.song_changed = true;
 
-        Ok(())
+        Ok(new_index.min(self.state.queue.len().saturating_sub(1)))
     }
 
     /// Append the tracks to the end of the queue
@@ -155,6 +187,7 @@ impl App {
             self.initiate_main_queue(tracks, skip).await;
             return;
         }
+        self.remember_queue();
         let mut new_queue: Vec<Song> = Vec::new();
         for (i, track) in tracks.iter().enumerate().skip(skip) {
             if track.id.starts_with("_album_") {
@@ -181,7 +214,8 @@ impl App {
         for song in &new_queue {
             match helpers::normalize_mpvsafe_url(&song.url) {
                 Ok(safe_url) => {
-                    self.mpv_handle.load_files(vec![safe_url], LoadFileFlag::Append, None).await;
+                    let file = self.mpv_file(song, safe_url);
+                    self.mpv_handle.load_files(vec![file], LoadFileFlag::Append, None).await;
                 }
                 Err(e) => {
                     log::error!("Failed to normalize URL '{}': {:?}", song.url, e);
@@ -212,6 +246,7 @@ impl App {
             self.initiate_main_queue(tracks, skip).await;
             return;
         }
+        self.remember_queue();
 
         let mut songs: Vec<Song> = Vec::new();
         for i in 0..n {
@@ -248,9 +283,10 @@ impl App {
         for song in songs.iter().rev() {
             match helpers::normalize_mpvsafe_url(&song.url) {
                 Ok(safe_url) => {
+                    let file = self.mpv_file(song, safe_url);
                     self.mpv_handle
                         .load_files(
-                            vec![safe_url],
+                            vec![file],
                             LoadFileFlag::InsertAt,
                             Some(selected_queue_item + 1),
                         )
@@ -282,6 +318,7 @@ impl App {
             self.initiate_main_queue(tracks, skip).await;
             return;
         }
+        self.remember_queue();
         let selected_queue_item = self.state.selected_queue_item.selected().unwrap_or(0);
         // if we shift click we only appned the selected track to the playlist
         let track = &tracks[skip];
@@ -295,7 +332,8 @@ impl App {
 
         match helpers::normalize_mpvsafe_url(&song.url) {
             Ok(safe_url) => {
-                self.mpv_handle.load_files(vec![safe_url], LoadFileFlag::InsertNext, None).await;
+                let file = self.mpv_file(&song, safe_url);
+                self.mpv_handle.load_files(vec![file], LoadFileFlag::InsertNext, None).await;
                 self.state.queue.insert(selected_queue_item + 1, song);
             }
             Err(e) => {
@@ -318,6 +356,36 @@ impl App {
         // println!("{:?}", self.state.queue.get(1).unwrap().url == second);
     }
 
+    /// Play all tracks right after the selected queue item, keeping their order
+    ///
+    pub async fn push_all_next_to_temporary_queue(&mut self, tracks: &Vec<DiscographySong>) {
+        if self.state.queue.is_empty() {
+            self.initiate_main_queue(tracks, 0).await;
+            return;
+        }
+        for i in (0..tracks.len()).rev() {
+            self.push_next_to_temporary_queue(tracks, i).await;
+        }
+    }
+
+    /// Resolves an id coming from outside the UI (remote control) to a single track,
+    /// or to all tracks of an album
+    ///
+    pub async fn tracks_by_id(&self, id: &str) -> Vec<DiscographySong> {
+        if let Ok(Some(track)) = get_track(&self.db.pool, id, self.client.as_ref()).await {
+            return vec![track];
+        }
+        if let Ok(tracks) = get_album_tracks(&self.db.pool, id, self.client.as_ref()).await {
+            if !tracks.is_empty() {
+                return tracks;
+            }
+        }
+        match &self.client {
+            Some(client) => client.album_tracks(id).await.unwrap_or_default(),
+            None => vec![],
+        }
+    }
+
     async fn push_album_to_temporary_queue(&mut self, start: bool) {
         let selected = self.state.selected_track.selected().unwrap_or(0);
         let refs = search_ranked_refs(&self.tracks, &self.state.tracks_search_term, true);
@@ -352,12 +420,9 @@ impl App {
                 true,
                 &self.transcoding,
             );
+            let file = self.mpv_file(&song, song.url.clone());
             self.mpv_handle
-                .load_files(
-                    vec![song.url.clone()],
-                    LoadFileFlag::InsertAt,
-                    Some(selected_queue_item + 1),
-                )
+                .load_files(vec![file], LoadFileFlag::InsertAt, Some(selected_queue_item + 1))
                 .await;
 
             self.state.queue.insert((selected_queue_item + 1) as usize, song);
@@ -376,6 +441,7 @@ impl App {
             None => return,
         };
 
+        self.remember_queue();
         self.mpv_handle.playlist_remove(selected_queue_item).await;
         self.state.queue.remove(selected_queue_item);
     }
@@ -391,6 +457,9 @@ impl App {
                 to_remove.push(i);
             }
         }
+        if !to_remove.is_empty() {
+            self.remember_queue();
+        }
         for i in to_remove.iter().rev() {
             self.mpv_handle.playlist_remove(*i).await;
             self.state.queue.remove(*i);
@@ -404,6 +473,7 @@ impl App {
             return;
         }
 
+        self.remember_queue();
         for i in (0..self.state.queue.len()).rev() {
             if self.state.queue[i].is_in_queue {
                 self.mpv_handle.playlist_remove(i).await;
@@ -439,6 +509,7 @@ impl App {
             return;
         }
 
+        self.remember_queue();
         // remove all queued songs from mpv + local queue
         for i in (0..self.state.queue.len()).rev() {
             if let Some(song) = self.state.queue.get(i) {
@@ -473,7 +544,8 @@ impl App {
                 (LoadFileFlag::InsertAt, Some(insert_pos as i64))
             };
 
-            self.mpv_handle.load_files(vec![safe_url], flag, pos).await;
+            let file = self.mpv_file(&song, safe_url);
+            self.mpv_handle.load_files(vec![file], flag, pos).await;
 
             if pos.is_some() {
                 self.state.queue.insert(insert_pos, song);
@@ -507,6 +579,7 @@ impl App {
                 }
             }
 
+            self.remember_queue();
             self.mpv_handle.playlist_move(selected_queue_item, selected_queue_item - 1).await;
 
             self.state.selected_queue_item.select(Some(selected_queue_item - 1));
@@ -545,6 +618,7 @@ impl App {
                 }
             }
 
+            self.remember_queue();
             self.mpv_handle.playlist_move(selected_queue_item + 1, selected_queue_item).await;
 
             self.state.queue.swap(selected_queue_item, selected_queue_item + 1);
@@ -582,6 +656,7 @@ impl App {
         if start >= len {
             return;
         }
+        self.remember_queue();
 
         let mut temp: Vec<Song> = Vec::new();
         let mut rest: Vec<Song> = Vec::new();
@@ -633,8 +708,14 @@ impl App {
 
         // shuffle the rest
         let mut local_current: Vec<Song> = self.state.queue[shuffle_from..].to_vec();
-        let mut desired_order = local_current.clone();
-        desired_order.shuffle(&mut rand::rng());
+        let desired_order = match self.preferences.shuffle_mode {
+            ShuffleMode::Random => {
+                let mut order = local_current.clone();
+                order.shuffle(&mut rand::rng());
+                order
+            }
+            ShuffleMode::Smart => self.smart_shuffled(local_current.clone()).await,
+        };
 
         for i in 0..desired_order.len() {
             if let Some(j) = local_current.iter().position(|s| s.id == desired_order[i].id) {
@@ -674,6 +755,7 @@ impl App {
         if start >= len {
             return;
         }
+        self.remember_queue();
 
         let mut temp: Vec<Song> = Vec::new();
         let mut rest: Vec<Song> = Vec::new();
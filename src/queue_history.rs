@@ -0,0 +1,253 @@
+/* --------------------------
+Queue history
+    - Every queue change made from the UI first saves what the queue looked like, `u` goes back to
+      it and ctrl+r forward again
+    - Everything done while handling one key press (or one MPRIS, ctl or remote command) is a
+      single step, so an operation made of several smaller ones is undone in one go
+    - Restoring keeps the current track playing when it's part of the restored queue, mpv's
+      playlist is rebuilt around it
+    - Bounded to QUEUE_HISTORY_DEPTH steps each way. Stored in its own file next to the state and
+      only written when it changed, the state autosave stays small
+-------------------------- */
+
+use crate::helpers;
+use crate::mpv::{LoadFileFlag, MpvFile};
+use crate::tui::{App, Song};
+use dirs::data_dir;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::OpenOptions;
+
+/// How many steps can be undone (and redone)
+const QUEUE_HISTORY_DEPTH: usize = 20;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct QueueSnapshot {
+    queue: Vec<Song>,
+    current_index: usize,
+}
+
+impl QueueSnapshot {
+    fn same_as(&self, other: &QueueSnapshot) -> bool {
+        self.current_index == other.current_index
+            && self.queue.len() == other.queue.len()
+            && self.queue.iter().zip(other.queue.iter()).all(|(a, b)| a.id == b.id)
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct QueueHistory {
+    undo: VecDeque<QueueSnapshot>,
+    redo: VecDeque<QueueSnapshot>,
+    /// a snapshot was already taken for the current step
+    #[serde(skip)]
+    step_open: bool,
+    /// not written to disk since the last change
+    #[serde(skip)]
+    changed: bool,
+}
+
+impl QueueHistory {
+    fn record(&mut self, snapshot: QueueSnapshot) {
+        if self.step_open {
+            return;
+        }
+        self.step_open = true;
+        if self.undo.back().is_some_and(|last| last.same_as(&snapshot)) {
+            return;
+        }
+        Self::push(&mut self.undo, snapshot);
+        self.redo.clear();
+        self.changed = true;
+    }
+
+    fn push(stack: &mut VecDeque<QueueSnapshot>, snapshot: QueueSnapshot) {
+        stack.push_back(snapshot);
+        while stack.len() > QUEUE_HISTORY_DEPTH {
+            stack.pop_front();
+        }
+    }
+
+    /// Called once per main loop iteration, before input is handled
+    ///
+    pub fn end_step(&mut self) {
+        self.step_open = false;
+    }
+
+    /// Changes the app makes on its own (like the radio topping up the queue) aren't steps
+    ///
+    pub fn skip_step(&mut self) {
+        self.step_open = true;
+    }
+
+    pub fn songs_mut(&mut self) -> impl Iterator<Item = &mut Song> {
+        self.undo.iter_mut().chain(self.redo.iter_mut()).flat_map(|s| s.queue.iter_mut())
+    }
+
+    fn filename(server_id: &String, offline: bool) -> String {
+        if offline {
+            format!("offline_{}_queue_history.json", server_id)
+        } else {
+            format!("{}_queue_history.json", server_id)
+        }
+    }
+
+    /// Written next to the state, but only when something changed since the last save
+    ///
+    pub fn save(
+        &mut self,
+        server_id: &String,
+        offline: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.changed {
+            return Ok(());
+        }
+        let states_dir = data_dir().unwrap().join("jellyfin-tui").join("states");
+        let filename = Self::filename(server_id, offline);
+        let final_path = states_dir.join(&filename);
+        let tmp_path = states_dir.join(format!("{}.tmp", filename));
+        {
+            let file =
+                OpenOptions::new().create(true).write(true).truncate(true).open(&tmp_path)?;
+            serde_json::to_writer(file, &self)?;
+        }
+        std::fs::rename(&tmp_path, &final_path)?;
+        self.changed = false;
+        Ok(())
+    }
+
+    pub fn load(
+        server_id: &String,
+        offline: bool,
+    ) -> Result<QueueHistory, Box<dyn std::error::Error>> {
+        let states_dir = data_dir().unwrap().join("jellyfin-tui").join("states");
+        match OpenOptions::new()
+            .read(true)
+            .open(states_dir.join(Self::filename(server_id, offline)))
+        {
+            Ok(file) => Ok(serde_json::from_reader(file)?),
+            Err(_) => Ok(QueueHistory::default()),
+        }
+    }
+}
+
+impl App {
+    fn queue_snapshot(&self) -> QueueSnapshot {
+        QueueSnapshot {
+            queue: self.state.queue.clone(),
+            current_index: self.state.current_playback_state.current_index,
+        }
+    }
+
+    /// Saves the queue before it is changed. Only the first call of a step is kept
+    ///
+    pub fn remember_queue(&mut self) {
+        let snapshot = self.queue_snapshot();
+        self.queue_history.record(snapshot);
+    }
+
+    pub async fn undo_queue(&mut self) {
+        let Some(snapshot) = self.queue_history.undo.pop_back() else {
+            return;
+        };
+        let current = self.queue_snapshot();
+        QueueHistory::push(&mut self.queue_history.redo, current);
+        self.queue_history.changed = true;
+        self.restore_queue(snapshot).await;
+    }
+
+    pub async fn redo_queue(&mut self) {
+        let Some(snapshot) = self.queue_history.redo.pop_back() else {
+            return;
+        };
+        let current = self.queue_snapshot();
+        QueueHistory::push(&mut self.queue_history.undo, current);
+        self.queue_history.changed = true;
+        self.restore_queue(snapshot).await;
+    }
+
+    async fn restore_queue(&mut self, snapshot: QueueSnapshot) {
+        // undo and redo are a step of their own, nothing they call should record another one
+        self.queue_history.skip_step();
+        self.dirty = true;
+
+        let current_index = self.state.current_playback_state.current_index;
+        let playing = self.state.queue.get(current_index).map(|s| s.id.clone());
+        // the playing track, if the restored queue has it. The occurrence closest to where it was
+        let keep = playing.filter(|_| !self.stopped).and_then(|id| {
+            snapshot
+                .queue
+                .iter()
+                .enumerate()
+                .filter(|(_, s)| s.id == id)
+                .min_by_key(|(i, _)| i.abs_diff(snapshot.current_index))
+                .map(|(i, _)| i)
+        });
+
+        let Some(keep) = keep else {
+            // nothing to hold on to, start the restored queue where it was
+            if snapshot.queue.is_empty() {
+                self.stop().await;
+                return;
+            }
+            self.state.queue = snapshot.queue;
+            let index = match self.start_new_queue(snapshot.current_index).await {
+                Ok(index) => index,
+                Err(e) => {
+                    log::error!("Failed to restore the queue: {}", e);
+                    return;
+                }
+            };
+            self.mpv_handle.play_index(index).await;
+            self.state.current_playback_state.current_index = index;
+            self.state.selected_queue_item.select(Some(index));
+            return;
+        };
+
+        // everything but the current track goes, then the restored queue is built around it
+        for i in (0..self.state.queue.len()).rev() {
+            if i != current_index {
+                self.mpv_handle.playlist_remove(i).await;
+            }
+        }
+        let mut queue = snapshot.queue;
+        let after = queue.split_off(keep + 1);
+        let current = queue.pop();
+        let mut restored = Vec::with_capacity(queue.len() + after.len() + 1);
+        for song in queue {
+            let Some(file) = self.restore_file(&song) else {
+                continue;
+            };
+            let at = restored.len() as i64;
+            self.mpv_handle.load_files(vec![file], LoadFileFlag::InsertAt, Some(at)).await;
+            restored.push(song);
+        }
+        let index = restored.len();
+        restored.extend(current);
+        for song in after {
+            let Some(file) = self.restore_file(&song) else {
+                continue;
+            };
+            self.mpv_handle.load_files(vec![file], LoadFileFlag::Append, None).await;
+            restored.push(song);
+        }
+
+        self.state.queue = restored;
+        self.state.current_playback_state.current_index = index;
+        let selected = self.state.selected_queue_item.selected().unwrap_or(index);
+        self.state.selected_queue_item.select(Some(selected.min(self.state.queue.len() - 1)));
+
+        // discard next poll
+        let _ = self.receiver.try_recv();
+    }
+
+    fn restore_file(&self, song: &Song) -> Option<MpvFile> {
+        match helpers::normalize_mpvsafe_url(&song.url) {
+            Ok(url) => Some(self.mpv_file(song, url)),
+            Err(e) => {
+                log::error!("Failed to normalize URL '{}': {:?}", song.url, e);
+                None
+            }
+        }
+    }
+}
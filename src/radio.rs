@@ -0,0 +1,257 @@
+/* --------------------------
+Radio mode
+    - Started from a track, album, artist or genre. Keeps appending similar tracks to the main queue
+      whenever it is about to run out
+    - Online the tracks come from Jellyfin's InstantMix, offline (or when that fails) from a local
+      heuristic over the genres, artists and year of the seed
+    - Disliked tracks and tracks already in the queue are never added
+    - Starting any other queue ends the radio
+-------------------------- */
+
+use crate::client::DiscographySong;
+use crate::database::extension::{
+    get_album_tracks, get_discography, get_disliked_ids, get_radio_candidates, get_track,
+};
+use crate::keyboard::{ActiveSection, ActiveTab, Selectable};
+use crate::tui::App;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// How many tracks are fetched at once
+const RADIO_BATCH: usize = 25;
+/// More tracks are fetched once fewer than this many are left to play
+const RADIO_LOW_WATER: usize = 5;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RadioSeed {
+    Track { id: String, name: String },
+    Album { id: String, name: String },
+    Artist { id: String, name: String },
+    Genre { name: String },
+}
+
+impl RadioSeed {
+    pub fn name(&self) -> &str {
+        match self {
+            RadioSeed::Track { name, .. }
+            | RadioSeed::Album { name, .. }
+            | RadioSeed::Artist { name, .. }
+            | RadioSeed::Genre { name } => name,
+        }
+    }
+}
+
+/// What the local heuristic compares candidates against
+///
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RadioProfile {
+    pub genres: Vec<String>,
+    pub artists: Vec<String>,
+    pub year: u64,
+}
+
+impl RadioProfile {
+    /// The most common genres and artists of the seed tracks, and their median year
+    ///
+    pub fn from_tracks(tracks: &[DiscographySong]) -> Self {
+        fn most_common(values: impl Iterator<Item = String>, n: usize) -> Vec<String> {
+            let mut counts: HashMap<String, usize> = HashMap::new();
+            for value in values {
+                *counts.entry(value).or_default() += 1;
+            }
+            let mut counts = counts.into_iter().collect::<Vec<_>>();
+            counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            counts.into_iter().take(n).map(|(value, _)| value).collect()
+        }
+
+        let mut years =
+            tracks.iter().map(|t| t.production_year).filter(|y| *y > 0).collect::<Vec<u64>>();
+        years.sort_unstable();
+
+        Self {
+            genres: most_common(tracks.iter().flat_map(|t| t.genres.iter().cloned()), 3),
+            artists: most_common(tracks.iter().flat_map(|t| t.artists.iter().cloned()), 3),
+            year: years.get(years.len() / 2).copied().unwrap_or(0),
+        }
+    }
+
+    /// Shared genres weigh the most, then shared artists, then how close the year is
+    ///
+    fn score(&self, track: &DiscographySong) -> f64 {
+        let has =
+            |list: &[String], value: &String| list.iter().any(|v| v.eq_ignore_ascii_case(value));
+        let genres = track.genres.iter().filter(|g| has(&self.genres, g)).count() as f64;
+        let artists = track.artists.iter().filter(|a| has(&self.artists, a)).count() as f64;
+        let year = match (self.year, track.production_year) {
+            (0, _) | (_, 0) => 0.0,
+            (a, b) => (1.0 - a.abs_diff(b) as f64 / 10.0).max(0.0),
+        };
+        genres * 3.0 + artists * 2.0 + year
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Radio {
+    pub seed: RadioSeed,
+    pub profile: RadioProfile,
+}
+
+impl App {
+    /// Radio seed for whatever the popup was opened on
+    ///
+    pub async fn radio_seed_from_selection(&self) -> Option<(RadioSeed, RadioProfile)> {
+        let client = self.client.clone();
+        let track_seed = |track: &DiscographySong| {
+            (
+                RadioSeed::Track { id: track.id.clone(), name: track.name.clone() },
+                RadioProfile::from_tracks(std::slice::from_ref(track)),
+            )
+        };
+        match (self.state.active_tab, self.state.last_section) {
+            (ActiveTab::Library, ActiveSection::Tracks) => {
+                let id = self.get_id_of_selected(&self.tracks, Selectable::Track);
+                let track = self.tracks.iter().find(|t| t.id == id)?;
+                if let Some(album_id) = id.strip_prefix("_album_") {
+                    let tracks =
+                        get_album_tracks(&self.db.pool, album_id, client.as_ref()).await.ok()?;
+                    return Some((
+                        RadioSeed::Album { id: album_id.to_string(), name: track.album.clone() },
+                        RadioProfile::from_tracks(&tracks),
+                    ));
+                }
+                Some(track_seed(track))
+            }
+            (ActiveTab::Library, _) => {
+                let id = self.get_id_of_selected(&self.artists, Selectable::Artist);
+                let artist = self.artists.iter().find(|a| a.id == id)?.clone();
+                let tracks = get_discography(&self.db.pool, &id, client.as_ref()).await.ok()?;
+                Some((
+                    RadioSeed::Artist { id, name: artist.name },
+                    RadioProfile::from_tracks(&tracks),
+                ))
+            }
+            (ActiveTab::Albums, ActiveSection::Tracks) => {
+                let id = self.get_id_of_selected(&self.album_tracks, Selectable::AlbumTrack);
+                self.album_tracks.iter().find(|t| t.id == id).map(track_seed)
+            }
+            (ActiveTab::Albums, _) => {
+                let id = self.get_id_of_selected(&self.albums, Selectable::Album);
+                let album = self.albums.iter().find(|a| a.id == id)?.clone();
+                let tracks = get_album_tracks(&self.db.pool, &id, client.as_ref()).await.ok()?;
+                Some((
+                    RadioSeed::Album { id, name: album.name },
+                    RadioProfile::from_tracks(&tracks),
+                ))
+            }
+            (ActiveTab::Playlists, ActiveSection::Tracks) => {
+                let id = self.get_id_of_selected(&self.playlist_tracks, Selectable::PlaylistTrack);
+                self.playlist_tracks.iter().find(|t| t.id == id).map(track_seed)
+            }
+            (ActiveTab::Genres, _) => {
+                let name = match self.state.last_section {
+                    ActiveSection::Tracks => self.state.current_genre.clone(),
+                    _ => self.selected_genre()?.name.clone(),
+                };
+                let profile = RadioProfile { genres: vec![name.clone()], ..Default::default() };
+                Some((RadioSeed::Genre { name }, profile))
+            }
+            _ => None,
+        }
+    }
+
+    /// Replaces the main queue with the first batch of the radio
+    ///
+    pub async fn start_radio(&mut self, seed: RadioSeed, profile: RadioProfile) {
+        let radio = Radio { seed, profile };
+        let mut tracks = self.radio_tracks(&radio, &HashSet::new()).await;
+        // a track radio starts with the track itself
+        if let RadioSeed::Track { id, .. } = &radio.seed {
+            tracks.retain(|t| &t.id != id);
+            if let Ok(Some(track)) = get_track(&self.db.pool, id, self.client.as_ref()).await {
+                tracks.insert(0, track);
+            }
+        }
+        if tracks.is_empty() {
+            self.set_generic_message(
+                "Radio",
+                &format!("Could not find any tracks similar to {}.", radio.seed.name()),
+            );
+            return;
+        }
+        self.initiate_main_queue(&tracks, 0).await;
+        self.state.radio = Some(radio);
+    }
+
+    pub fn stop_radio(&mut self) {
+        self.state.radio = None;
+    }
+
+    /// Called on every song change, appends the next batch when the queue is about to end
+    ///
+    pub async fn top_up_radio(&mut self) {
+        let Some(radio) = self.state.radio.clone() else {
+            return;
+        };
+        let remaining = self
+            .state
+            .queue
+            .len()
+            .saturating_sub(self.state.current_playback_state.current_index + 1);
+        if remaining >= RADIO_LOW_WATER {
+            return;
+        }
+        let queued = self.state.queue.iter().map(|s| s.id.clone()).collect::<HashSet<String>>();
+        let tracks = self.radio_tracks(&radio, &queued).await;
+        if tracks.is_empty() {
+            log::warn!("Radio '{}' ran out of new tracks", radio.seed.name());
+            return;
+        }
+        // not something to undo
+        self.queue_history.skip_step();
+        self.append_to_main_queue(&tracks, 0).await;
+    }
+
+    /// The next batch of radio tracks, without disliked ones or anything in `exclude`
+    ///
+    async fn radio_tracks(&self, radio: &Radio, exclude: &HashSet<String>) -> Vec<DiscographySong> {
+        let disliked = get_disliked_ids(&self.db.pool).await.unwrap_or_default();
+        let keep = |t: &DiscographySong| !exclude.contains(&t.id) && !disliked.contains(&t.id);
+
+        if let Some(client) = &self.client {
+            match client.instant_mix(&radio.seed, RADIO_BATCH * 2).await {
+                Ok(tracks) => {
+                    let tracks =
+                        tracks.into_iter().filter(keep).take(RADIO_BATCH).collect::<Vec<_>>();
+                    if !tracks.is_empty() {
+                        return tracks;
+                    }
+                }
+                Err(e) => log::warn!("InstantMix failed, using local radio instead: {}", e),
+            }
+        }
+
+        let candidates = match get_radio_candidates(
+            &self.db.pool,
+            &radio.profile,
+            self.client.is_some(),
+        )
+        .await
+        {
+            Ok(candidates) => candidates,
+            Err(e) => {
+                log::error!("Failed to load radio candidates: {}", e);
+                return vec![];
+            }
+        };
+        // a bit of noise so the same seed doesn't always play the same tracks
+        let mut rng = rand::rng();
+        let mut scored = candidates
+            .into_iter()
+            .filter(keep)
+            .map(|t| (radio.profile.score(&t) + rng.random_range(0.0..1.5), t))
+            .collect::<Vec<_>>();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        scored.into_iter().take(RADIO_BATCH).map(|(_, t)| t).collect()
+    }
+}
@@ -0,0 +1,375 @@
+/* --------------------------
+Jellyfin remote control session
+    - Registers jellyfin-tui as a controllable session, so the web UI or phone apps can control it
+    - The server pushes commands over its websocket, we forward them to the App like MPRIS events
+    - The websocket url is derived from the client's base url, so a local mock server can stand in for Jellyfin
+-------------------------- */
+
+use crate::client::Client;
+use crate::mpv::SeekFlag;
+use crate::tui::App;
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::Message;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlayCommand {
+    Now,
+    Next,
+    Last,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RemoteCommand {
+    PlayPause,
+    Pause,
+    Unpause,
+    Stop,
+    NextTrack,
+    PreviousTrack,
+    /// Position in seconds
+    Seek(f64),
+    Play {
+        item_ids: Vec<String>,
+        start_index: usize,
+        command: PlayCommand,
+    },
+    SetVolume(i64),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Incoming {
+    Command(RemoteCommand),
+    /// The server drops the session unless we send a keep alive within this many seconds
+    ForceKeepAlive(u64),
+    Ignored,
+}
+
+#[derive(Deserialize)]
+struct WebSocketMessage {
+    #[serde(rename = "MessageType")]
+    message_type: String,
+    #[serde(rename = "Data", default)]
+    data: serde_json::Value,
+}
+
+/// Translates a websocket message from the server. Kept free of any IO so it can be checked on its own.
+///
+pub fn parse_message(text: &str) -> Incoming {
+    let Ok(message) = serde_json::from_str::<WebSocketMessage>(text) else {
+        return Incoming::Ignored;
+    };
+    let data = &message.data;
+
+    let command = match message.message_type.as_str() {
+        "ForceKeepAlive" => return Incoming::ForceKeepAlive(data.as_u64().unwrap_or(60)),
+        "Playstate" => match data["Command"].as_str().unwrap_or_default() {
+            "PlayPause" => RemoteCommand::PlayPause,
+            "Pause" => RemoteCommand::Pause,
+            "Unpause" => RemoteCommand::Unpause,
+            "Stop" => RemoteCommand::Stop,
+            "NextTrack" => RemoteCommand::NextTrack,
+            "PreviousTrack" => RemoteCommand::PreviousTrack,
+            "Seek" => RemoteCommand::Seek(
+                data["SeekPositionTicks"].as_u64().unwrap_or(0) as f64 / 10_000_000.0,
+            ),
+            _ => return Incoming::Ignored,
+        },
+        "Play" => {
+            let command = match data["PlayCommand"].as_str().unwrap_or("PlayNow") {
+                "PlayNext" => PlayCommand::Next,
+                "PlayLast" => PlayCommand::Last,
+                _ => PlayCommand::Now,
+            };
+            let item_ids = data["ItemIds"]
+                .as_array()
+                .map(|ids| ids.iter().filter_map(|id| id.as_str().map(String::from)).collect())
+                .unwrap_or_default();
+            let start_index = data["StartIndex"].as_u64().unwrap_or(0) as usize;
+            RemoteCommand::Play { item_ids, start_index, command }
+        }
+        "GeneralCommand" if data["Name"] == "SetVolume" => {
+            // arguments are always sent as strings
+            match data["Arguments"]["Volume"].as_str().and_then(|v| v.parse::<i64>().ok()) {
+                Some(volume) => RemoteCommand::SetVolume(volume.clamp(0, 100)),
+                None => return Incoming::Ignored,
+            }
+        }
+        _ => return Incoming::Ignored,
+    };
+    Incoming::Command(command)
+}
+
+/// Keeps a remote control session open for the lifetime of the program, reconnecting when it drops
+///
+pub fn spawn_session(client: Arc<Client>, tx: std::sync::mpsc::Sender<RemoteCommand>) {
+    tokio::spawn(async move {
+        let mut backoff = Duration::from_secs(5);
+        loop {
+            match run_session(&client, &tx).await {
+                Ok(true) => {
+                    log::info!("Remote control session closed by the server, reconnecting");
+                    backoff = Duration::from_secs(5);
+                }
+                // the app is gone
+                Ok(false) => return,
+                Err(e) => {
+                    log::warn!("Remote control session error: {}", e);
+                }
+            }
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(Duration::from_secs(120));
+        }
+    });
+}
+
+/// Returns Ok(false) once the App stopped listening
+///
+async fn run_session(
+    client: &Client,
+    tx: &std::sync::mpsc::Sender<RemoteCommand>,
+) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+    client.post_capabilities().await?;
+    run_socket(&client.websocket_url(), tx).await
+}
+
+/// The websocket half of a session, forwards commands until the socket or the App goes away
+///
+async fn run_socket(
+    url: &str,
+    tx: &std::sync::mpsc::Sender<RemoteCommand>,
+) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+    let (mut socket, _) = tokio_tungstenite::connect_async(url).await?;
+    log::info!("Remote control session connected");
+
+    let mut keep_alive = tokio::time::interval(Duration::from_secs(30));
+    loop {
+        tokio::select! {
+            _ = keep_alive.tick() => {
+                socket.send(Message::Text(r#"{"MessageType":"KeepAlive"}"#.into())).await?;
+            }
+            message = socket.next() => {
+                let Some(message) = message else {
+                    return Ok(true);
+                };
+                let text = match message? {
+                    Message::Text(text) => text,
+                    Message::Close(_) => return Ok(true),
+                    // pings are answered by tungstenite
+                    _ => continue,
+                };
+                match parse_message(text.as_str()) {
+                    Incoming::Command(command) => {
+                        if tx.send(command).is_err() {
+                            return Ok(false);
+                        }
+                    }
+                    Incoming::ForceKeepAlive(timeout) => {
+                        let period = Duration::from_secs((timeout / 2).max(5));
+                        keep_alive = tokio::time::interval(period);
+                    }
+                    Incoming::Ignored => {}
+                }
+            }
+        }
+    }
+}
+
+impl App {
+    pub async fn handle_remote_events(&mut self) {
+        while let Ok(command) = self.remote_rx.try_recv() {
+            // each command is an undo step of its own
+            self.queue_history.end_step();
+            self.dirty = true;
+            match command {
+                RemoteCommand::PlayPause => {
+                    if self.paused {
+                        self.play().await;
+                    } else {
+                        self.pause().await;
+                    }
+                }
+                RemoteCommand::Pause => self.pause().await,
+                RemoteCommand::Unpause => self.play().await,
+                RemoteCommand::Stop => self.stop().await,
+                RemoteCommand::NextTrack => self.next().await,
+                RemoteCommand::PreviousTrack => self.previous().await,
+                RemoteCommand::Seek(secs) => {
+                    if self.stopped {
+                        continue;
+                    }
+                    self.update_mpris_position(secs);
+                    self.mpv_handle.seek(secs, SeekFlag::Absolute).await;
+                }
+                RemoteCommand::SetVolume(volume) => {
+                    self.state.current_playback_state.volume = volume;
+                    self.mpv_handle.set_volume(volume).await;
+                    #[cfg(target_os = "linux")]
+                    {
+                        if let Some(ref mut controls) = self.controls {
+                            let _ = controls.set_volume(volume as f64 / 100.0);
+                        }
+                    }
+                }
+                RemoteCommand::Play { item_ids, start_index, command } => {
+                    // albums and playlists expand into several tracks, the start index is an item index
+                    let mut tracks = vec![];
+                    let mut start = 0;
+                    for (i, id) in item_ids.iter().enumerate() {
+                        if i == start_index {
+                            start = tracks.len();
+                        }
+                        tracks.extend(self.tracks_by_id(id).await);
+                    }
+                    if tracks.is_empty() {
+                        log::warn!("Remote play command with no playable items: {:?}", item_ids);
+                        continue;
+                    }
+                    match command {
+                        PlayCommand::Now => {
+                            self.initiate_main_queue(&tracks, start.min(tracks.len() - 1)).await
+                        }
+                        PlayCommand::Next => self.push_all_next_to_temporary_queue(&tracks).await,
+                        PlayCommand::Last => self.append_to_main_queue(&tracks, 0).await,
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn parses_playstate_commands() {
+        let msg = |command: &str| {
+            format!(r#"{{"MessageType":"Playstate","Data":{{"Command":"{}"}}}}"#, command)
+        };
+        assert_eq!(parse_message(&msg("PlayPause")), Incoming::Command(RemoteCommand::PlayPause));
+        assert_eq!(parse_message(&msg("Pause")), Incoming::Command(RemoteCommand::Pause));
+        assert_eq!(parse_message(&msg("Unpause")), Incoming::Command(RemoteCommand::Unpause));
+        assert_eq!(parse_message(&msg("Stop")), Incoming::Command(RemoteCommand::Stop));
+        assert_eq!(parse_message(&msg("NextTrack")), Incoming::Command(RemoteCommand::NextTrack));
+        assert_eq!(
+            parse_message(&msg("PreviousTrack")),
+            Incoming::Command(RemoteCommand::PreviousTrack)
+        );
+        assert_eq!(parse_message(&msg("Rewind")), Incoming::Ignored);
+    }
+
+    #[test]
+    fn parses_seek_in_seconds() {
+        let text = r#"{"MessageType":"Playstate","Data":{"Command":"Seek","SeekPositionTicks":305000000}}"#;
+        assert_eq!(parse_message(text), Incoming::Command(RemoteCommand::Seek(30.5)));
+    }
+
+    #[test]
+    fn parses_play_commands() {
+        let text = r#"{"MessageType":"Play","Data":{"ItemIds":["a","b"],"StartIndex":1,"PlayCommand":"PlayNext"}}"#;
+        assert_eq!(
+            parse_message(text),
+            Incoming::Command(RemoteCommand::Play {
+                item_ids: vec!["a".into(), "b".into()],
+                start_index: 1,
+                command: PlayCommand::Next,
+            })
+        );
+
+        // missing fields fall back to playing everything from the start
+        let text = r#"{"MessageType":"Play","Data":{"ItemIds":["a"]}}"#;
+        assert_eq!(
+            parse_message(text),
+            Incoming::Command(RemoteCommand::Play {
+                item_ids: vec!["a".into()],
+                start_index: 0,
+                command: PlayCommand::Now,
+            })
+        );
+    }
+
+    #[test]
+    fn parses_set_volume() {
+        let msg = |volume: &str| {
+            format!(
+                r#"{{"MessageType":"GeneralCommand","Data":{{"Name":"SetVolume","Arguments":{{"Volume":"{}"}}}}}}"#,
+                volume
+            )
+        };
+        assert_eq!(parse_message(&msg("40")), Incoming::Command(RemoteCommand::SetVolume(40)));
+        assert_eq!(parse_message(&msg("250")), Incoming::Command(RemoteCommand::SetVolume(100)));
+        assert_eq!(parse_message(&msg("loud")), Incoming::Ignored);
+    }
+
+    #[test]
+    fn parses_keep_alive_and_ignores_the_rest() {
+        assert_eq!(
+            parse_message(r#"{"MessageType":"ForceKeepAlive","Data":60}"#),
+            Incoming::ForceKeepAlive(60)
+        );
+        assert_eq!(parse_message(r#"{"MessageType":"KeepAlive"}"#), Incoming::Ignored);
+        assert_eq!(
+            parse_message(r#"{"MessageType":"UserDataChanged","Data":{}}"#),
+            Incoming::Ignored
+        );
+        assert_eq!(parse_message("not json"), Incoming::Ignored);
+    }
+
+    /// Accepts one websocket connection and plays the server side of a short session
+    async fn mock_server(messages: Vec<&'static str>) -> (String, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}/socket", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+            // the keep alive timer fires right away
+            let first = socket.next().await.unwrap().unwrap().into_text().unwrap().to_string();
+            for message in messages {
+                socket.send(Message::Text(message.into())).await.unwrap();
+            }
+            socket.close(None).await.unwrap();
+            first
+        });
+        (url, server)
+    }
+
+    #[tokio::test]
+    async fn session_forwards_commands_until_closed() {
+        let (url, server) = mock_server(vec![
+            r#"{"MessageType":"ForceKeepAlive","Data":60}"#,
+            r#"{"MessageType":"Playstate","Data":{"Command":"Pause"}}"#,
+            r#"{"MessageType":"UserDataChanged","Data":{}}"#,
+            r#"{"MessageType":"Play","Data":{"ItemIds":["a"],"PlayCommand":"PlayLast"}}"#,
+        ])
+        .await;
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        assert!(run_socket(&url, &tx).await.unwrap());
+        assert_eq!(server.await.unwrap(), r#"{"MessageType":"KeepAlive"}"#);
+        assert_eq!(
+            rx.try_iter().collect::<Vec<_>>(),
+            vec![
+                RemoteCommand::Pause,
+                RemoteCommand::Play {
+                    item_ids: vec!["a".into()],
+                    start_index: 0,
+                    command: PlayCommand::Last,
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn session_ends_when_the_app_is_gone() {
+        let (url, _server) =
+            mock_server(vec![r#"{"MessageType":"Playstate","Data":{"Command":"Stop"}}"#]).await;
+        let (tx, rx) = std::sync::mpsc::channel();
+        drop(rx);
+
+        assert!(!run_socket(&url, &tx).await.unwrap());
+    }
+}
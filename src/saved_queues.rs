@@ -0,0 +1,153 @@
+/* --------------------------
+Saved queues
+    - The queue can be saved as a new Jellyfin playlist or appended to an existing one
+    - It can also be saved locally under a name, in the `saved_queues` table. These are listed in the
+      global popup where they can be restored or deleted, also while offline
+    - Restoring replaces the queue and mpv's playlist. Tracks are looked up again, so downloads made
+      since are played from disk and offline the tracks that aren't downloaded are left out
+-------------------------- */
+
+use crate::database::database::{Command, UpdateCommand};
+use crate::database::extension::{
+    delete_saved_queue, get_saved_queue_songs, get_saved_queues, get_track, insert_saved_queue,
+};
+use crate::queue::make_track;
+use crate::tui::{App, Song};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedQueue {
+    pub id: i64,
+    pub name: String,
+    pub tracks: usize,
+    pub created_at: i64,
+}
+
+impl SavedQueue {
+    /// Shown in the popup, e.g. "Road trip (42 tracks, 3 Mar 14:05)"
+    ///
+    pub fn label(&self) -> String {
+        let saved = chrono::DateTime::from_timestamp(self.created_at, 0)
+            .map(|d| d.with_timezone(&chrono::Local).format(", %-d %b %H:%M").to_string())
+            .unwrap_or_default();
+        format!("{} ({} tracks{})", self.name, self.tracks, saved)
+    }
+}
+
+impl App {
+    fn queue_ids(&self) -> Vec<String> {
+        self.state.queue.iter().map(|s| s.id.clone()).collect()
+    }
+
+    /// Creates a private Jellyfin playlist holding the queue in its current order
+    ///
+    pub async fn save_queue_as_playlist(&self, name: &str) -> Result<usize, reqwest::Error> {
+        let Some(client) = self.client.as_ref() else {
+            return Ok(0);
+        };
+        let playlist_id = client.create_playlist(&name.to_string(), false).await?;
+        let ids = self.queue_ids();
+        client.add_to_playlist_chunked(&ids, &playlist_id).await?;
+        let _ = self.db.cmd_tx.send(Command::Update(UpdateCommand::Library)).await;
+        Ok(ids.len())
+    }
+
+    /// Appends the whole queue to an existing playlist
+    ///
+    pub async fn append_queue_to_playlist(
+        &mut self,
+        playlist_id: &String,
+    ) -> Result<usize, reqwest::Error> {
+        let Some(client) = self.client.as_ref() else {
+            return Ok(0);
+        };
+        let ids = self.queue_ids();
+        client.add_to_playlist_chunked(&ids, playlist_id).await?;
+        if let Some(playlist) = self.playlists.iter_mut().find(|p| p.id == *playlist_id) {
+            playlist.child_count += ids.len() as u64;
+        }
+        Ok(ids.len())
+    }
+
+    pub async fn saved_queues(&self) -> Vec<SavedQueue> {
+        get_saved_queues(&self.db.pool).await.unwrap_or_else(|e| {
+            log::error!("Failed to load saved queues: {}", e);
+            vec![]
+        })
+    }
+
+    pub async fn save_queue_locally(&self, name: &str) -> Result<(), sqlx::Error> {
+        insert_saved_queue(
+            &self.db.pool,
+            name,
+            &self.state.queue,
+            self.state.current_playback_state.current_index,
+        )
+        .await
+    }
+
+    pub async fn delete_saved_queue(&self, id: i64) -> Result<(), sqlx::Error> {
+        delete_saved_queue(&self.db.pool, id).await
+    }
+
+    /// Replaces the queue with a saved one and starts playing where it was when it was saved.
+    /// Returns how many tracks were restored and how many had to be left out
+    ///
+    pub async fn restore_saved_queue(&mut self, id: i64) -> Result<(usize, usize), sqlx::Error> {
+        let Some((saved, saved_index)) = get_saved_queue_songs(&self.db.pool, id).await? else {
+            return Ok((0, 0));
+        };
+        let total = saved.len();
+
+        let mut queue: Vec<Song> = Vec::with_capacity(total);
+        let mut index = 0;
+        for (i, song) in saved.into_iter().enumerate() {
+            // if the playing track is left out, the one after it plays instead
+            if i == saved_index {
+                index = queue.len();
+            }
+            let track = get_track(&self.db.pool, &song.id, self.client.as_ref()).await;
+            let restored = match (track, &self.client) {
+                (Ok(Some(track)), _) => Song {
+                    is_in_queue: song.is_in_queue,
+                    original_index: song.original_index,
+                    ..make_track(
+                        self.client.as_ref(),
+                        &self.downloads_dir,
+                        &track,
+                        song.is_in_queue,
+                        &self.transcoding,
+                    )
+                },
+                // not in the local library (anymore), the server may still have it
+                (_, Some(client)) => Song {
+                    url: client.song_url_sync(&song.id, &self.transcoding),
+                    is_transcoded: self.transcoding.enabled,
+                    ..song
+                },
+                (_, None) => continue,
+            };
+            queue.push(restored);
+        }
+
+        let restored = queue.len();
+        if restored == 0 {
+            return Ok((0, total));
+        }
+        self.remember_queue();
+        self.state.radio = None;
+        self.state.queue = queue;
+        let index = match self.start_new_queue(index.min(restored - 1)).await {
+            Ok(index) => index,
+            Err(e) => {
+                log::error!("Failed to start the saved queue: {}", e);
+                return Ok((0, total));
+            }
+        };
+        self.mpv_handle.play_index(index).await;
+        self.state.current_playback_state.current_index = index;
+        self.state.selected_queue_item.select(Some(index));
+
+        Ok((restored, total - restored))
+    }
+}
@@ -6,6 +6,7 @@ Search tab rendering
 -------------------------- */
 
 use crate::keyboard::*;
+use crate::selection::SelectionList;
 use crate::tui::App;
 
 use crate::helpers;
@@ -100,10 +101,12 @@ impl App {
             .iter()
             .map(|album| album.name.as_str())
             .collect::<Vec<&str>>();
+        let marked = self.marked_rows(SelectionList::SearchTracks);
         let tracks = self
             .search_result_tracks
             .iter()
-            .map(|track| {
+            .enumerate()
+            .map(|(i, track)| {
                 let title = format!("{} - {}", track.name, track.album);
                 // track.run_time_ticks is in microseconds
                 let seconds = (track.run_time_ticks / 10_000_000) % 60;
@@ -120,7 +123,11 @@ impl App {
                     time_span_text.push_str(" ♪");
                 }
 
-                if track.id == self.active_song_id {
+                if marked.contains(&i) {
+                    let mut time: Text = Text::from(Span::raw(title));
+                    time.push_span(Span::raw(time_span_text));
+                    ListItem::new(time).style(self.marked_style())
+                } else if track.id == self.active_song_id {
                     let mut time: Text = Text::from(Span::styled(
                         title,
                         Style::default().fg(self.theme.primary_color), // active title = primary
@@ -235,7 +242,12 @@ impl App {
                         .borders(Borders::ALL)
                         .border_style(self.theme.resolve(&self.theme.border_focused))
                         .border_type(self.border_type)
-                        .title("Tracks"),
+                        .title("Tracks")
+                        .title_bottom(
+                            self.selection_instructions(SelectionList::SearchTracks)
+                                .unwrap_or_default()
+                                .centered(),
+                        ),
                 )
                 .highlight_symbol(">>")
                 .highlight_style(
@@ -300,5 +312,7 @@ impl App {
             &mut self.state.search_track_scroll_state,
             &self.theme,
         );
+
+        self.create_popup(frame);
     }
 }
@@ -0,0 +1,417 @@
+/* --------------------------
+Visual selection
+    - Marks several rows of a track table (or the queue), `p` then opens a popup whose actions apply
+      to all of them at once
+    - `v` starts a range at the cursor and fixes it on the second press, `m` marks or unmarks a single
+      row, `Esc` drops the selection
+    - Rows are remembered by their position in the list as it's displayed. Anything that changes the
+      list (another artist, a search term, a queue change) quietly drops the selection
+-------------------------- */
+
+use crate::client::DiscographySong;
+use crate::database::database::{Command, DownloadCommand, RemoveCommand};
+use crate::database::extension::{set_favorite_track, DownloadStatus};
+use crate::keyboard::{search_ranked_refs, ActiveSection, ActiveTab, SearchSection};
+use crate::tui::App;
+use ratatui::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SelectionList {
+    Tracks,
+    AlbumTracks,
+    PlaylistTracks,
+    SearchTracks,
+    Queue,
+}
+
+#[derive(Debug, Default)]
+pub struct Selection {
+    list: Option<SelectionList>,
+    /// what the list looked like when the selection started, see `selection_context`
+    context: String,
+    /// start of the range that follows the cursor
+    anchor: Option<usize>,
+    marked: BTreeSet<usize>,
+}
+
+impl App {
+    /// The list a section of the current tab shows, if rows can be selected in it
+    ///
+    pub fn selection_list_for(&self, section: ActiveSection) -> Option<SelectionList> {
+        if self.state.active_tab == ActiveTab::Search {
+            return matches!(self.state.search_section, SearchSection::Tracks)
+                .then_some(SelectionList::SearchTracks);
+        }
+        if section == ActiveSection::Queue {
+            return Some(SelectionList::Queue);
+        }
+        match self.state.active_tab {
+            ActiveTab::Library if section == ActiveSection::Tracks => Some(SelectionList::Tracks),
+            ActiveTab::Albums if section == ActiveSection::Tracks => {
+                Some(SelectionList::AlbumTracks)
+            }
+            ActiveTab::Playlists if section == ActiveSection::Tracks => {
+                Some(SelectionList::PlaylistTracks)
+            }
+            _ => None,
+        }
+    }
+
+    fn selection_cursor(&self, list: SelectionList) -> Option<usize> {
+        match list {
+            SelectionList::Tracks => self.state.selected_track.selected(),
+            SelectionList::AlbumTracks => self.state.selected_album_track.selected(),
+            SelectionList::PlaylistTracks => self.state.selected_playlist_track.selected(),
+            SelectionList::SearchTracks => self.state.selected_search_track.selected(),
+            SelectionList::Queue => self.state.selected_queue_item.selected(),
+        }
+    }
+
+    fn selection_context(&self, list: SelectionList) -> String {
+        match list {
+            SelectionList::Tracks => format!(
+                "{}/{}/{}",
+                self.state.current_artist.id,
+                self.state.tracks_search_term,
+                self.tracks.len()
+            ),
+            SelectionList::AlbumTracks => format!(
+                "{}/{}/{}",
+                self.state.current_album.id,
+                self.state.album_tracks_search_term,
+                self.album_tracks.len()
+            ),
+            SelectionList::PlaylistTracks => format!(
+                "{}/{}/{}",
+                self.state.current_playlist.id,
+                self.state.playlist_tracks_search_term,
+                self.playlist_tracks.len()
+            ),
+            SelectionList::SearchTracks => {
+                format!("{}/{}", self.search_term_last, self.search_result_tracks.len())
+            }
+            SelectionList::Queue => {
+                self.state.queue.iter().map(|s| s.id.as_str()).collect::<Vec<&str>>().join(",")
+            }
+        }
+    }
+
+    /// Rows of a list that are part of the selection, including a range that is still open
+    ///
+    pub fn marked_rows(&self, list: SelectionList) -> BTreeSet<usize> {
+        let selection = &self.selection;
+        if selection.list != Some(list) || selection.context != self.selection_context(list) {
+            return BTreeSet::new();
+        }
+        let mut rows = selection.marked.clone();
+        if let (Some(anchor), Some(cursor)) = (selection.anchor, self.selection_cursor(list)) {
+            rows.extend(anchor.min(cursor)..=anchor.max(cursor));
+        }
+        rows
+    }
+
+    /// Makes sure the selection belongs to `list` as it looks now, starting over otherwise
+    ///
+    fn selection_for(&mut self, list: SelectionList) -> &mut Selection {
+        let context = self.selection_context(list);
+        if self.selection.list != Some(list) || self.selection.context != context {
+            self.selection = Selection { list: Some(list), context, ..Default::default() };
+        }
+        &mut self.selection
+    }
+
+    /// First press starts a range at the cursor, the second one keeps it
+    ///
+    pub fn visual_select(&mut self) {
+        let Some(list) = self.selection_list_for(self.state.active_section) else {
+            return;
+        };
+        let Some(cursor) = self.selection_cursor(list) else {
+            return;
+        };
+        let selection = self.selection_for(list);
+        match selection.anchor.take() {
+            Some(anchor) => selection.marked.extend(anchor.min(cursor)..=anchor.max(cursor)),
+            None => selection.anchor = Some(cursor),
+        }
+    }
+
+    pub fn toggle_mark(&mut self) {
+        let Some(list) = self.selection_list_for(self.state.active_section) else {
+            return;
+        };
+        let Some(cursor) = self.selection_cursor(list) else {
+            return;
+        };
+        let selection = self.selection_for(list);
+        if !selection.marked.remove(&cursor) {
+            selection.marked.insert(cursor);
+        }
+    }
+
+    /// Returns whether there was anything to clear
+    ///
+    pub fn clear_selection(&mut self) -> bool {
+        let had_selection = self.selection.anchor.is_some() || !self.selection.marked.is_empty();
+        self.selection = Selection::default();
+        had_selection
+    }
+
+    /// The list the selection was made in, when it's still valid and not empty
+    ///
+    pub fn active_selection(&self) -> Option<SelectionList> {
+        let list = self.selection.list?;
+        (!self.marked_rows(list).is_empty()).then_some(list)
+    }
+
+    /// Selected tracks in display order. Album headers in the library stand for their tracks
+    ///
+    pub fn selected_tracks(&self) -> Vec<DiscographySong> {
+        let Some(list) = self.active_selection() else {
+            return vec![];
+        };
+        let rows = self.marked_rows(list);
+        let (items, search_term) = match list {
+            SelectionList::Tracks => (&self.tracks, &self.state.tracks_search_term),
+            SelectionList::AlbumTracks => {
+                (&self.album_tracks, &self.state.album_tracks_search_term)
+            }
+            SelectionList::PlaylistTracks => {
+                (&self.playlist_tracks, &self.state.playlist_tracks_search_term)
+            }
+            SelectionList::SearchTracks => {
+                return rows
+                    .iter()
+                    .filter_map(|i| self.search_result_tracks.get(*i))
+                    .cloned()
+                    .collect();
+            }
+            SelectionList::Queue => return vec![],
+        };
+        let displayed = search_ranked_refs(items, search_term, true);
+
+        let mut seen = HashSet::new();
+        let mut tracks = vec![];
+        for track in rows.iter().filter_map(|i| displayed.get(*i)) {
+            let album_tracks: Vec<&DiscographySong> = match track.id.strip_prefix("_album_") {
+                Some(album_id) => items.iter().filter(|t| t.album_id == album_id).collect(),
+                None => vec![*track],
+            };
+            for track in album_tracks {
+                // playlists can hold a track more than once, every entry counts there
+                let key = if track.playlist_item_id.is_empty() {
+                    &track.id
+                } else {
+                    &track.playlist_item_id
+                };
+                if !track.id.starts_with("_album_") && seen.insert(key.clone()) {
+                    tracks.push(track.clone());
+                }
+            }
+        }
+        tracks
+    }
+
+    /// Ids of the selected tracks, also for the queue
+    ///
+    pub fn selected_track_ids(&self) -> Vec<String> {
+        match self.active_selection() {
+            Some(SelectionList::Queue) => self
+                .marked_rows(SelectionList::Queue)
+                .iter()
+                .filter_map(|i| self.state.queue.get(*i))
+                .map(|s| s.id.clone())
+                .collect(),
+            _ => self.selected_tracks().into_iter().map(|t| t.id).collect(),
+        }
+    }
+
+    /// Style of rows that are part of the selection
+    ///
+    pub fn marked_style(&self) -> Style {
+        Style::default()
+            .fg(self.theme.resolve(&self.theme.selected_inactive_foreground))
+            .bg(self.theme.resolve(&self.theme.selected_inactive_background))
+    }
+
+    /// Replaces the instructions at the bottom of a list while it has a selection
+    ///
+    pub fn selection_instructions(&self, list: SelectionList) -> Option<Line<'static>> {
+        let count = self.marked_rows(list).len();
+        if count == 0 {
+            return None;
+        }
+        Some(Line::from(vec![
+            format!(" {} selected ", count).fg(self.theme.resolve(&self.theme.section_title)),
+            " Actions ".fg(self.theme.resolve(&self.theme.section_title)),
+            "<p>".fg(self.theme.primary_color).bold(),
+            " Clear ".fg(self.theme.resolve(&self.theme.section_title)),
+            "<Esc> ".fg(self.theme.primary_color).bold(),
+        ]))
+    }
+
+    /// Favorites all selected tracks, or unfavorites them if they all are already
+    ///
+    pub async fn bulk_favorite(&mut self, favorite: bool) {
+        let Some(client) = self.client.as_ref() else {
+            return;
+        };
+        let ids = self.selected_track_ids();
+        // jellyfin has no endpoint for several items, so this is one request per track
+        for id in &ids {
+            let _ = client.set_favorite(id, favorite).await;
+            let _ = set_favorite_track(&self.db.pool, id, favorite).await;
+        }
+        let ids = ids.into_iter().collect::<HashSet<String>>();
+        for track in self
+            .tracks
+            .iter_mut()
+            .chain(self.album_tracks.iter_mut())
+            .chain(self.playlist_tracks.iter_mut())
+            .chain(self.search_result_tracks.iter_mut())
+            .filter(|t| ids.contains(&t.id))
+        {
+            track.user_data.is_favorite = favorite;
+        }
+        for song in self.state.queue.iter_mut().filter(|s| ids.contains(&s.id)) {
+            song.is_favorite = favorite;
+        }
+    }
+
+    pub async fn bulk_dislike(&mut self, disliked: bool) {
+        let ids = self.selected_track_ids().into_iter().collect::<HashSet<String>>();
+        for id in &ids {
+            let _ =
+                self.db.cmd_tx.send(Command::DislikeTrack { track_id: id.clone(), disliked }).await;
+        }
+        for track in self
+            .tracks
+            .iter_mut()
+            .chain(self.album_tracks.iter_mut())
+            .chain(self.playlist_tracks.iter_mut())
+            .chain(self.search_result_tracks.iter_mut())
+            .filter(|t| ids.contains(&t.id))
+        {
+            track.disliked = disliked;
+        }
+        for song in self.state.queue.iter_mut().filter(|s| ids.contains(&s.id)) {
+            song.disliked = disliked;
+        }
+    }
+
+    pub async fn bulk_download(&mut self) {
+        let tracks = self
+            .selected_tracks()
+            .into_iter()
+            .filter(|t| matches!(t.download_status, DownloadStatus::NotDownloaded))
+            .collect::<Vec<DiscographySong>>();
+        if tracks.is_empty() {
+            return;
+        }
+        // tracks downloaded from a playlist are linked to it, so they show up in it offline
+        if self.selection.list == Some(SelectionList::PlaylistTracks) {
+            for track in tracks {
+                let _ = self
+                    .db
+                    .cmd_tx
+                    .send(Command::Download(DownloadCommand::Track {
+                        track,
+                        playlist_id: Some(self.state.current_playlist.id.clone()),
+                    }))
+                    .await;
+            }
+            return;
+        }
+        let _ = self.db.cmd_tx.send(Command::Download(DownloadCommand::Tracks { tracks })).await;
+    }
+
+    pub async fn bulk_remove_download(&mut self) {
+        let tracks = self
+            .selected_tracks()
+            .into_iter()
+            .filter(|t| !matches!(t.download_status, DownloadStatus::NotDownloaded))
+            .collect::<Vec<DiscographySong>>();
+        if tracks.is_empty() {
+            return;
+        }
+        let ids = tracks.iter().map(|t| t.id.clone()).collect::<HashSet<String>>();
+        let _ = self.db.cmd_tx.send(Command::Remove(RemoveCommand::Tracks { tracks })).await;
+
+        for track in self
+            .tracks
+            .iter_mut()
+            .chain(self.album_tracks.iter_mut())
+            .chain(self.playlist_tracks.iter_mut())
+            .chain(self.search_result_tracks.iter_mut())
+            .filter(|t| ids.contains(&t.id))
+        {
+            track.download_status = DownloadStatus::NotDownloaded;
+        }
+        // offline only downloaded tracks are listed
+        if self.client.is_none() {
+            self.tracks.retain(|t| !ids.contains(&t.id));
+            self.album_tracks.retain(|t| !ids.contains(&t.id));
+            self.playlist_tracks.retain(|t| !ids.contains(&t.id));
+            for id in ids {
+                self.remove_from_queue_by_id(id).await;
+            }
+            let album_order = crate::helpers::extract_album_order(&self.tracks);
+            self.group_tracks_into_albums(self.tracks.clone(), Some(album_order));
+        }
+    }
+
+    /// Adds the selection to a playlist, returns how many tracks were added
+    ///
+    pub async fn bulk_add_to_playlist(
+        &mut self,
+        playlist_id: &String,
+    ) -> Result<usize, reqwest::Error> {
+        let Some(client) = self.client.as_ref() else {
+            return Ok(0);
+        };
+        let ids = self.selected_track_ids();
+        client.add_to_playlist_chunked(&ids, playlist_id).await?;
+        if let Some(playlist) = self.playlists.iter_mut().find(|p| p.id == *playlist_id) {
+            playlist.child_count += ids.len() as u64;
+        }
+        Ok(ids.len())
+    }
+
+    /// Removes the selected entries from the open playlist, returns how many were removed
+    ///
+    pub async fn bulk_remove_from_playlist(&mut self) -> Result<usize, reqwest::Error> {
+        let Some(client) = self.client.as_ref() else {
+            return Ok(0);
+        };
+        let entry_ids = self
+            .selected_tracks()
+            .into_iter()
+            .map(|t| if t.playlist_item_id.is_empty() { t.id } else { t.playlist_item_id })
+            .collect::<Vec<String>>();
+        let playlist_id = self.state.current_playlist.id.clone();
+        client.remove_from_playlist_chunked(&entry_ids, &playlist_id).await?;
+        let removed = entry_ids.into_iter().collect::<HashSet<String>>();
+        self.playlist_tracks
+            .retain(|t| !removed.contains(&t.playlist_item_id) && !removed.contains(&t.id));
+        Ok(removed.len())
+    }
+
+    pub async fn bulk_remove_from_queue(&mut self) {
+        let rows = self.marked_rows(SelectionList::Queue);
+        if rows.is_empty() {
+            return;
+        }
+        self.remember_queue();
+        for i in rows.into_iter().rev() {
+            if i < self.state.queue.len() {
+                self.mpv_handle.playlist_remove(i).await;
+                self.state.queue.remove(i);
+            }
+        }
+        let last = self.state.queue.len().saturating_sub(1);
+        let selected = self.state.selected_queue_item.selected().unwrap_or(0);
+        self.state.selected_queue_item.select(Some(selected.min(last)));
+    }
+}
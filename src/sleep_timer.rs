@@ -0,0 +1,136 @@
+/* --------------------------
+Sleep timer
+    - Pauses after a number of minutes, with the volume fading out over the last minute
+    - Or at a boundary: after the current track, the current album or a number of tracks
+    - Kept in the State, so it survives a restart. A timer that ran out while the app was closed is
+      simply dropped
+-------------------------- */
+
+use crate::mpv::SeekFlag;
+use crate::tui::{App, Song};
+use serde::{Deserialize, Serialize};
+
+/// The fade starts this many seconds before a timed sleep
+const SLEEP_FADE_SECS: i64 = 60;
+/// Label of the fade filter in mpv's filter chain
+const SLEEP_FILTER_LABEL: &str = "sleep";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SleepTimer {
+    At { deadline: i64 }, // unix timestamp
+    AfterTrack { track_id: String },
+    AfterAlbum { album_id: String },
+    AfterTracks { remaining: usize },
+}
+
+impl SleepTimer {
+    pub fn in_minutes(minutes: u64) -> Self {
+        SleepTimer::At { deadline: chrono::Utc::now().timestamp() + minutes as i64 * 60 }
+    }
+
+    /// Short description for the player and the popup
+    ///
+    pub fn label(&self) -> String {
+        match self {
+            SleepTimer::At { deadline } => {
+                let left = (deadline - chrono::Utc::now().timestamp()).max(0);
+                if left >= 3600 {
+                    format!("{}:{:02}:{:02}", left / 3600, left % 3600 / 60, left % 60)
+                } else {
+                    format!("{}:{:02}", left / 60, left % 60)
+                }
+            }
+            SleepTimer::AfterTrack { .. } => String::from("after this track"),
+            SleepTimer::AfterAlbum { .. } => String::from("after this album"),
+            SleepTimer::AfterTracks { remaining: 1 } => String::from("after 1 track"),
+            SleepTimer::AfterTracks { remaining } => format!("after {} tracks", remaining),
+        }
+    }
+}
+
+impl App {
+    pub async fn set_sleep_timer(&mut self, timer: Option<SleepTimer>) {
+        self.state.sleep_timer = timer;
+        self.reset_sleep_fade().await;
+        self.dirty = true;
+    }
+
+    pub fn sleep_after_track(&self) -> Option<SleepTimer> {
+        let song = self.state.queue.get(self.state.current_playback_state.current_index)?;
+        Some(SleepTimer::AfterTrack { track_id: song.id.clone() })
+    }
+
+    pub fn sleep_after_album(&self) -> Option<SleepTimer> {
+        let song = self.state.queue.get(self.state.current_playback_state.current_index)?;
+        Some(SleepTimer::AfterAlbum { album_id: song.album_id.clone() })
+    }
+
+    /// Called every loop, runs out timed sleeps and fades them out
+    ///
+    pub async fn check_sleep_timer(&mut self) {
+        let Some(SleepTimer::At { deadline }) = self.state.sleep_timer else {
+            return;
+        };
+        let left = deadline - chrono::Utc::now().timestamp();
+        if left <= 0 {
+            self.sleep_now().await;
+            return;
+        }
+        if left > SLEEP_FADE_SECS || self.paused {
+            return;
+        }
+
+        let level = left as f64 / SLEEP_FADE_SECS as f64;
+        match self.sleep_fade {
+            Some(last) if (last - level).abs() < 0.02 => {}
+            Some(_) => {
+                self.mpv_handle
+                    .audio_filter_command(SLEEP_FILTER_LABEL, "volume", format!("{:.3}", level))
+                    .await;
+                self.sleep_fade = Some(level);
+            }
+            None => {
+                self.mpv_handle
+                    .set_audio_filter(
+                        SLEEP_FILTER_LABEL,
+                        Some(format!("lavfi=[volume=volume={:.3}]", level)),
+                    )
+                    .await;
+                self.sleep_fade = Some(level);
+            }
+        }
+        // the remaining time is shown in the player
+        self.dirty = true;
+    }
+
+    /// Called on every song change, handles the boundary modes
+    ///
+    pub async fn sleep_timer_song_changed(&mut self, song: &Song) {
+        let sleep = match &mut self.state.sleep_timer {
+            Some(SleepTimer::AfterTrack { track_id }) => *track_id != song.id,
+            Some(SleepTimer::AfterAlbum { album_id }) => *album_id != song.album_id,
+            Some(SleepTimer::AfterTracks { remaining }) => {
+                *remaining = remaining.saturating_sub(1);
+                *remaining == 0
+            }
+            _ => false,
+        };
+        if sleep {
+            self.sleep_now().await;
+            // the next track already started, so it waits at its beginning
+            self.mpv_handle.seek(0.0, SeekFlag::Absolute).await;
+        }
+    }
+
+    async fn sleep_now(&mut self) {
+        log::info!("Sleep timer ran out, pausing");
+        self.pause().await;
+        self.set_sleep_timer(None).await;
+    }
+
+    async fn reset_sleep_fade(&mut self) {
+        if self.sleep_fade.take().is_some() {
+            self.mpv_handle.set_audio_filter(SLEEP_FILTER_LABEL, None).await;
+        }
+    }
+}
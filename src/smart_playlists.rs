@@ -0,0 +1,163 @@
+/* --------------------------
+Smart playlists
+    - Defined in the config as rules over the locally cached tracks, no server support needed
+    - Listed in the Playlists tab next to the server playlists and re-evaluated after every library sync
+    - Can be saved as a real Jellyfin playlist, which is then a plain snapshot of the current tracks
+-------------------------- */
+
+use crate::client::Playlist;
+use crate::database::extension::get_smart_playlist_tracks;
+use crate::tui::App;
+use serde::Deserialize;
+
+/// Smart playlists live in the same list as server playlists, this tells them apart
+pub const SMART_PLAYLIST_PREFIX: &str = "_smart_";
+
+pub fn is_smart_playlist(id: &str) -> bool {
+    id.starts_with(SMART_PLAYLIST_PREFIX)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SmartPlaylistOrder {
+    #[default]
+    RecentlyAdded,
+    MostPlayed,
+    LeastPlayed,
+    Name,
+    Album,
+    Random,
+}
+
+/// One entry of the `smart_playlists` config list. Every rule that is set has to match
+///
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SmartPlaylistRules {
+    pub name: String,
+    /// Any of these genres, case insensitive
+    pub genres: Vec<String>,
+    /// Any of these artists, case insensitive
+    pub artists: Vec<String>,
+    pub added_within_days: Option<u32>,
+    pub favorite: Option<bool>,
+    pub min_play_count: Option<u64>,
+    pub max_play_count: Option<u64>,
+    pub min_duration_secs: Option<u64>,
+    pub max_duration_secs: Option<u64>,
+    pub include_disliked: bool,
+    pub order: SmartPlaylistOrder,
+    pub limit: Option<usize>,
+}
+
+impl SmartPlaylistRules {
+    pub fn id(&self) -> String {
+        format!("{}{}", SMART_PLAYLIST_PREFIX, self.name)
+    }
+
+    /// Parses the `smart_playlists` list. Broken entries are skipped and reported in the returned errors
+    ///
+    pub fn from_config(config: &serde_yaml::Value) -> (Vec<Self>, Vec<String>) {
+        let mut rules: Vec<Self> = vec![];
+        let mut errors = vec![];
+
+        let Some(entries) = config.get("smart_playlists").and_then(|v| v.as_sequence()) else {
+            return (rules, errors);
+        };
+        for entry in entries {
+            match serde_yaml::from_value::<Self>(entry.clone()) {
+                Ok(rule) if rule.name.trim().is_empty() => {
+                    errors.push(String::from("a smart playlist is missing its name"));
+                }
+                Ok(rule) if rules.iter().any(|r| r.name == rule.name) => {
+                    errors.push(format!("smart playlist '{}' is defined twice", rule.name));
+                }
+                Ok(rule) => rules.push(rule),
+                Err(e) => errors.push(format!("invalid smart playlist: {}", e)),
+            }
+        }
+        (rules, errors)
+    }
+}
+
+impl App {
+    /// Re-evaluates every smart playlist. Called on startup and after the library was synchronized
+    ///
+    pub async fn refresh_smart_playlists(&mut self) {
+        let mut playlists = Vec::with_capacity(self.smart_playlist_rules.len());
+        for rules in &self.smart_playlist_rules {
+            let tracks = match get_smart_playlist_tracks(
+                &self.db.pool,
+                rules,
+                self.client.is_some(),
+            )
+            .await
+            {
+                Ok(tracks) => tracks,
+                Err(e) => {
+                    log::error!("Failed to evaluate smart playlist '{}': {}", rules.name, e);
+                    vec![]
+                }
+            };
+            playlists.push(Playlist {
+                name: rules.name.clone(),
+                id: rules.id(),
+                child_count: tracks.len() as u64,
+                run_time_ticks: tracks.iter().map(|t| t.run_time_ticks).sum(),
+                type_: String::from("SmartPlaylist"),
+                ..Default::default()
+            });
+        }
+        self.smart_playlists = playlists;
+        self.reorder_lists();
+
+        if is_smart_playlist(&self.state.current_playlist.id) {
+            let selected = self.state.selected_playlist_track.selected();
+            let id = self.state.current_playlist.id.clone();
+            self.smart_playlist(&id).await;
+            if let Some(index) = selected {
+                self.playlist_track_select_by_index(
+                    index.min(self.playlist_tracks.len().saturating_sub(1)),
+                );
+            }
+        }
+    }
+
+    /// Fills the playlist tracks from the rules of a smart playlist, the counterpart of `App::playlist`
+    ///
+    pub async fn smart_playlist(&mut self, id: &str) {
+        let Some(rules) = self.smart_playlist_rules.iter().find(|r| r.id() == id) else {
+            return;
+        };
+        self.playlist_incomplete = false;
+        self.playlist_stale = false;
+        self.playlist_tracks =
+            match get_smart_playlist_tracks(&self.db.pool, rules, self.client.is_some()).await {
+                Ok(tracks) => tracks,
+                Err(e) => {
+                    log::error!("Failed to evaluate smart playlist '{}': {}", rules.name, e);
+                    vec![]
+                }
+            };
+        self.state.current_playlist =
+            self.smart_playlists.iter().find(|p| p.id == id).cloned().unwrap_or_default();
+    }
+
+    /// Server playlists only, smart playlists can't hold tracks added by hand
+    ///
+    pub fn server_playlists(&self) -> Vec<Playlist> {
+        self.playlists.iter().filter(|p| !is_smart_playlist(&p.id)).cloned().collect()
+    }
+
+    /// Saves the current tracks of a smart playlist as a regular, private Jellyfin playlist
+    ///
+    pub async fn save_smart_playlist(&self, name: &str) -> Result<usize, reqwest::Error> {
+        let Some(client) = self.client.as_ref() else {
+            return Ok(0);
+        };
+        let playlist_id = client.create_playlist(&name.to_string(), false).await?;
+        let ids = self.playlist_tracks.iter().map(|t| t.id.clone()).collect::<Vec<String>>();
+        client.add_to_playlist_chunked(&ids, &playlist_id).await?;
+        Ok(ids.len())
+    }
+}
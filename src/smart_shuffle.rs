@@ -0,0 +1,266 @@
+/* --------------------------
+Smart shuffle
+    - An alternative to the plain random shuffle, picked in the global popup and kept in the preferences
+    - Spreads artists evenly over the queue, and the albums of each artist over that artist's slots.
+      A last pass makes sure the same artist never comes up twice in a row, unless one artist has
+      more than half of the tracks
+    - Tracks played recently (`last_played` in the tracks table) drift towards the end, optionally
+      favorites towards the front
+    - Only the order changes, `original_index` is untouched so unshuffling works the same
+-------------------------- */
+
+use crate::database::extension::get_last_played;
+use crate::tui::{App, Song};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// Tracks played within this window are pushed back, less so the longer ago they were played
+const RECENT_SECS: i64 = 3 * 24 * 60 * 60;
+/// How far back a track that just played is pushed, as a fraction of the queue
+const RECENT_PENALTY: f64 = 0.5;
+/// How far favorites are pulled forward when they get extra weight
+const FAVORITE_BONUS: f64 = 0.15;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum ShuffleMode {
+    #[default]
+    Random,
+    Smart,
+}
+
+/// Spreads `n` items evenly over 0..1, starting at a random offset and with some jitter
+///
+fn spread(n: usize, rng: &mut impl Rng) -> Vec<f64> {
+    let step = 1.0 / n as f64;
+    let offset = rng.random_range(0.0..step);
+    (0..n).map(|i| offset + i as f64 * step + rng.random_range(-0.1..0.1) * step).collect()
+}
+
+/// Groups by `key` (keeping the first-seen order) and interleaves the groups evenly
+///
+fn interleave<T>(items: Vec<T>, key: impl Fn(&T) -> String, rng: &mut impl Rng) -> Vec<(f64, T)> {
+    let mut groups: Vec<Vec<T>> = vec![];
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for item in items {
+        let i = *index.entry(key(&item)).or_insert_with(|| {
+            groups.push(vec![]);
+            groups.len() - 1
+        });
+        groups[i].push(item);
+    }
+    let mut placed = vec![];
+    for group in groups {
+        let positions = spread(group.len(), rng);
+        placed.extend(positions.into_iter().zip(group));
+    }
+    placed.sort_by(|a, b| a.0.total_cmp(&b.0));
+    placed
+}
+
+fn artist_key(song: &Song) -> String {
+    if song.artist.is_empty() {
+        song.id.clone()
+    } else {
+        song.artist.to_lowercase()
+    }
+}
+
+/// Keeps the order as far as possible, but takes the next track by another artist when the same
+/// one would play twice in a row. An artist left with more than half of the remaining tracks goes
+/// first, otherwise it would have to be doubled up at the end
+///
+fn separate_artists(songs: Vec<Song>) -> Vec<Song> {
+    let mut remaining: HashMap<String, usize> = HashMap::new();
+    for song in &songs {
+        *remaining.entry(artist_key(song)).or_default() += 1;
+    }
+    let mut rest: VecDeque<Song> = songs.into();
+    let mut ordered: Vec<Song> = Vec::with_capacity(rest.len());
+    while !rest.is_empty() {
+        let last = ordered.last().map(artist_key);
+        let crowding = remaining
+            .iter()
+            .find(|(artist, count)| Some(*artist) != last.as_ref() && **count * 2 > rest.len())
+            .map(|(artist, _)| artist.clone());
+        let next = match crowding {
+            Some(artist) => rest.iter().position(|s| artist_key(s) == artist),
+            None => rest.iter().position(|s| Some(artist_key(s)) != last),
+        };
+        let song = rest.remove(next.unwrap_or(0)).unwrap();
+        if let Some(count) = remaining.get_mut(&artist_key(&song)) {
+            *count -= 1;
+        }
+        ordered.push(song);
+    }
+    ordered
+}
+
+/// Orders `songs` so artists and their albums are spread out.
+/// `last_played` maps track ids to unix seconds, `now` is the same clock
+///
+pub fn smart_shuffle(
+    mut songs: Vec<Song>,
+    last_played: &HashMap<String, i64>,
+    now: i64,
+    favor_favorites: bool,
+) -> Vec<Song> {
+    let mut rng = rand::rng();
+    songs.shuffle(&mut rng);
+
+    let mut by_artist: HashMap<String, Vec<Song>> = HashMap::new();
+    let mut artists: Vec<String> = vec![];
+    for song in songs {
+        let key = artist_key(&song);
+        if !by_artist.contains_key(&key) {
+            artists.push(key.clone());
+        }
+        by_artist.entry(key).or_default().push(song);
+    }
+
+    let mut placed: Vec<(f64, Song)> = vec![];
+    for artist in artists {
+        let tracks = by_artist.remove(&artist).unwrap_or_default();
+        // alternate the albums of the artist, then give the artist evenly spaced slots
+        let tracks = interleave(tracks, |s: &Song| s.album_id.clone(), &mut rng);
+        let positions = spread(tracks.len(), &mut rng);
+        placed.extend(positions.into_iter().zip(tracks.into_iter().map(|(_, song)| song)));
+    }
+
+    for (position, song) in placed.iter_mut() {
+        if let Some(played) = last_played.get(&song.id) {
+            let age = (now - played).clamp(0, RECENT_SECS);
+            *position += RECENT_PENALTY * (1.0 - age as f64 / RECENT_SECS as f64);
+        }
+        if favor_favorites && song.is_favorite {
+            *position -= FAVORITE_BONUS;
+        }
+    }
+    placed.sort_by(|a, b| a.0.total_cmp(&b.0));
+    separate_artists(placed.into_iter().map(|(_, song)| song).collect())
+}
+
+impl App {
+    /// The shuffled order for the smart mode, looks up when the tracks were last played
+    ///
+    pub async fn smart_shuffled(&self, songs: Vec<Song>) -> Vec<Song> {
+        let ids = songs.iter().map(|s| s.id.clone()).collect::<Vec<String>>();
+        let last_played = get_last_played(&self.db.pool, &ids).await.unwrap_or_else(|e| {
+            log::error!("Failed to read when tracks were last played: {}", e);
+            HashMap::new()
+        });
+        smart_shuffle(
+            songs,
+            &last_played,
+            chrono::Utc::now().timestamp(),
+            self.preferences.smart_shuffle_favorites,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn song(id: usize, artist: &str, album: &str) -> Song {
+        Song {
+            id: id.to_string(),
+            name: format!("Track {}", id),
+            artist: artist.to_string(),
+            album_id: album.to_string(),
+            original_index: id as i64,
+            ..Default::default()
+        }
+    }
+
+    /// `counts` tracks for each artist, spread over two albums
+    ///
+    fn songs(counts: &[(&str, usize)]) -> Vec<Song> {
+        let mut songs = vec![];
+        for (artist, count) in counts {
+            for i in 0..*count {
+                let album = format!("{} {}", artist, i % 2);
+                songs.push(song(songs.len(), artist, &album));
+            }
+        }
+        songs
+    }
+
+    fn ids(songs: &[Song]) -> Vec<String> {
+        songs.iter().map(|s| s.id.clone()).collect()
+    }
+
+    fn doubled_artists(songs: &[Song]) -> usize {
+        songs.windows(2).filter(|w| w[0].artist == w[1].artist).count()
+    }
+
+    #[test]
+    fn is_a_permutation() {
+        let input = songs(&[("ABBA", 7), ("Queen", 3), ("", 4), ("Björk", 1)]);
+        for _ in 0..50 {
+            let mut shuffled = ids(&smart_shuffle(input.clone(), &HashMap::new(), 0, false));
+            shuffled.sort();
+            let mut expected = ids(&input);
+            expected.sort();
+            assert_eq!(shuffled, expected);
+        }
+        assert!(smart_shuffle(vec![], &HashMap::new(), 0, false).is_empty());
+    }
+
+    #[test]
+    fn no_artist_twice_in_a_row_when_avoidable() {
+        let even = songs(&[("ABBA", 4), ("Queen", 4), ("Björk", 4)]);
+        let uneven = songs(&[("ABBA", 7), ("Queen", 4), ("Björk", 2), ("Portishead", 1)]);
+        // only just possible, the crowded artist has to take every other slot
+        let crowded = songs(&[("ABBA", 5), ("Queen", 4)]);
+        for _ in 0..200 {
+            for input in [&even, &uneven, &crowded] {
+                let shuffled = smart_shuffle(input.clone(), &HashMap::new(), 0, false);
+                assert_eq!(doubled_artists(&shuffled), 0);
+            }
+        }
+        // when it can't be avoided, as few as possible
+        let impossible = songs(&[("ABBA", 5), ("Queen", 2)]);
+        for _ in 0..50 {
+            let shuffled = smart_shuffle(impossible.clone(), &HashMap::new(), 0, false);
+            assert_eq!(doubled_artists(&shuffled), 2);
+        }
+    }
+
+    #[test]
+    fn recently_played_tracks_move_towards_the_end() {
+        let now = 1_700_000_000;
+        // all by different artists, so nothing else moves them around
+        let input = (0..20).map(|i| song(i, &format!("Artist {}", i), "")).collect::<Vec<Song>>();
+        let played = (0..5).map(|i| (i.to_string(), now - 60)).collect::<HashMap<String, i64>>();
+        // a play older than the window doesn't count
+        let mut long_ago = played.clone();
+        long_ago.values_mut().for_each(|t| *t = now - RECENT_SECS - 1);
+
+        let average_position = |last_played: &HashMap<String, i64>| {
+            let mut total = 0;
+            for _ in 0..200 {
+                let shuffled = smart_shuffle(input.clone(), last_played, now, false);
+                total += shuffled.iter().position(|s| s.id == "0").unwrap();
+                total += shuffled.iter().position(|s| s.id == "4").unwrap();
+            }
+            total as f64 / 400.0
+        };
+        assert!(average_position(&played) > 13.0);
+        assert!((7.0..12.0).contains(&average_position(&long_ago)));
+    }
+
+    #[test]
+    fn original_index_is_kept_for_unshuffling() {
+        let input = songs(&[("ABBA", 6), ("Queen", 6), ("Björk", 3)]);
+        let last_played = HashMap::from([(String::from("2"), 10)]);
+        let mut shuffled = smart_shuffle(input.clone(), &last_played, 20, true);
+        for song in &shuffled {
+            assert_eq!(song.original_index, song.id.parse::<i64>().unwrap());
+        }
+        // what do_unshuffle sorts by
+        shuffled.sort_by_key(|s| s.original_index);
+        assert_eq!(ids(&shuffled), ids(&input));
+    }
+}
@@ -0,0 +1,271 @@
+/* --------------------------
+Listening statistics
+    - Every listened track is recorded in the `play_history` table when the song changes
+    - The Stats tab aggregates the history over a time window (week, month, all time)
+    - Everything is local, so it works the same offline
+-------------------------- */
+
+use crate::database::database::Command;
+use crate::database::extension::get_listening_stats;
+use crate::tui::{App, Song};
+use ratatui::{prelude::*, widgets::*, Frame};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Plays shorter than this are not recorded at all
+const MIN_LISTENED_SECS: f64 = 1.0;
+/// A play counts as complete after half the track or 4 minutes, same rule as last.fm
+const COMPLETE_AFTER_SECS: f64 = 240.0;
+
+#[derive(Debug, Clone)]
+pub struct PlayRecord {
+    pub track_id: String,
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    pub album_id: String,
+    pub played_at: i64,
+    pub listened_secs: f64,
+    pub duration_secs: f64,
+    pub skipped: bool,
+}
+
+impl PlayRecord {
+    pub fn start(song: &Song) -> Self {
+        Self {
+            track_id: song.id.clone(),
+            title: song.name.clone(),
+            artist: song.artist.clone(),
+            album: song.album.clone(),
+            album_id: song.album_id.clone(),
+            played_at: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+                as i64,
+            listened_secs: 0.0,
+            duration_secs: song.run_time_ticks as f64 / 10_000_000.0,
+            skipped: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum StatsWindow {
+    #[default]
+    Week,
+    Month,
+    AllTime,
+}
+
+impl StatsWindow {
+    pub fn title(&self) -> &'static str {
+        match self {
+            StatsWindow::Week => "Last 7 days",
+            StatsWindow::Month => "Last 30 days",
+            StatsWindow::AllTime => "All time",
+        }
+    }
+
+    /// Unix timestamp the window starts at
+    pub fn since(&self) -> i64 {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()) as i64;
+        match self {
+            StatsWindow::Week => now - 7 * 24 * 3600,
+            StatsWindow::Month => now - 30 * 24 * 3600,
+            StatsWindow::AllTime => 0,
+        }
+    }
+
+    fn cycle(self, forwards: bool) -> Self {
+        match (self, forwards) {
+            (StatsWindow::Week, true) | (StatsWindow::AllTime, false) => StatsWindow::Month,
+            (StatsWindow::Month, true) | (StatsWindow::Week, false) => StatsWindow::AllTime,
+            (StatsWindow::AllTime, true) | (StatsWindow::Month, false) => StatsWindow::Week,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct StatsEntry {
+    pub name: String,
+    pub artist: String,
+    pub plays: i64,
+    pub skips: i64,
+    pub listened_secs: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ListeningStats {
+    pub window: StatsWindow,
+    pub listened_secs: f64,
+    pub plays: i64,
+    pub skips: i64,
+    pub top_artists: Vec<StatsEntry>,
+    pub top_albums: Vec<StatsEntry>,
+    pub top_tracks: Vec<StatsEntry>,
+}
+
+fn skip_rate(plays: i64, skips: i64) -> String {
+    match plays + skips {
+        0 => String::from("-"),
+        total => format!("{:.0}%", skips as f64 / total as f64 * 100.0),
+    }
+}
+
+fn listened(secs: f64) -> String {
+    match secs {
+        s if s >= 3600.0 => format!("{:.1}h", s / 3600.0),
+        s => format!("{:.0}m", s / 60.0),
+    }
+}
+
+impl App {
+    /// Called on every song change. Closes the play of the previous song and starts tracking the new one
+    ///
+    pub async fn record_play(&mut self, song: &Song) {
+        if let Some(play) = self.finish_play() {
+            let _ = self.db.cmd_tx.send(Command::RecordPlay { play }).await;
+        }
+        if !song.id.is_empty() {
+            self.current_play = Some(PlayRecord::start(song));
+            self.last_history_position = self.state.current_playback_state.position;
+        }
+    }
+
+    /// Stops tracking the current play, returns it if it is worth recording
+    ///
+    pub fn finish_play(&mut self) -> Option<PlayRecord> {
+        let mut play = self.current_play.take()?;
+        if play.listened_secs < MIN_LISTENED_SECS {
+            return None;
+        }
+        play.skipped = play.listened_secs < COMPLETE_AFTER_SECS
+            && play.listened_secs < play.duration_secs * 0.5;
+        Some(play)
+    }
+
+    /// Adds the time played since the last call. Jumps (seeking) and paused time are ignored
+    ///
+    pub fn track_listening_time(&mut self) {
+        let position = self.state.current_playback_state.position;
+        let delta = position - self.last_history_position;
+        self.last_history_position = position;
+        // the position updates about once a second, at higher speeds that covers more of the track
+        let max_delta = 2.0 * self.state.current_playback_state.speed.max(1.0);
+        if self.paused || !(0.0..max_delta).contains(&delta) {
+            return;
+        }
+        if let Some(play) = self.current_play.as_mut() {
+            play.listened_secs += delta;
+        }
+    }
+
+    pub async fn refresh_stats(&mut self) {
+        let window = self.stats.window;
+        match get_listening_stats(&self.db.pool, window).await {
+            Ok(stats) => self.stats = stats,
+            Err(e) => log::error!("Failed to load listening stats: {}", e),
+        }
+    }
+
+    pub async fn cycle_stats_window(&mut self, forwards: bool) {
+        self.stats.window = self.stats.window.cycle(forwards);
+        self.refresh_stats().await;
+    }
+
+    pub fn render_stats(&mut self, app_container: Rect, frame: &mut Frame) {
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Length(3), Constraint::Min(3)])
+            .split(app_container);
+
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![
+                Constraint::Percentage(30),
+                Constraint::Percentage(30),
+                Constraint::Percentage(40),
+            ])
+            .split(layout[1]);
+
+        let foreground = self.theme.resolve(&self.theme.foreground);
+        let section_title_color = self.theme.resolve(&self.theme.section_title);
+        let stats = &self.stats;
+
+        let instructions = Line::from(vec![
+            " Week / Month / All time ".fg(foreground),
+            "<Tab>".fg(self.theme.primary_color).bold(),
+            " ".into(),
+        ]);
+        let summary = Paragraph::new(Line::from(vec![
+            format!("{:.1}", stats.listened_secs / 3600.0).fg(self.theme.primary_color).bold(),
+            " hours listened, ".fg(foreground),
+            stats.plays.to_string().fg(self.theme.primary_color).bold(),
+            " plays, ".fg(foreground),
+            stats.skips.to_string().fg(self.theme.primary_color).bold(),
+            " skipped (".fg(foreground),
+            skip_rate(stats.plays - stats.skips, stats.skips).fg(foreground),
+            ")".fg(foreground),
+        ]))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(self.border_type)
+                .border_style(self.theme.resolve(&self.theme.border))
+                .title(
+                    Line::from(format!("Stats - {}", stats.window.title())).fg(section_title_color),
+                )
+                .title_bottom(instructions.alignment(Alignment::Center)),
+        );
+        frame.render_widget(summary, layout[0]);
+
+        let tables = [
+            ("Top artists", &stats.top_artists, false),
+            ("Top albums", &stats.top_albums, true),
+            ("Top tracks", &stats.top_tracks, true),
+        ];
+        for ((title, entries, show_artist), area) in tables.into_iter().zip(columns.iter()) {
+            let rows = entries.iter().enumerate().map(|(i, entry)| {
+                let name = match show_artist {
+                    true => Line::from(vec![
+                        entry.name.clone().fg(foreground),
+                        format!(" - {}", entry.artist)
+                            .fg(self.theme.resolve(&self.theme.foreground_dim)),
+                    ]),
+                    false => Line::from(entry.name.clone().fg(foreground)),
+                };
+                Row::new(vec![
+                    Cell::from(format!("{}.", i + 1)),
+                    Cell::from(name),
+                    Cell::from(entry.plays.to_string()),
+                    Cell::from(skip_rate(entry.plays, entry.skips)),
+                    Cell::from(listened(entry.listened_secs)),
+                ])
+            });
+            let table = Table::new(
+                rows,
+                [
+                    Constraint::Length(4),
+                    Constraint::Fill(1),
+                    Constraint::Length(6),
+                    Constraint::Length(6),
+                    Constraint::Length(6),
+                ],
+            )
+            .header(
+                Row::new(vec!["#", "Name", "Plays", "Skips", "Time"])
+                    .style(Style::new().bold().fg(foreground)),
+            )
+            .style(
+                Style::default()
+                    .fg(foreground)
+                    .bg(self.theme.resolve_opt(&self.theme.background).unwrap_or(Color::Reset)),
+            )
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(self.border_type)
+                    .border_style(self.theme.resolve(&self.theme.border))
+                    .title(Line::from(title).fg(section_title_color)),
+            );
+            frame.render_widget(table, *area);
+        }
+    }
+}
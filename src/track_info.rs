@@ -0,0 +1,210 @@
+/* --------------------------
+Track info
+    - `i` (or "Track info" in a track popup) shows what we know about the track under the cursor, in
+      any track list, the search results or the queue
+    - Technical details come from the media source Jellyfin reports, the rest from the cached track
+    - Also tells whether the track would be direct played or transcoded with the current settings
+-------------------------- */
+
+use crate::client::{DiscographySong, MediaStream, DIRECT_PLAY_CONTAINERS};
+use crate::database::extension::{get_track, DownloadStatus};
+use crate::keyboard::{ActiveSection, Selectable};
+use crate::popup::PopupMenu;
+use crate::selection::SelectionList;
+use crate::tui::App;
+
+/// What the server caps streams at when transcoding without a configured bitrate
+const DEFAULT_MAX_BITRATE: u64 = 320_000;
+
+fn or_unknown(value: String) -> String {
+    if value.is_empty() {
+        "unknown".to_string()
+    } else {
+        value
+    }
+}
+
+impl App {
+    /// The track under the cursor of a section, album headers and empty lists have none
+    ///
+    fn track_id_in(&self, section: ActiveSection) -> Option<String> {
+        let id = match self.selection_list_for(section)? {
+            SelectionList::Tracks => self.get_id_of_selected(&self.tracks, Selectable::Track),
+            SelectionList::AlbumTracks => {
+                self.get_id_of_selected(&self.album_tracks, Selectable::AlbumTrack)
+            }
+            SelectionList::PlaylistTracks => {
+                self.get_id_of_selected(&self.playlist_tracks, Selectable::PlaylistTrack)
+            }
+            SelectionList::SearchTracks => self
+                .search_result_tracks
+                .get(self.state.selected_search_track.selected()?)?
+                .id
+                .clone(),
+            SelectionList::Queue => {
+                self.state.queue.get(self.state.selected_queue_item.selected()?)?.id.clone()
+            }
+        };
+        (!id.is_empty() && !id.starts_with("_album_")).then_some(id)
+    }
+
+    /// Opens the info popup for the track under the cursor of `section`
+    ///
+    pub async fn show_track_info(&mut self, section: ActiveSection) {
+        let Some(id) = self.track_id_in(section) else {
+            return;
+        };
+        // the database knows the download status best, the lists cover tracks it doesn't have
+        let track = match get_track(&self.db.pool, &id, self.client.as_ref()).await {
+            Ok(Some(track)) => Some(track),
+            _ => self
+                .tracks
+                .iter()
+                .chain(self.album_tracks.iter())
+                .chain(self.playlist_tracks.iter())
+                .chain(self.search_result_tracks.iter())
+                .find(|t| t.id == id)
+                .cloned(),
+        };
+        let Some(track) = track else {
+            return;
+        };
+
+        if self.state.active_section != ActiveSection::Popup {
+            self.state.last_section = self.state.active_section;
+            self.state.active_section = ActiveSection::Popup;
+        }
+        self.popup.global = false;
+        self.popup.current_menu = Some(PopupMenu::TrackInfo {
+            track_name: track.name.clone(),
+            rows: self.track_info_rows(&track),
+        });
+        self.popup.selected.select_last();
+    }
+
+    fn track_info_rows(&self, track: &DiscographySong) -> Vec<(String, String)> {
+        let source = track.media_sources.first();
+        let audio: Option<&MediaStream> = source
+            .and_then(|s| s.media_streams.iter().find(|m| m.type_ == "Audio"))
+            .or_else(|| source.and_then(|s| s.media_streams.first()));
+        let bitrate = audio.map(|a| a.bit_rate).filter(|b| *b > 0).or(source.map(|s| s.bitrate));
+
+        let artists = if track.artists.is_empty() {
+            track.album_artist.clone()
+        } else {
+            track.artists.join(", ")
+        };
+        let album = match track.production_year {
+            0 => track.album.clone(),
+            year => format!("{} ({})", track.album, year),
+        };
+        let seconds = track.run_time_ticks / 10_000_000;
+
+        let mut rows = vec![
+            ("Artist", artists),
+            ("Album", album),
+            ("Duration", format!("{}:{:02}", seconds / 60, seconds % 60)),
+            ("Container", or_unknown(source.map(|s| s.container.clone()).unwrap_or_default())),
+            ("Codec", or_unknown(audio.map(|a| a.codec.clone()).unwrap_or_default())),
+            (
+                "Bit depth",
+                audio
+                    .filter(|a| a.bit_depth > 0)
+                    .map_or("unknown".to_string(), |a| format!("{} bit", a.bit_depth)),
+            ),
+            (
+                "Sample rate",
+                audio.filter(|a| a.sample_rate > 0).map_or("unknown".to_string(), |a| {
+                    format!("{} kHz", a.sample_rate as f64 / 1000.0)
+                }),
+            ),
+            (
+                "Bitrate",
+                bitrate
+                    .filter(|b| *b > 0)
+                    .map_or("unknown".to_string(), |b| format!("{} kbps", b / 1000)),
+            ),
+            (
+                "Channels",
+                match audio {
+                    Some(a) if !a.channel_layout.is_empty() => {
+                        format!("{} ({})", a.channel_layout, a.channels)
+                    }
+                    Some(a) if a.channels > 0 => a.channels.to_string(),
+                    _ => "unknown".to_string(),
+                },
+            ),
+            (
+                "File size",
+                source.filter(|s| s.size > 0).map_or("unknown".to_string(), |s| {
+                    format!("{:.1} MB", s.size as f64 / 1024.0 / 1024.0)
+                }),
+            ),
+            ("Server path", or_unknown(source.map(|s| s.path.clone()).unwrap_or_default())),
+            (
+                "Date added",
+                chrono::DateTime::parse_from_rfc3339(&track.date_created)
+                    .map_or("unknown".to_string(), |d| d.format("%-d %b %Y").to_string()),
+            ),
+            ("Play count", track.user_data.play_count.to_string()),
+            (
+                "Normalization",
+                if track.normalization_gain == 0.0 {
+                    "none".to_string()
+                } else {
+                    format!("{:+.1} dB", track.normalization_gain)
+                },
+            ),
+            (
+                "Genres",
+                if track.genres.is_empty() { "none".into() } else { track.genres.join(", ") },
+            ),
+            ("Lyrics", if track.has_lyrics { "yes".into() } else { "no".into() }),
+        ];
+
+        let local_path =
+            self.downloads_dir.join(&track.server_id).join(&track.album_id).join(&track.id);
+        rows.push((
+            "Download",
+            match track.download_status {
+                DownloadStatus::Downloaded => format!("downloaded to {}", local_path.display()),
+                DownloadStatus::Downloading => "downloading".to_string(),
+                DownloadStatus::Queued => "queued".to_string(),
+                DownloadStatus::NotDownloaded => "not downloaded".to_string(),
+            },
+        ));
+        rows.push(("Playback", self.playback_method(track, bitrate)));
+
+        rows.into_iter().map(|(label, value)| (label.to_string(), value)).collect()
+    }
+
+    /// Mirrors what the universal stream endpoint does with the url `song_url_sync` builds
+    ///
+    fn playback_method(&self, track: &DiscographySong, bitrate: Option<u64>) -> String {
+        if matches!(track.download_status, DownloadStatus::Downloaded) {
+            return "direct play from the downloaded file".to_string();
+        }
+        if !self.transcoding.enabled {
+            return "direct play".to_string();
+        }
+        let max_bitrate = match self.transcoding.bitrate {
+            0 => DEFAULT_MAX_BITRATE,
+            kbps => kbps as u64 * 1000,
+        };
+        let container = track.media_sources.first().map(|s| s.container.to_lowercase());
+        let supported = container.is_some_and(|container| {
+            DIRECT_PLAY_CONTAINERS
+                .split(',')
+                .any(|entry| entry.split('|').next() == Some(container.as_str()))
+        });
+        if supported && bitrate.is_some_and(|b| b > 0 && b <= max_bitrate) {
+            format!("direct stream, within the {} kbps limit", max_bitrate / 1000)
+        } else {
+            format!(
+                "transcoded to {} at up to {} kbps",
+                self.transcoding.container,
+                max_bitrate / 1000
+            )
+        }
+    }
+}
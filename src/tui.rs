@@ -14,10 +14,11 @@ use crate::client::{
 use crate::database::extension::{
     get_album_tracks, get_albums_with_tracks, get_all_albums, get_all_artists, get_all_playlists,
     get_artists_with_tracks, get_discography, get_libraries, get_lyrics, get_playlist_tracks,
-    get_playlists_with_tracks, insert_lyrics,
+    get_playlists_with_tracks, insert_lyrics, insert_play_history,
 };
 use crate::helpers::{Preferences, State};
 use crate::popup::PopupState;
+use crate::queue_history::QueueHistory;
 use crate::{database, keyboard::*};
 use crate::{helpers, mpris, sort};
 
@@ -48,16 +49,26 @@ pub type Tui = Terminal<CrosstermBackend<Stdout>>;
 use std::sync::mpsc::{channel, Receiver};
 use std::sync::Arc;
 
-use crate::config::LyricsVisibility;
+use crate::ab_loop::AbLoop;
+use crate::audio_output::configured_audio_device;
+use crate::config::{LyricsVisibility, ReplayGain, SeekSteps, Transitions};
+use crate::dashboard::ShelfItem;
 use crate::database::database::{
     Command, DownloadCommand, DownloadItem, JellyfinCommand, UpdateCommand,
 };
-use crate::mpv::MpvHandle;
+use crate::equalizer::Equalizer;
+use crate::genres::Genre;
+use crate::ipc::IpcRequest;
+use crate::keybindings::KeyBindings;
+use crate::mpv::{MpvHandle, MPV_MAX_RESTARTS};
+use crate::remote::RemoteCommand;
+use crate::selection::Selection;
+use crate::smart_playlists::{is_smart_playlist, SmartPlaylistRules};
+use crate::stats::{ListeningStats, PlayRecord};
 use crate::themes::dialoguer::DialogTheme;
 use crate::themes::theme::Theme;
 use dialoguer::Select;
 use discord_rich_presence::activity::StatusDisplayType;
-use std::sync::atomic::Ordering;
 use std::{env, thread};
 use tokio::time::Instant;
 
@@ -69,6 +80,8 @@ pub struct MpvPlaybackState {
     pub duration: f64,
     pub current_index: usize,
     pub volume: i64,
+    #[serde(default = "MpvPlaybackState::default_speed")]
+    pub speed: f64,
     pub audio_bitrate: i64,
     pub audio_samplerate: i64,
     pub hr_channels: String,
@@ -81,6 +94,12 @@ pub struct MpvPlaybackState {
     pub idle_active: bool,
 }
 
+impl MpvPlaybackState {
+    fn default_speed() -> f64 {
+        1.0
+    }
+}
+
 impl Default for MpvPlaybackState {
     fn default() -> Self {
         MpvPlaybackState {
@@ -88,6 +107,7 @@ impl Default for MpvPlaybackState {
             duration: 0.0,
             current_index: 0,
             volume: 100,
+            speed: 1.0,
             audio_bitrate: 0,
             audio_samplerate: 0,
             file_format: String::from(""),
@@ -120,6 +140,8 @@ pub struct Song {
     pub run_time_ticks: u64,
     #[serde(default)]
     pub disliked: bool,
+    #[serde(default)]
+    pub normalization_gain: f64,
 }
 #[derive(Copy, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub enum Repeat {
@@ -157,6 +179,19 @@ pub enum Sort {
     TitleDesc,
 }
 
+/// Screen areas from the last rendered frame. Used to resolve mouse events
+#[derive(Default)]
+pub struct LayoutAreas {
+    pub tabs: Rect,
+    pub columns: [Rect; 3], // constraint_width_percentages_music columns
+    pub list: Rect,
+    pub tracks: Rect,
+    pub lyrics: Rect,
+    pub lyric_heights: Vec<usize>,
+    pub queue: Rect,
+    pub progress_bar: Rect,
+}
+
 pub struct DatabaseWrapper {
     pub pool: Arc<Pool<Sqlite>>,
     pub cmd_tx: mpsc::Sender<database::database::Command>,
@@ -170,10 +205,20 @@ pub struct App {
     pub dirty_clear: bool, // dirty flag for clearing the screen
     pub db_updating: bool, // flag to show if db is processing data
     pub transcoding: Transcoding,
+    pub replaygain: ReplayGain,
+    pub seek_steps: SeekSteps,
+    pub keybindings: KeyBindings,
+    pub smart_playlist_rules: Vec<SmartPlaylistRules>,
+    pub equalizer: Equalizer,
+    pub transitions: Transitions,
+    pub transitions_enabled: bool, // from the config, the global popup toggle overrides it
+    pub audio_device: String,      // output device mpv is playing to
 
     pub state: State,             // main persistent state
     pub preferences: Preferences, // user preferences
     pub server_id: String,
+    // undo/redo of queue changes, saved in a file of its own next to the state
+    pub queue_history: QueueHistory,
 
     pub music_libraries: Vec<LibraryView>, // all the libraries and whether they're enabled
 
@@ -190,6 +235,7 @@ pub struct App {
     pub original_artists: Vec<Artist>,     // all artists
     pub original_albums: Vec<Album>,       // all albums
     pub original_playlists: Vec<Playlist>, // playlists
+    pub smart_playlists: Vec<Playlist>,    // evaluated smart playlists, listed with the playlists
 
     pub artists: Vec<Artist>,                  // all artists
     pub albums: Vec<Album>,                    // all albums
@@ -197,6 +243,11 @@ pub struct App {
     pub playlists: Vec<Playlist>,              // playlists
     pub tracks: Vec<DiscographySong>,          // current artist's tracks
     pub playlist_tracks: Vec<DiscographySong>, // current playlist tracks
+    pub genres: Vec<Genre>,                    // all genres of the cached tracks
+    pub genre_tracks: Vec<DiscographySong>,    // current genre's tracks, ordered by album
+    pub shelves: [Vec<ShelfItem>; 5],          // home tab shelves, indexed by Shelf
+    pub dashboard_loading: bool,               // shelves are being fetched in the background
+    pub dashboard_stale: bool,                 // a refresh was asked for during the fetch
 
     pub lyrics: Option<(String, Vec<Lyric>, bool)>, // ID, lyrics, time_synced
     pub lyrics_visibility: LyricsVisibility,
@@ -224,6 +275,8 @@ pub struct App {
     pub search_term_last: String,
 
     pub locally_searching: bool,
+    pub genre_search_term: String, // the Genres tab searches without filtering, see genres.rs
+    pub selection: Selection,      // rows marked in visual mode
 
     // this means some new data has been fetched
     pub discography_stale: bool,
@@ -237,6 +290,11 @@ pub struct App {
     pub left_list_height: usize,
     pub track_list_height: usize,
 
+    // mouse support
+    pub layout_areas: LayoutAreas,
+    pub last_click: Option<(Instant, u16, u16)>, // for double click detection
+    pub dragged_border: Option<usize>,           // index of the column border being dragged
+
     pub search_result_artists: Vec<Artist>,
     pub search_result_albums: Vec<Album>,
     pub search_result_tracks: Vec<DiscographySong>,
@@ -252,12 +310,28 @@ pub struct App {
 
     // mpv is run in a separate thread, this is the handle
     pub mpv_handle: MpvHandle,
+    // when mpv dies it is recreated, these limit how often and show a notice afterwards
+    pub mpv_restart_attempts: u32,
+    pub mpv_last_restart: Option<Instant>,
+    pub sleep_fade: Option<f64>, // volume level of the sleep timer fade, if it started
+    pub ab_loop: AbLoop,
 
     pub song_changed: bool,
 
     pub mpris_paused: bool,
     pub mpris_active_song_id: String,
     pub(crate) mpris_rx: std::sync::mpsc::Receiver<MediaControlEvent>,
+    pub(crate) ipc_rx: mpsc::Receiver<IpcRequest>,
+    pub(crate) remote_rx: std::sync::mpsc::Receiver<RemoteCommand>,
+
+    // offline scrobble outbox
+    pub scrobbles_pending: i64,
+    pub scrobbles_replayed: usize,
+
+    // play history
+    pub stats: ListeningStats,
+    pub current_play: Option<PlayRecord>,
+    pub last_history_position: f64,
 
     pub window_title_enabled: bool,
     pub window_title_format: String,
@@ -357,6 +431,16 @@ impl App {
             }
         };
 
+        // remote control socket, see `jellyfin-tui ctl`
+        let (ipc_tx, ipc_rx) = mpsc::channel::<IpcRequest>(32);
+        crate::ipc::spawn_server(ipc_tx);
+
+        // jellyfin remote control, lets the web UI and other clients control this session
+        let (remote_tx, remote_rx) = channel::<RemoteCommand>();
+        if let Some(client) = &client {
+            crate::remote::spawn_session(Arc::clone(client), remote_tx);
+        }
+
         let preferences = Preferences::load().unwrap_or_else(|_| Preferences::new());
 
         let (theme, _, picker, user_themes, auto_color) =
@@ -370,6 +454,24 @@ impl App {
             println!(" ! The `always_show_lyrics` config option has been moved to lyrics: 'MODE'. The available modes are: 'always', 'never', 'auto'.");
         }
 
+        let keybindings = KeyBindings::from_config(&config);
+        for conflict in &keybindings.conflicts {
+            println!(" ! Keybinding conflict: {}", conflict);
+            log::warn!("Keybinding conflict: {}", conflict);
+        }
+        let (smart_playlist_rules, smart_playlist_errors) =
+            SmartPlaylistRules::from_config(&config);
+        for error in &smart_playlist_errors {
+            println!(" ! Smart playlists: {}", error);
+            log::warn!("Smart playlists: {}", error);
+        }
+        let (transitions, transitions_enabled) = Transitions::from_config(&config);
+        let (equalizer, equalizer_errors) = Equalizer::from_config(&config);
+        for error in &equalizer_errors {
+            println!(" ! Equalizer: {}", error);
+            log::warn!("Equalizer: {}", error);
+        }
+
         // discord presence starts only if a discord id is set in the config
         let discord = if let Some(discord_id) = config.get("discord").and_then(|d| d.as_u64()) {
             let show_art = config.get("discord_art").and_then(|d| d.as_bool()).unwrap_or_default();
@@ -416,7 +518,16 @@ impl App {
                     .unwrap_or(320),
                 container: config["transcoding"]["container"].as_str().unwrap_or("mp3").to_string(),
             },
+            replaygain: ReplayGain::from_config(&config),
+            seek_steps: SeekSteps::from_config(&config),
+            keybindings,
+            smart_playlist_rules,
+            equalizer,
+            transitions,
+            transitions_enabled,
+            audio_device: configured_audio_device(&config),
             state: State::new(),
+            queue_history: QueueHistory::default(),
             preferences,
             server_id,
 
@@ -441,6 +552,7 @@ impl App {
             original_artists,
             original_albums,
             original_playlists,
+            smart_playlists: vec![],
 
             artists: vec![],
             albums: vec![],
@@ -448,6 +560,11 @@ impl App {
             playlists: vec![],
             tracks: vec![],
             playlist_tracks: vec![],
+            genres: vec![],
+            genre_tracks: vec![],
+            shelves: Default::default(),
+            dashboard_loading: false,
+            dashboard_stale: false,
 
             lyrics: None,
             lyrics_visibility: config
@@ -485,6 +602,8 @@ impl App {
             search_term_last: String::from(""),
 
             locally_searching: false,
+            genre_search_term: String::new(),
+            selection: Selection::default(),
 
             discography_stale: client.is_some(),
             playlist_stale: client.is_some(),
@@ -497,6 +616,10 @@ impl App {
             left_list_height: 0,
             track_list_height: 0,
 
+            layout_areas: LayoutAreas::default(),
+            last_click: None,
+            dragged_border: None,
+
             search_result_artists: vec![],
             search_result_albums: vec![],
             search_result_tracks: vec![],
@@ -512,11 +635,24 @@ impl App {
             mpris_paused: true,
             mpris_active_song_id: String::from(""),
             mpris_rx,
+            ipc_rx,
+            remote_rx,
+
+            scrobbles_pending: 0,
+            scrobbles_replayed: 0,
+
+            stats: ListeningStats::default(),
+            current_play: None,
+            last_history_position: 0.0,
 
             window_title_enabled,
             window_title_format,
 
             mpv_handle,
+            mpv_restart_attempts: 0,
+            mpv_last_restart: None,
+            sleep_fade: None,
+            ab_loop: AbLoop::default(),
             song_changed: false,
 
             receiver,
@@ -695,6 +831,7 @@ impl App {
         self.artists = self.original_artists.clone();
         self.albums = self.original_albums.clone();
         self.playlists = self.original_playlists.clone();
+        self.playlists.extend(self.smart_playlists.iter().cloned());
 
         self.artists.sort_by(|a, b| {
             sort::compare(&a.name.to_ascii_lowercase(), &b.name.to_ascii_lowercase())
@@ -1052,6 +1189,7 @@ impl App {
     }
 
     pub async fn run<'a>(&mut self) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        self.supervise_mpv().await;
         // get playback state from the mpv thread
         let _ = self.receive_mpv_state().await;
         let current_song = self
@@ -1070,10 +1208,15 @@ impl App {
 
         self.handle_database_events().await?;
 
+        // whatever the next input does to the queue is one undo step
+        self.queue_history.end_step();
         self.handle_events().await?;
 
         self.handle_mpris_events().await;
+        self.handle_ipc_events().await;
+        self.handle_remote_events().await;
 
+        self.check_sleep_timer().await;
         self.handle_state_autosave();
 
         // update spinners (all are the same)
@@ -1116,6 +1259,49 @@ impl App {
                     .and_then(|v| v.as_str())
                     .map(LyricsVisibility::from_config)
                     .unwrap_or(LyricsVisibility::Always);
+                // applies to newly queued tracks
+                self.replaygain = ReplayGain::from_config(&new_config);
+                self.seek_steps = SeekSteps::from_config(&new_config);
+                self.keybindings = KeyBindings::from_config(&new_config);
+                if !self.keybindings.conflicts.is_empty()
+                    && self.state.active_section != ActiveSection::Popup
+                {
+                    self.state.last_section = self.state.active_section;
+                    self.state.active_section = ActiveSection::Popup;
+                    self.popup.global = true;
+                    self.set_generic_message(
+                        "Keybinding conflicts",
+                        &self.keybindings.conflicts.join("; "),
+                    );
+                }
+                let (smart_playlist_rules, smart_playlist_errors) =
+                    SmartPlaylistRules::from_config(&new_config);
+                self.smart_playlist_rules = smart_playlist_rules;
+                self.refresh_smart_playlists().await;
+                if !smart_playlist_errors.is_empty()
+                    && self.state.active_section != ActiveSection::Popup
+                {
+                    self.state.last_section = self.state.active_section;
+                    self.state.active_section = ActiveSection::Popup;
+                    self.popup.global = true;
+                    self.set_generic_message(
+                        "Smart playlist errors",
+                        &smart_playlist_errors.join("; "),
+                    );
+                }
+                (self.transitions, self.transitions_enabled) =
+                    Transitions::from_config(&new_config);
+                self.apply_transitions().await;
+                let (equalizer, equalizer_errors) = Equalizer::from_config(&new_config);
+                self.equalizer = equalizer;
+                self.apply_equalizer().await;
+                if !equalizer_errors.is_empty() && self.state.active_section != ActiveSection::Popup
+                {
+                    self.state.last_section = self.state.active_section;
+                    self.state.active_section = ActiveSection::Popup;
+                    self.popup.global = true;
+                    self.set_generic_message("Equalizer errors", &equalizer_errors.join("; "));
+                }
                 self.dirty = true;
             }
         }
@@ -1152,6 +1338,7 @@ impl App {
         playback.current_index = state.current_index;
         playback.duration = state.duration;
         playback.volume = state.volume;
+        playback.speed = state.speed;
 
         // Check if we should scrobble:
         // If new position is at the beginning (<= 3 seconds) and old position was at the end (>= 90% of duration)
@@ -1258,6 +1445,8 @@ impl App {
         &mut self,
         force: bool,
     ) -> Result<(), Box<dyn std::error::Error>> {
+        self.track_listening_time();
+
         let Some(current_song) =
             self.state.queue.get(self.state.current_playback_state.current_index)
         else {
@@ -1273,24 +1462,24 @@ impl App {
             self.scrobble_this =
                 (current_song.id.clone(), (playback.position * 10_000_000.0) as u64);
 
-            if self.client.is_some() {
-                let _ = self
-                    .db
-                    .cmd_tx
-                    .send(Command::Jellyfin(JellyfinCommand::ReportProgress {
-                        progress_report: ProgressReport {
-                            volume_level: playback.volume as u64,
-                            is_paused: self.paused,
-                            position_ticks: self.scrobble_this.1,
-                            media_source_id: self.active_song_id.clone(),
-                            playback_start_time_ticks: 0,
-                            can_seek: false,
-                            item_id: self.active_song_id.clone(),
-                            event_name: "timeupdate".into(),
-                        },
-                    }))
-                    .await;
-            }
+            // sent offline too, the database thread keeps the latest position of the play for later
+            let _ = self
+                .db
+                .cmd_tx
+                .send(Command::Jellyfin(JellyfinCommand::ReportProgress {
+                    progress_report: ProgressReport {
+                        volume_level: playback.volume as u64,
+                        is_paused: self.paused,
+                        playback_rate: playback.speed,
+                        position_ticks: self.scrobble_this.1,
+                        media_source_id: self.active_song_id.clone(),
+                        playback_start_time_ticks: 0,
+                        can_seek: false,
+                        item_id: self.active_song_id.clone(),
+                        event_name: "timeupdate".into(),
+                    },
+                }))
+                .await;
         } else if self.last_position_secs > playback.position {
             self.last_position_secs = playback.position;
         }
@@ -1307,7 +1496,10 @@ impl App {
             const LYRIC_EARLY_OFFSET_US: u64 = 2_500_000; // this is to show the lyric a bit earlier for better timing
             let current_time = self.state.current_playback_state.position;
             let current_time_us = (current_time * 10_000_000.0) as u64;
-            let effective_time = current_time_us.saturating_add(LYRIC_EARLY_OFFSET_US);
+            // the offset is wall clock time, at other speeds it covers more or less of the track
+            let speed = self.state.current_playback_state.speed;
+            let early_offset = (LYRIC_EARLY_OFFSET_US as f64 * speed) as u64;
+            let effective_time = current_time_us.saturating_add(early_offset);
             for (i, lyric) in lyrics.iter().enumerate() {
                 if lyric.start >= effective_time {
                     let index = if i == 0 { 0 } else { i - 1 };
@@ -1336,7 +1528,7 @@ impl App {
         let song_changed = song.id != self.active_song_id || self.song_changed;
         let should_scrobble = (self.should_scrobble || song_changed) && !self.paused;
 
-        if should_scrobble && self.client.is_some() {
+        if should_scrobble {
             self.should_scrobble = false;
 
             // Scrobble. The way to do scrobbling in jellyfin is using the last.fm jellyfin plugin.
@@ -1371,6 +1563,11 @@ impl App {
         self.active_song_id = song.id.clone();
         self.state.selected_lyric_manual_override = false;
 
+        self.record_play(song).await;
+        self.top_up_radio().await;
+        self.sleep_timer_song_changed(song).await;
+        self.clear_ab_loop().await;
+
         self.set_lyrics().await?;
         let _ = self
             .db
@@ -1459,6 +1656,9 @@ impl App {
         if let Err(e) = self.state.save(&self.server_id, self.client.is_none()) {
             log::error!(" ! Failed to autosave state: {}", e);
         }
+        if let Err(e) = self.queue_history.save(&self.server_id, self.client.is_none()) {
+            log::error!(" ! Failed to autosave the queue history: {}", e);
+        }
     }
 
     async fn set_lyrics(&mut self) -> Result<(), Box<dyn std::error::Error>> {
@@ -1662,10 +1862,16 @@ impl App {
             .constraints(vec![Constraint::Min(1), Constraint::Percentage(100)])
             .split(frame.area());
 
+        // areas get filled in by the render functions below
+        self.layout_areas = LayoutAreas { tabs: app_container[0], ..Default::default() };
+
         // render tabs
         self.render_tabs(app_container[0], frame.buffer_mut());
 
         match self.state.active_tab {
+            ActiveTab::Home => {
+                self.render_dashboard(app_container[1], frame);
+            }
             ActiveTab::Library => {
                 if self.show_help {
                     self.render_home_help(app_container[1], frame);
@@ -1690,6 +1896,12 @@ impl App {
             ActiveTab::Search => {
                 self.render_search(app_container[1], frame);
             }
+            ActiveTab::Stats => {
+                self.render_stats(app_container[1], frame);
+            }
+            ActiveTab::Genres => {
+                self.render_genres(app_container[1], frame);
+            }
         }
     }
 
@@ -1704,7 +1916,7 @@ impl App {
             ])
             .split(area);
 
-        Tabs::new(vec!["Library", "Albums", "Playlists", "Search"])
+        Tabs::new(TAB_TITLES.to_vec())
             .style(Style::default().fg(self.theme.resolve(&self.theme.tab_inactive_foreground)))
             .highlight_style(
                 Style::default().fg(self.theme.resolve(&self.theme.tab_active_foreground)),
@@ -1716,8 +1928,16 @@ impl App {
 
         let mut status_bar: Vec<Span> = vec![];
 
-        if self.mpv_handle.dead.load(Ordering::Relaxed) {
-            status_bar.push(Span::raw("please restart").fg(Color::Red));
+        if self.mpv_handle.is_dead() {
+            if self.mpv_restart_attempts >= MPV_MAX_RESTARTS {
+                status_bar.push(Span::raw("player failed, please restart").fg(Color::Red));
+            } else {
+                status_bar.push(Span::raw("restarting player").fg(Color::Red));
+            }
+        } else if self.mpv_last_restart.is_some_and(|t| t.elapsed() < Duration::from_secs(10)) {
+            status_bar.push(
+                Span::raw("player restarted").fg(self.theme.resolve(&self.theme.foreground_dim)),
+            );
         }
 
         match self.network_quality {
@@ -1901,6 +2121,15 @@ impl App {
     }
 
     pub async fn playlist(&mut self, album_id: &String, limit: Option<usize>) {
+        if is_smart_playlist(album_id) {
+            self.smart_playlist(album_id).await;
+            if !self.playlist_tracks.is_empty() {
+                self.state.active_section = ActiveSection::Tracks;
+            }
+            self.state.playlist_tracks_scroll_state =
+                ScrollbarState::new(self.playlist_tracks.len().saturating_sub(1));
+            return;
+        }
         self.playlist_incomplete = false;
         self.playlist_stale = false;
         let playlist = match self.playlists.iter().find(|a| a.id == *album_id).cloned() {
@@ -2115,7 +2344,7 @@ impl App {
         }
     }
 
-    pub fn save_state(&self) {
+    pub fn save_state(&mut self) {
         let persist = self.config.get("persist").and_then(|a| a.as_bool()).unwrap_or(true);
         if !persist {
             return;
@@ -2123,6 +2352,9 @@ impl App {
         if let Err(e) = self.state.save(&self.server_id, self.client.is_none()) {
             log::error!("[XX] Failed to save state This is most likely a bug: {:?}", e);
         }
+        if let Err(e) = self.queue_history.save(&self.server_id, self.client.is_none()) {
+            log::error!("Failed to save the queue history: {:?}", e);
+        }
     }
 
     pub async fn load_state(&mut self) -> std::result::Result<(), Box<dyn std::error::Error>> {
@@ -2134,9 +2366,12 @@ impl App {
         self.state.selected_album.select_first();
         self.state.selected_playlist.select_first();
 
+        self.refresh_smart_playlists().await;
+
         let persist = self.config.get("persist").and_then(|a| a.as_bool()).unwrap_or(true);
         if !persist {
             self.reorder_lists();
+            self.refresh_dashboard();
             return Ok(());
         }
 
@@ -2148,9 +2383,14 @@ impl App {
             Err(e) => {
                 log::warn!("Failed to load previous state. Starting fresh. Error: {:?}", e);
                 self.reorder_lists();
+                self.refresh_dashboard();
                 return Ok(());
             }
         }
+        match QueueHistory::load(&self.server_id, offline) {
+            Ok(history) => self.queue_history = history,
+            Err(e) => log::warn!("Failed to load the queue history: {:?}", e),
+        }
 
         let mut needs_repair = false;
         self.state.queue.retain(|song| match helpers::normalize_mpvsafe_url(&song.url) {
@@ -2175,11 +2415,22 @@ impl App {
             self.state.last_section = ActiveSection::List;
         }
 
+        match self.state.active_tab {
+            ActiveTab::Home => self.refresh_dashboard(),
+            ActiveTab::Stats => self.refresh_stats().await,
+            _ => {}
+        }
+        self.refresh_genres().await;
+        self.restore_audio_device().await;
+        self.apply_equalizer().await;
+        self.apply_transitions().await;
+
         // set the previous song as current
         if let Some(current_song) =
             self.state.queue.get(self.state.current_playback_state.current_index).cloned()
         {
             self.active_song_id = current_song.id.clone();
+            self.current_play = Some(PlayRecord::start(&current_song));
             let _ = self
                 .db
                 .cmd_tx
@@ -2235,15 +2486,21 @@ impl App {
             for song in &mut self.state.queue {
                 song.url = client.song_url_sync(&song.id, &self.transcoding);
             }
+            for song in self.queue_history.songs_mut() {
+                song.url = client.song_url_sync(&song.id, &self.transcoding);
+            }
         }
 
-        if let Err(e) = self.start_new_queue().await {
-            log::error!("Failed to initialize mpv queue at launch: {}", e);
+        let current_index = self.state.current_playback_state.current_index;
+        match self.start_new_queue(current_index).await {
+            Ok(index) => self.state.current_playback_state.current_index = index,
+            Err(e) => log::error!("Failed to initialize mpv queue at launch: {}", e),
         }
 
         self.mpv_handle.play_index(self.state.current_playback_state.current_index).await;
         self.mpv_handle.set_volume(self.state.current_playback_state.volume).await;
         self.mpv_handle.set_repeat(self.preferences.repeat).await;
+        self.apply_speed().await;
 
         self.pause().await;
 
@@ -2265,6 +2522,12 @@ impl App {
 
     pub async fn exit(&mut self) {
         self.save_state();
+        // written directly, the database thread will not get to it anymore
+        if let Some(play) = self.finish_play() {
+            if let Err(e) = insert_play_history(&self.db.pool, &play).await {
+                log::error!("Failed to record play: {}", e);
+            }
+        }
         if let Err(e) = self.preferences.save() {
             log::error!("Failed to save preferences: {:?}", e);
         }